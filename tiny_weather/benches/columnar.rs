@@ -0,0 +1,130 @@
+//! Compares the map-based `ForecastResponse` parse with the columnar parse
+//!
+//! Run with `cargo bench --bench columnar`. A counting global allocator
+//! reports the peak heap usage of each path on top of the raw body.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use tiny_weather::client::columnar;
+use tiny_weather::client::types::ForecastResponse;
+
+/// 92 past days plus 16 forecast days
+const HOURS: usize = (92 + 16) * 24;
+const VARIABLES: usize = 30;
+const LOCATIONS: usize = 10;
+const ITERATIONS: usize = 10;
+
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let current = ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK.fetch_max(current, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Run `f` repeatedly, returning the median time and the peak heap growth
+fn measure<T>(f: impl Fn() -> T) -> (Duration, usize) {
+    let mut times = Vec::with_capacity(ITERATIONS);
+    let mut peak = 0;
+    for _ in 0..ITERATIONS {
+        let baseline = ALLOCATED.load(Ordering::Relaxed);
+        PEAK.store(baseline, Ordering::Relaxed);
+
+        let start = Instant::now();
+        let result = black_box(f());
+        times.push(start.elapsed());
+        drop(result);
+
+        peak = peak.max(PEAK.load(Ordering::Relaxed) - baseline);
+    }
+    times.sort();
+    (times[ITERATIONS / 2], peak)
+}
+
+/// Build a multi-location JSON body like the forecast API returns
+fn body() -> Vec<u8> {
+    let locations: Vec<String> = (0..LOCATIONS)
+        .map(|location| {
+            let time: Vec<String> = (0..HOURS)
+                .map(|h| {
+                    format!(
+                        "\"2024-{:02}-{:02}T{:02}:00\"",
+                        1 + h / 720,
+                        1 + h / 24 % 30,
+                        h % 24
+                    )
+                })
+                .collect();
+            let mut hourly = vec![format!("\"time\":[{}]", time.join(","))];
+            for variable in 0..VARIABLES {
+                let values: Vec<String> = (0..HOURS)
+                    .map(|h| match (h + variable) % 97 {
+                        0 => "null".to_string(),
+                        n => format!("{:.1}", (n as f64 - 40.0) / 3.0),
+                    })
+                    .collect();
+                hourly.push(format!("\"variable_{}\":[{}]", variable, values.join(",")));
+            }
+            format!(
+                "{{\"latitude\":{},\"longitude\":13.4,\"elevation\":38.0,\"generationtime_ms\":1.5,\
+                 \"utc_offset_seconds\":0,\"timezone\":\"GMT\",\"timezone_abbreviation\":\"GMT\",\
+                 \"hourly\":{{{}}}}}",
+                50.0 + location as f64,
+                hourly.join(",")
+            )
+        })
+        .collect();
+    format!("[{}]", locations.join(",")).into_bytes()
+}
+
+fn main() {
+    let body = body();
+    println!(
+        "{} locations x {} hours x {} variables, {:.1} MiB body\n",
+        LOCATIONS,
+        HOURS,
+        VARIABLES,
+        body.len() as f64 / (1024.0 * 1024.0)
+    );
+
+    // Both parsers read the same borrowed body, so only the parse itself is measured
+    let (map_time, map_peak) =
+        measure(|| serde_json::from_slice::<Vec<ForecastResponse>>(&body).unwrap());
+    let (columnar_time, columnar_peak) = measure(|| columnar::parse(&body).unwrap());
+
+    let mib = |bytes: usize| bytes as f64 / (1024.0 * 1024.0);
+    println!("{:<28} {:>10} {:>12}", "parser", "time", "peak heap");
+    println!(
+        "{:<28} {:>10.2?} {:>9.1} MiB",
+        "ForecastResponse",
+        map_time,
+        mib(map_peak)
+    );
+    println!(
+        "{:<28} {:>10.2?} {:>9.1} MiB",
+        "columnar::parse",
+        columnar_time,
+        mib(columnar_peak)
+    );
+    println!(
+        "\n{:.1}x faster, {:.1}x less memory",
+        map_time.as_secs_f64() / columnar_time.as_secs_f64(),
+        map_peak as f64 / columnar_peak as f64
+    );
+}
@@ -0,0 +1,706 @@
+//! Aggregation of hourly series into days, weeks and custom periods
+//!
+//! Hourly values are grouped into windows of local time (using the response's
+//! `utc_offset_seconds`, so unix timestamps and ISO 8601 times group the same
+//! way) and reduced with an [`Aggregation`]. Missing values are handled by an
+//! explicit [`Gaps`] policy instead of silently shrinking the sample.
+//!
+//! Daily windows are labelled with the same `YYYY-MM-DD` dates as the API's
+//! [`DailyData`], so local rollups can be compared with (or stand in for) the
+//! API's own daily aggregates:
+//!
+//! ```no_run
+//! # async fn run() -> tiny_weather::client::Result<()> {
+//! use tiny_weather::aggregate::{Aggregation, Period, Rollup};
+//! use tiny_weather::client::ForecastClient;
+//! use tiny_weather::client::types::ForecastRequest;
+//!
+//! let request = ForecastRequest::new(52.52, 13.41)
+//!     .hourly(&["temperature_2m", "precipitation"]);
+//! let mut forecast = ForecastClient::new().get(request).await?;
+//!
+//! // Mean temperature of each 6-hour block and during working hours
+//! let blocks = Rollup::new(Period::Hours(6), Aggregation::Mean);
+//! let blocks = forecast.aggregate("temperature_2m", &blocks)?;
+//! let working = Period::HoursOfDay { start: 8, end: 18 };
+//! let office = forecast.aggregate("temperature_2m", &Rollup::new(working, Aggregation::Max))?;
+//!
+//! // Adds `precipitation_sum` to the daily data, like requesting it from the API
+//! forecast.add_daily_aggregates(&[("precipitation", Aggregation::Sum)])?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Datelike, Duration, NaiveDateTime, TimeDelta, Timelike, Utc};
+
+use crate::client::error::{OpenMeteoError, Result};
+use crate::client::types::forecast::{DailyData, ForecastResponse, HourlyData};
+use crate::frame::{local_date, parse_time};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const TIME_FORMAT: &str = "%Y-%m-%dT%H:%M";
+
+/// Windows of local time that hourly values are grouped into
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Period {
+    /// Calendar days
+    Daily,
+    /// Weeks starting on Monday, labelled with the Monday
+    Weekly,
+    /// Consecutive blocks of this many hours, aligned to local midnight when they divide a day
+    Hours(u32),
+    /// The same hours of every day, from `start` (inclusive) to `end` (exclusive)
+    ///
+    /// `HoursOfDay { start: 8, end: 18 }` covers working hours. When `start` is
+    /// after `end` the window spans midnight and is labelled with the day it starts.
+    HoursOfDay { start: u32, end: u32 },
+    /// Arbitrary windows of local time, each from start (inclusive) to end (exclusive)
+    ///
+    /// Every window gets a result, in the given order, even if no values fall in it.
+    Windows(Vec<(NaiveDateTime, NaiveDateTime)>),
+}
+
+impl Period {
+    fn validate(&self) -> Result<()> {
+        let valid = match self {
+            Self::Daily | Self::Weekly => true,
+            Self::Hours(hours) => *hours > 0,
+            Self::HoursOfDay { start, end } => *start < 24 && *end <= 24 && start != end,
+            Self::Windows(windows) => windows.iter().all(|(start, end)| start < end),
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(OpenMeteoError::InvalidParameter(format!(
+                "invalid aggregation period: {:?}",
+                self
+            )))
+        }
+    }
+
+    /// Start and end of the window containing a local time, if any
+    ///
+    /// Not used for [`Period::Windows`], which may overlap.
+    fn window(&self, local: NaiveDateTime) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        let midnight = local.date().and_time(Default::default());
+        match self {
+            Self::Daily => Some((midnight, midnight + Duration::days(1))),
+            Self::Weekly => {
+                let monday =
+                    midnight - Duration::days(local.weekday().num_days_from_monday().into());
+                Some((monday, monday + Duration::weeks(1)))
+            }
+            Self::Hours(hours) => {
+                let length = i64::from(*hours) * 3600;
+                let seconds = local.and_utc().timestamp();
+                let start = NaiveDateTime::default()
+                    + Duration::seconds(seconds.div_euclid(length) * length);
+                Some((start, start + Duration::seconds(length)))
+            }
+            Self::HoursOfDay { start, end } => {
+                let at = |day: NaiveDateTime, hour: u32| day + Duration::hours(hour.into());
+                let hour = local.hour();
+                if start < end {
+                    (*start..*end)
+                        .contains(&hour)
+                        .then(|| (at(midnight, *start), at(midnight, *end)))
+                } else if hour >= *start {
+                    Some((at(midnight, *start), at(midnight + Duration::days(1), *end)))
+                } else if hour < *end {
+                    Some((at(midnight - Duration::days(1), *start), at(midnight, *end)))
+                } else {
+                    None
+                }
+            }
+            Self::Windows(_) => None,
+        }
+    }
+
+    /// Label of a window starting at `start`
+    fn label(&self, start: NaiveDateTime) -> String {
+        match self {
+            Self::Daily | Self::Weekly | Self::HoursOfDay { .. } => {
+                start.format(DATE_FORMAT).to_string()
+            }
+            Self::Hours(_) | Self::Windows(_) => start.format(TIME_FORMAT).to_string(),
+        }
+    }
+}
+
+/// Reduction applied to the values of a window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Aggregation {
+    Sum,
+    Mean,
+    Min,
+    Max,
+    /// Percentile from 0 to 100, linearly interpolated between values
+    Percentile(f64),
+}
+
+impl Aggregation {
+    /// Suffix of the daily variable holding this aggregation, as used by the API
+    /// (e.g., `max` for `temperature_2m_max`); percentiles use `p<n>`
+    pub fn suffix(self) -> String {
+        match self {
+            Self::Sum => "sum".to_string(),
+            Self::Mean => "mean".to_string(),
+            Self::Min => "min".to_string(),
+            Self::Max => "max".to_string(),
+            Self::Percentile(percentile) => format!("p{}", percentile),
+        }
+    }
+
+    fn validate(self) -> Result<()> {
+        match self {
+            Self::Percentile(percentile) if !(0.0..=100.0).contains(&percentile) => Err(
+                OpenMeteoError::InvalidParameter(format!("invalid percentile: {}", percentile)),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Reduce a non-empty set of values
+    fn apply(self, values: &mut [f64]) -> f64 {
+        match self {
+            Self::Sum => values.iter().sum(),
+            Self::Mean => values.iter().sum::<f64>() / values.len() as f64,
+            Self::Min => values.iter().copied().fold(f64::INFINITY, f64::min),
+            Self::Max => values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            Self::Percentile(percentile) => {
+                values.sort_by(f64::total_cmp);
+                let rank = percentile / 100.0 * (values.len() - 1) as f64;
+                let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+                values[lower] + (values[upper] - values[lower]) * (rank - lower as f64)
+            }
+        }
+    }
+}
+
+/// How missing values (`None` or absent time steps) affect a window
+///
+/// A window's expected number of values is its length divided by the series'
+/// time step, so windows cut short by the start or end of the forecast count
+/// as incomplete too.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Gaps {
+    /// Aggregate the values that are present; `None` only if there are none
+    #[default]
+    Skip,
+    /// `None` if any value is missing
+    Strict,
+    /// `None` unless at least this fraction (0–1) of the values is present
+    MinCoverage(f64),
+}
+
+impl Gaps {
+    fn accepts(self, present: usize, expected: usize) -> bool {
+        match self {
+            Self::Skip => present > 0,
+            Self::Strict => present > 0 && present >= expected,
+            Self::MinCoverage(fraction) => {
+                present > 0 && present as f64 >= fraction * expected as f64
+            }
+        }
+    }
+}
+
+/// Period, aggregation and gap policy of a rollup
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rollup {
+    pub period: Period,
+    pub aggregation: Aggregation,
+    pub gaps: Gaps,
+}
+
+impl Rollup {
+    /// Create a rollup that skips missing values
+    pub fn new(period: Period, aggregation: Aggregation) -> Self {
+        Self {
+            period,
+            aggregation,
+            gaps: Gaps::default(),
+        }
+    }
+
+    /// Set how missing values are handled
+    pub fn gaps(mut self, gaps: Gaps) -> Self {
+        self.gaps = gaps;
+        self
+    }
+}
+
+/// One value per window, labelled with the window's local date or start time
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeriodSeries<T = f64> {
+    /// `YYYY-MM-DD` for days, weeks and hours of day, `YYYY-MM-DDTHH:MM` otherwise
+    pub time: Vec<String>,
+    pub values: Vec<Option<T>>,
+}
+
+/// Hourly values of one window
+struct Window {
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    indices: Vec<usize>,
+}
+
+impl HourlyData {
+    /// Aggregate an hourly variable over a period
+    ///
+    /// `utc_offset_seconds` is the response's offset of local times from UTC.
+    pub fn aggregate(
+        &self,
+        variable: &str,
+        rollup: &Rollup,
+        utc_offset_seconds: i32,
+    ) -> Result<PeriodSeries> {
+        rollup.aggregation.validate()?;
+        self.reduce(variable, rollup, utc_offset_seconds, |values| {
+            let mut values: Vec<f64> = values.iter().map(|(_, value)| *value).collect();
+            rollup.aggregation.apply(&mut values)
+        })
+    }
+
+    /// Time of the highest value of an hourly variable in each window
+    ///
+    /// Ties resolve to the earliest time; times are returned as in `self.time`.
+    /// The rollup's aggregation is ignored.
+    pub fn argmax(
+        &self,
+        variable: &str,
+        rollup: &Rollup,
+        utc_offset_seconds: i32,
+    ) -> Result<PeriodSeries<String>> {
+        self.reduce(variable, rollup, utc_offset_seconds, |values| {
+            let (index, _) = values
+                .iter()
+                .copied()
+                .reduce(|best, next| if next.1 > best.1 { next } else { best })
+                .expect("windows passed to reduce are non-empty");
+            self.time[index].clone()
+        })
+    }
+
+    /// Group a variable into windows and reduce the present `(index, value)` pairs
+    /// of each window that passes the gap policy
+    fn reduce<T>(
+        &self,
+        variable: &str,
+        rollup: &Rollup,
+        utc_offset_seconds: i32,
+        reduce: impl Fn(&[(usize, f64)]) -> T,
+    ) -> Result<PeriodSeries<T>> {
+        let values = self.variables.get(variable).ok_or_else(|| {
+            OpenMeteoError::InvalidParameter(format!("no hourly variable {}", variable))
+        })?;
+        let windows = self.windows(&rollup.period, utc_offset_seconds)?;
+        let step = self.step(utc_offset_seconds)?;
+
+        let mut series = PeriodSeries {
+            time: Vec::with_capacity(windows.len()),
+            values: Vec::with_capacity(windows.len()),
+        };
+        for window in windows {
+            let present: Vec<(usize, f64)> = window
+                .indices
+                .iter()
+                .filter_map(|i| values.get(*i).copied().flatten().map(|value| (*i, value)))
+                .collect();
+            let expected = ((window.end - window.start).num_seconds() / step.num_seconds()).max(1);
+
+            series.time.push(rollup.period.label(window.start));
+            series.values.push(
+                rollup
+                    .gaps
+                    .accepts(present.len(), expected as usize)
+                    .then(|| reduce(&present)),
+            );
+        }
+
+        Ok(series)
+    }
+
+    /// Local times of the series
+    fn local_times(&self, utc_offset_seconds: i32) -> Result<Vec<NaiveDateTime>> {
+        let offset = Duration::seconds(utc_offset_seconds.into());
+        self.time
+            .iter()
+            .map(|time| Ok((parse_time(time, utc_offset_seconds)? + offset).naive_utc()))
+            .collect()
+    }
+
+    /// Time step of the series, one hour if it has fewer than two values
+    fn step(&self, utc_offset_seconds: i32) -> Result<TimeDelta> {
+        let step = match self.time.get(..2) {
+            Some([first, second]) => {
+                parse_time(second, utc_offset_seconds)? - parse_time(first, utc_offset_seconds)?
+            }
+            _ => Duration::hours(1),
+        };
+        if step <= TimeDelta::zero() {
+            return Err(OpenMeteoError::InvalidResponse(
+                "hourly times are not increasing".to_string(),
+            ));
+        }
+        Ok(step)
+    }
+
+    fn windows(&self, period: &Period, utc_offset_seconds: i32) -> Result<Vec<Window>> {
+        period.validate()?;
+        let times = self.local_times(utc_offset_seconds)?;
+
+        if let Period::Windows(windows) = period {
+            return Ok(windows
+                .iter()
+                .map(|(start, end)| Window {
+                    start: *start,
+                    end: *end,
+                    indices: (0..times.len())
+                        .filter(|i| (*start..*end).contains(&times[*i]))
+                        .collect(),
+                })
+                .collect());
+        }
+
+        let mut windows: BTreeMap<NaiveDateTime, Window> = BTreeMap::new();
+        for (i, time) in times.iter().enumerate() {
+            let Some((start, end)) = period.window(*time) else {
+                continue;
+            };
+            windows
+                .entry(start)
+                .or_insert_with(|| Window {
+                    start,
+                    end,
+                    indices: Vec::new(),
+                })
+                .indices
+                .push(i);
+        }
+        Ok(windows.into_values().collect())
+    }
+}
+
+/// Daily columns computed from hourly data but not yet added to a response
+pub(crate) struct DailyAggregates {
+    /// Dates of the daily rows, used when the response has no daily data
+    time: Vec<String>,
+    /// Name, unit and values of each column
+    columns: Vec<(String, Option<String>, Vec<Option<f64>>)>,
+}
+
+impl ForecastResponse {
+    /// Aggregate an hourly variable over a period, in the response's local time
+    pub fn aggregate(&self, variable: &str, rollup: &Rollup) -> Result<PeriodSeries> {
+        self.require_hourly()?
+            .aggregate(variable, rollup, self.utc_offset_seconds)
+    }
+
+    /// Add daily columns aggregated from hourly variables
+    ///
+    /// Each `(variable, aggregation)` pair becomes a `<variable>_<suffix>` column
+    /// (e.g., `precipitation_sum`), aligned on the local dates of the existing daily
+    /// rows or, without daily data, on the dates of the hourly series. Missing values
+    /// are skipped, and columns already returned by the API are kept. Nothing is
+    /// added if any column fails.
+    pub fn add_daily_aggregates(&mut self, columns: &[(&str, Aggregation)]) -> Result<()> {
+        let hourly = self.require_hourly()?;
+        let aggregates = self.daily_aggregates(hourly, self.hourly_units.as_ref(), columns)?;
+        self.insert_daily_aggregates(aggregates);
+        Ok(())
+    }
+
+    /// Aggregate hourly variables into columns aligned on this response's daily rows
+    ///
+    /// `hourly` may hold variables that are not in the response yet, so callers can
+    /// finish every fallible step before changing it.
+    pub(crate) fn daily_aggregates(
+        &self,
+        hourly: &HourlyData,
+        hourly_units: Option<&HashMap<String, String>>,
+        columns: &[(&str, Aggregation)],
+    ) -> Result<DailyAggregates> {
+        let offset = self.utc_offset_seconds;
+        let mut computed = Vec::with_capacity(columns.len());
+        for (variable, aggregation) in columns {
+            let rollup = Rollup::new(Period::Daily, *aggregation);
+            let series = hourly.aggregate(variable, &rollup, offset)?;
+            let by_date: HashMap<DateTime<Utc>, Option<f64>> = local_dates(&series.time, offset)?
+                .into_iter()
+                .zip(series.values.iter().copied())
+                .collect();
+            let name = format!("{}_{}", variable, aggregation.suffix());
+            let unit = hourly_units.and_then(|units| units.get(*variable)).cloned();
+            computed.push((name, unit, series.time, by_date));
+        }
+
+        // Daily times are unix timestamps with `timeformat=unixtime`, so rows are
+        // matched on their local date rather than on the label
+        let rows = match &self.daily {
+            Some(daily) => local_dates(&daily.time, offset)?,
+            None => match computed.first() {
+                Some((_, _, time, _)) => local_dates(time, offset)?,
+                None => Vec::new(),
+            },
+        };
+        Ok(DailyAggregates {
+            time: computed
+                .first()
+                .map(|(_, _, time, _)| time.clone())
+                .unwrap_or_default(),
+            columns: computed
+                .into_iter()
+                .map(|(name, unit, _, by_date)| {
+                    let values = rows
+                        .iter()
+                        .map(|date| by_date.get(date).copied().flatten())
+                        .collect();
+                    (name, unit, values)
+                })
+                .collect(),
+        })
+    }
+
+    /// Add columns from [`ForecastResponse::daily_aggregates`], keeping existing ones
+    pub(crate) fn insert_daily_aggregates(&mut self, aggregates: DailyAggregates) {
+        let daily = self.daily.get_or_insert_with(|| DailyData {
+            time: aggregates.time,
+            variables: HashMap::new(),
+            time_variables: HashMap::new(),
+        });
+        let units = self.daily_units.get_or_insert_with(HashMap::new);
+        for (name, unit, values) in aggregates.columns {
+            if daily.variables.contains_key(&name) {
+                continue;
+            }
+            daily.variables.insert(name.clone(), values);
+            if let Some(unit) = unit {
+                units.entry(name).or_insert(unit);
+            }
+        }
+    }
+
+    fn require_hourly(&self) -> Result<&HourlyData> {
+        self.hourly.as_ref().ok_or_else(|| {
+            OpenMeteoError::InvalidParameter("aggregation requires hourly data".to_string())
+        })
+    }
+}
+
+/// Local date of each time, as midnight UTC
+fn local_dates(time: &[String], utc_offset_seconds: i32) -> Result<Vec<DateTime<Utc>>> {
+    time.iter()
+        .map(|time| {
+            Ok(local_date(
+                parse_time(time, utc_offset_seconds)?,
+                utc_offset_seconds,
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::types::forecast::fixtures;
+
+    /// Two days of hourly values 0, 1, 2, ... starting at local midnight
+    fn hourly(hours: usize) -> HourlyData {
+        let start = NaiveDateTime::parse_from_str("2024-01-01T00:00", TIME_FORMAT).unwrap();
+        HourlyData {
+            time: (0..hours)
+                .map(|h| {
+                    (start + Duration::hours(h as i64))
+                        .format(TIME_FORMAT)
+                        .to_string()
+                })
+                .collect(),
+            variables: HashMap::from([(
+                "temperature_2m".to_string(),
+                (0..hours).map(|h| Some(h as f64)).collect(),
+            )]),
+        }
+    }
+
+    fn aggregate(hourly: &HourlyData, period: Period, aggregation: Aggregation) -> PeriodSeries {
+        hourly
+            .aggregate("temperature_2m", &Rollup::new(period, aggregation), 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_daily_aggregations() {
+        let hourly = hourly(48);
+        let max = aggregate(&hourly, Period::Daily, Aggregation::Max);
+        assert_eq!(max.time, vec!["2024-01-01", "2024-01-02"]);
+        assert_eq!(max.values, vec![Some(23.0), Some(47.0)]);
+
+        let sum = aggregate(&hourly, Period::Daily, Aggregation::Sum);
+        assert_eq!(sum.values, vec![Some(276.0), Some(852.0)]);
+        let mean = aggregate(&hourly, Period::Daily, Aggregation::Mean);
+        assert_eq!(mean.values, vec![Some(11.5), Some(35.5)]);
+        let min = aggregate(&hourly, Period::Daily, Aggregation::Min);
+        assert_eq!(min.values, vec![Some(0.0), Some(24.0)]);
+        let median = aggregate(&hourly, Period::Daily, Aggregation::Percentile(50.0));
+        assert_eq!(median.values, vec![Some(11.5), Some(35.5)]);
+        let p90 = aggregate(&hourly, Period::Daily, Aggregation::Percentile(90.0));
+        assert!((p90.values[0].unwrap() - 20.7).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_custom_windows() {
+        let hourly = hourly(48);
+        let blocks = aggregate(&hourly, Period::Hours(6), Aggregation::Max);
+        assert_eq!(blocks.time.len(), 8);
+        assert_eq!(blocks.time[1], "2024-01-01T06:00");
+        assert_eq!(blocks.values[1], Some(11.0));
+
+        let working = Period::HoursOfDay { start: 8, end: 18 };
+        let office = aggregate(&hourly, working, Aggregation::Min);
+        assert_eq!(office.time, vec!["2024-01-01", "2024-01-02"]);
+        assert_eq!(office.values, vec![Some(8.0), Some(32.0)]);
+
+        // Overnight windows belong to the evening they start on
+        let night = aggregate(
+            &hourly,
+            Period::HoursOfDay { start: 22, end: 6 },
+            Aggregation::Max,
+        );
+        assert_eq!(night.time, vec!["2023-12-31", "2024-01-01", "2024-01-02"]);
+        assert_eq!(night.values, vec![Some(5.0), Some(29.0), Some(47.0)]);
+
+        let weekly = aggregate(&hourly, Period::Weekly, Aggregation::Sum);
+        assert_eq!(weekly.time, vec!["2024-01-01"]);
+        assert_eq!(weekly.values, vec![Some(1128.0)]);
+
+        let at = |text| NaiveDateTime::parse_from_str(text, TIME_FORMAT).unwrap();
+        let windows = Period::Windows(vec![
+            (at("2024-01-01T10:00"), at("2024-01-01T12:00")),
+            (at("2024-03-01T00:00"), at("2024-03-02T00:00")),
+        ]);
+        let custom = aggregate(&hourly, windows, Aggregation::Sum);
+        assert_eq!(custom.time, vec!["2024-01-01T10:00", "2024-03-01T00:00"]);
+        assert_eq!(custom.values, vec![Some(21.0), None]);
+    }
+
+    #[test]
+    fn test_utc_offset() {
+        // Unix timestamps from 2024-01-01T00:00Z, in a UTC+2 timezone
+        let hourly = HourlyData {
+            time: (0..24)
+                .map(|h| (1_704_067_200 + h * 3600).to_string())
+                .collect(),
+            variables: HashMap::from([(
+                "temperature_2m".to_string(),
+                (0..24).map(|h| Some(h as f64)).collect(),
+            )]),
+        };
+        let rollup = Rollup::new(Period::Daily, Aggregation::Max);
+        let daily = hourly.aggregate("temperature_2m", &rollup, 7200).unwrap();
+        assert_eq!(daily.time, vec!["2024-01-01", "2024-01-02"]);
+        assert_eq!(daily.values, vec![Some(21.0), Some(23.0)]);
+
+        let argmax = hourly.argmax("temperature_2m", &rollup, 7200).unwrap();
+        assert_eq!(argmax.values[0].as_deref(), Some("1704142800"));
+    }
+
+    #[test]
+    fn test_gap_policies() {
+        let mut hourly = hourly(36);
+        hourly.variables.get_mut("temperature_2m").unwrap()[3] = None;
+        let rollup = |gaps| Rollup::new(Period::Daily, Aggregation::Max).gaps(gaps);
+        let max = |gaps| {
+            hourly
+                .aggregate("temperature_2m", &rollup(gaps), 0)
+                .unwrap()
+                .values
+        };
+
+        assert_eq!(max(Gaps::Skip), vec![Some(23.0), Some(35.0)]);
+        // The first day misses one hour, the second is cut short after 12 hours
+        assert_eq!(max(Gaps::Strict), vec![None, None]);
+        assert_eq!(max(Gaps::MinCoverage(0.9)), vec![Some(23.0), None]);
+        assert_eq!(max(Gaps::MinCoverage(0.5)), vec![Some(23.0), Some(35.0)]);
+    }
+
+    #[test]
+    fn test_invalid_rollups() {
+        let hourly = hourly(24);
+        let invalid = [
+            Rollup::new(Period::Hours(0), Aggregation::Sum),
+            Rollup::new(Period::HoursOfDay { start: 8, end: 8 }, Aggregation::Sum),
+            Rollup::new(Period::Daily, Aggregation::Percentile(120.0)),
+        ];
+        for rollup in invalid {
+            let error = hourly.aggregate("temperature_2m", &rollup, 0).unwrap_err();
+            assert!(matches!(error, OpenMeteoError::InvalidParameter(_)));
+        }
+        let rollup = Rollup::new(Period::Daily, Aggregation::Sum);
+        assert!(hourly.aggregate("precipitation", &rollup, 0).is_err());
+    }
+
+    #[test]
+    fn test_add_daily_aggregates_matches_api() {
+        let mut response = fixtures::forecast(serde_json::json!({
+            "hourly": {
+                "time": ["2024-01-01T00:00", "2024-01-01T12:00", "2024-01-02T00:00"],
+                "temperature_2m": [1.0, 5.0, -2.0],
+                "precipitation": [0.2, 0.0, null]
+            },
+            "hourly_units": {"temperature_2m": "°C", "precipitation": "mm"},
+            "daily": {
+                "time": ["2024-01-01", "2024-01-02"],
+                "temperature_2m_max": [5.0, -2.0]
+            }
+        }));
+
+        response
+            .add_daily_aggregates(&[
+                ("temperature_2m", Aggregation::Max),
+                ("temperature_2m", Aggregation::Min),
+                ("precipitation", Aggregation::Sum),
+            ])
+            .unwrap();
+
+        let daily = response.daily.as_ref().unwrap();
+        assert_eq!(
+            daily.variables["temperature_2m_max"],
+            vec![Some(5.0), Some(-2.0)]
+        );
+        assert_eq!(
+            daily.variables["temperature_2m_min"],
+            vec![Some(1.0), Some(-2.0)]
+        );
+        assert_eq!(daily.variables["precipitation_sum"], vec![Some(0.2), None]);
+        assert_eq!(
+            response.daily_units.as_ref().unwrap()["precipitation_sum"],
+            "mm"
+        );
+    }
+
+    #[test]
+    fn test_add_daily_aggregates_unixtime() {
+        // 2024-01-01T00:00, 12:00 and 2024-01-02T00:00 in UTC+1, daily rows at local midnight
+        let mut response = fixtures::forecast(serde_json::json!({
+            "utc_offset_seconds": 3600,
+            "hourly": {
+                "time": ["1704063600", "1704106800", "1704150000"],
+                "precipitation": [0.2, 0.3, 1.0]
+            },
+            "daily": {"time": ["1704063600", "1704150000"]}
+        }));
+
+        response
+            .add_daily_aggregates(&[("precipitation", Aggregation::Sum)])
+            .unwrap();
+
+        let daily = response.daily.as_ref().unwrap();
+        assert_eq!(
+            daily.variables["precipitation_sum"],
+            vec![Some(0.5), Some(1.0)]
+        );
+    }
+}
@@ -1,15 +1,454 @@
-use serde::{Deserialize, Serialize};
-use clap::Parser;
+use chrono::NaiveDate;
+use clap::{Args, Parser, Subcommand};
 
-#[derive(Debug, Parser, Clone, PartialEq, Eq, Hash)]
+use crate::client::types::{UnitSystem, WeatherModel};
+use crate::output::{OutputFormat, Theme};
+
+pub use crate::config::TinyWeatherConfig;
+
+/// Weather forecasts and history from Open-Meteo
+#[derive(Debug, Parser, Clone, PartialEq)]
+#[command(name = "tiny_weather", version)]
 pub struct TinyWeatherArgs {
-    // Flatten the config into the args
+    #[command(subcommand)]
+    pub command: Command,
+
+    /// Output format: table, json, ndjson, csv or yaml
+    #[arg(short, long, global = true, default_value_t)]
+    pub output: OutputFormat,
+
+    /// Weather code style in tables: plain, emoji or nerd
+    #[arg(long, global = true)]
+    pub theme: Option<Theme>,
+
+    /// Choose again among the matches of an ambiguous place name
+    #[arg(long, global = true)]
+    pub choose: bool,
+}
+
+#[derive(Debug, Subcommand, Clone, PartialEq)]
+pub enum Command {
+    /// Search for locations by name
+    Search(SearchArgs),
+    /// Current conditions
+    Now(NowArgs),
+    /// Hour-by-hour forecast
+    Hourly(ForecastArgs),
+    /// Day-by-day forecast
+    Daily(ForecastArgs),
+    /// Observed weather between two dates
+    History(HistoryArgs),
+    /// Sun and moon times, computed offline without a forecast request
+    Astro(AstroArgs),
+    /// Manage saved locations, usable by alias wherever a location is accepted
+    #[command(subcommand, alias = "fav")]
+    Favorite(FavoriteCommand),
+    /// Show or change the saved settings
+    #[command(subcommand)]
+    Config(ConfigCommand),
+}
+
+#[derive(Debug, Subcommand, Clone, PartialEq, Eq)]
+pub enum FavoriteCommand {
+    /// Look up a location and save it under an alias
+    Add {
+        /// Name to save the location under (e.g., "home")
+        alias: String,
+        /// Place name, coordinates, geohash, plus code or postal code to look up
+        location: String,
+        /// Replace an existing favorite with the same alias
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// List saved locations
+    List,
+    /// Forget a saved location
+    Remove { alias: String },
+    /// Save a location under a new alias
+    Rename { from: String, to: String },
+}
+
+#[derive(Debug, Subcommand, Clone, PartialEq, Eq)]
+pub enum ConfigCommand {
+    /// Print the location of the config file
+    Path,
+    /// Print one setting, or every setting with its environment override (with
+    /// the API key masked)
+    Get {
+        /// Setting such as `units` or `cache.forecast_ttl`
+        key: Option<String>,
+    },
+    /// Change a setting, or remove it when no value is given
+    Set {
+        /// Setting such as `units` or `cache.forecast_ttl`
+        key: String,
+        /// New value; lists are comma-separated
+        value: Option<String>,
+    },
+    /// Open the config file in $VISUAL or $EDITOR
+    Edit,
+}
+
+#[derive(Debug, Args, Clone, PartialEq, Eq)]
+pub struct SearchArgs {
+    /// Place name or postal code
+    pub name: String,
+
+    /// Maximum number of results
+    #[arg(short, long, default_value_t = 10)]
+    pub count: u8,
+
+    /// Language of the returned names (e.g., "de"); defaults to the configured one
+    #[arg(long)]
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Args, Clone, PartialEq)]
+pub struct NowArgs {
+    /// Place name, alias, coordinates (e.g., "52.52,13.41"), geohash, plus code or
+    /// postal code with country (e.g., "10115 DE"); defaults to the configured location
+    pub location: Option<String>,
+
+    #[command(flatten)]
+    pub options: RequestOptions,
+
+    #[command(flatten)]
+    pub diff: DiffOptions,
+}
+
+#[derive(Debug, Args, Clone, PartialEq)]
+pub struct ForecastArgs {
+    /// Place name, alias, coordinates (e.g., "52.52,13.41"), geohash, plus code or
+    /// postal code with country (e.g., "10115 DE"); defaults to the configured location
+    pub location: Option<String>,
+
+    #[command(flatten)]
+    pub options: RequestOptions,
+
+    /// Number of forecast days
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=16))]
+    pub days: Option<u8>,
+
     #[command(flatten)]
-    pub config: TinyWeatherConfig,
+    pub diff: DiffOptions,
+}
+
+/// Comparison with the previous forecast, for the commands that fetch one
+#[derive(Debug, Args, Clone, PartialEq)]
+pub struct DiffOptions {
+    /// Print how the forecast changed since the previous run
+    #[arg(long = "diff")]
+    pub enabled: bool,
+
+    /// Smallest change of a variable reported by `--diff` (e.g.,
+    /// "temperature_2m=2"); repeatable or comma-separated
+    #[arg(
+        long,
+        value_name = "VARIABLE=CHANGE",
+        value_parser = parse_threshold,
+        value_delimiter = ',',
+        requires = "enabled"
+    )]
+    pub threshold: Vec<(String, f64)>,
+}
+
+/// Parse a `variable=change` threshold
+fn parse_threshold(value: &str) -> Result<(String, f64), String> {
+    let (variable, change) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected VARIABLE=CHANGE, got '{}'", value))?;
+    let change: f64 = change
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid change '{}' for {}", change, variable))?;
+    if !change.is_finite() || change < 0.0 {
+        return Err(format!(
+            "change for {} must be a non-negative number",
+            variable
+        ));
+    }
+    Ok((variable.trim().to_string(), change))
 }
 
-#[derive(Debug, Deserialize, Serialize, Parser, Clone, PartialEq, Eq, Hash)]
-pub struct TinyWeatherConfig {
-    #[arg(short, long)] 
-    pub location: String,
-}
\ No newline at end of file
+#[derive(Debug, Args, Clone, PartialEq)]
+pub struct HistoryArgs {
+    /// Place name, alias, coordinates (e.g., "52.52,13.41"), geohash, plus code or
+    /// postal code with country (e.g., "10115 DE"); defaults to the configured location
+    pub location: Option<String>,
+
+    #[command(flatten)]
+    pub options: RequestOptions,
+
+    /// First day (YYYY-MM-DD)
+    #[arg(long)]
+    pub from: NaiveDate,
+
+    /// Last day, inclusive (YYYY-MM-DD)
+    #[arg(long)]
+    pub to: NaiveDate,
+}
+
+#[derive(Debug, Args, Clone, PartialEq, Eq)]
+pub struct AstroArgs {
+    /// Place name, alias, coordinates (e.g., "52.52,13.41"), geohash, plus code or
+    /// postal code with country (e.g., "10115 DE"); defaults to the configured location
+    pub location: Option<String>,
+
+    /// First day (YYYY-MM-DD); defaults to today
+    #[arg(long)]
+    pub date: Option<NaiveDate>,
+
+    /// Number of days
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..=366))]
+    pub days: u16,
+}
+
+/// Options shared by every command that fetches weather
+#[derive(Debug, Args, Clone, PartialEq)]
+pub struct RequestOptions {
+    /// Unit system: metric, imperial or aviation; defaults to the configured one
+    #[arg(short, long)]
+    pub units: Option<UnitSystem>,
+
+    /// Variables to fetch instead of the defaults (comma-separated)
+    #[arg(long, value_delimiter = ',')]
+    pub variables: Vec<String>,
+
+    /// Weather models to use instead of the best match (comma-separated)
+    #[arg(short, long, value_delimiter = ',')]
+    pub model: Vec<WeatherModel>,
+
+    /// Language of place names and weather descriptions (e.g., "de"); defaults to
+    /// the configured one
+    #[arg(long)]
+    pub language: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_forecast_command() {
+        let args = TinyWeatherArgs::try_parse_from([
+            "tiny_weather",
+            "hourly",
+            "Berlin",
+            "--units",
+            "imperial",
+            "--days",
+            "2",
+            "--variables",
+            "temperature_2m,precipitation",
+            "--model",
+            "icon_d2,gfs_global",
+        ])
+        .unwrap();
+
+        let Command::Hourly(forecast) = args.command else {
+            panic!("expected the hourly command");
+        };
+        assert_eq!(forecast.location.as_deref(), Some("Berlin"));
+        assert_eq!(forecast.options.units, Some(UnitSystem::Imperial));
+        assert_eq!(forecast.days, Some(2));
+        assert_eq!(
+            forecast.options.variables,
+            vec!["temperature_2m", "precipitation"]
+        );
+        assert_eq!(
+            forecast.options.model,
+            vec![WeatherModel::IconD2, WeatherModel::GfsGlobal]
+        );
+        assert!(!forecast.diff.enabled);
+        assert!(forecast.diff.threshold.is_empty());
+        assert_eq!(forecast.options.language, None);
+        assert_eq!(args.output, OutputFormat::Table);
+        assert_eq!(args.theme, None);
+        assert!(!args.choose);
+    }
+
+    #[test]
+    fn test_parse_diff_thresholds() {
+        let args = TinyWeatherArgs::try_parse_from([
+            "tiny_weather",
+            "daily",
+            "--diff",
+            "--threshold",
+            "temperature_2m_max=2,precipitation_sum=0.5",
+            "--threshold",
+            "wind_speed_10m_max=10",
+        ])
+        .unwrap();
+        let Command::Daily(daily) = args.command else {
+            panic!("expected the daily command");
+        };
+        assert!(daily.diff.enabled);
+        assert_eq!(
+            daily.diff.threshold,
+            vec![
+                ("temperature_2m_max".to_string(), 2.0),
+                ("precipitation_sum".to_string(), 0.5),
+                ("wind_speed_10m_max".to_string(), 10.0),
+            ]
+        );
+
+        for bad in ["temperature_2m", "temperature_2m=warm", "temperature_2m=-1"] {
+            assert!(
+                TinyWeatherArgs::try_parse_from([
+                    "tiny_weather",
+                    "daily",
+                    "--diff",
+                    "--threshold",
+                    bad
+                ])
+                .is_err()
+            );
+        }
+        // Thresholds only apply to --diff
+        assert!(
+            TinyWeatherArgs::try_parse_from(["tiny_weather", "daily", "--threshold", "rain=1"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_parse_astro_command() {
+        let args = TinyWeatherArgs::try_parse_from([
+            "tiny_weather",
+            "astro",
+            "52.52,13.41",
+            "--date",
+            "2024-06-21",
+            "--days",
+            "3",
+        ])
+        .unwrap();
+
+        let Command::Astro(astro) = args.command else {
+            panic!("expected the astro command");
+        };
+        assert_eq!(astro.location.as_deref(), Some("52.52,13.41"));
+        assert_eq!(astro.date, NaiveDate::from_ymd_opt(2024, 6, 21));
+        assert_eq!(astro.days, 3);
+        assert!(TinyWeatherArgs::try_parse_from(["tiny_weather", "astro", "-d", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_output_format() {
+        let args =
+            TinyWeatherArgs::try_parse_from(["tiny_weather", "search", "Berlin", "-o", "ndjson"])
+                .unwrap();
+        assert_eq!(args.output, OutputFormat::Ndjson);
+
+        let args =
+            TinyWeatherArgs::try_parse_from(["tiny_weather", "--output", "csv", "now", "Berlin"])
+                .unwrap();
+        assert_eq!(args.output, OutputFormat::Csv);
+    }
+
+    #[test]
+    fn test_parse_history_command() {
+        let args = TinyWeatherArgs::try_parse_from([
+            "tiny_weather",
+            "history",
+            "Berlin",
+            "--from",
+            "2024-01-01",
+            "--to",
+            "2024-01-31",
+            "--language",
+            "de",
+        ])
+        .unwrap();
+
+        let Command::History(history) = args.command else {
+            panic!("expected the history command");
+        };
+        assert_eq!(history.from, NaiveDate::from_ymd_opt(2024, 1, 1).unwrap());
+        assert_eq!(history.to, NaiveDate::from_ymd_opt(2024, 1, 31).unwrap());
+        assert_eq!(history.options.units, None);
+        assert_eq!(history.options.language.as_deref(), Some("de"));
+
+        // The location may come from the config file
+        let args = TinyWeatherArgs::try_parse_from(["tiny_weather", "daily"]).unwrap();
+        let Command::Daily(daily) = args.command else {
+            panic!("expected the daily command");
+        };
+        assert_eq!(daily.location, None);
+        assert!(
+            TinyWeatherArgs::try_parse_from(["tiny_weather", "now", "Berlin", "--model", "x"])
+                .is_err()
+        );
+
+        // Current conditions have no days, but can be compared
+        assert!(
+            TinyWeatherArgs::try_parse_from(["tiny_weather", "now", "Berlin", "--days", "2"])
+                .is_err()
+        );
+        let args =
+            TinyWeatherArgs::try_parse_from(["tiny_weather", "now", "--diff", "--language", "fr"])
+                .unwrap();
+        let Command::Now(now) = args.command else {
+            panic!("expected the now command");
+        };
+        assert!(now.diff.enabled);
+        assert_eq!(now.options.language.as_deref(), Some("fr"));
+    }
+
+    #[test]
+    fn test_parse_config_command() {
+        let args =
+            TinyWeatherArgs::try_parse_from(["tiny_weather", "config", "set", "units", "imperial"])
+                .unwrap();
+        assert_eq!(
+            args.command,
+            Command::Config(ConfigCommand::Set {
+                key: "units".to_string(),
+                value: Some("imperial".to_string()),
+            })
+        );
+
+        let args =
+            TinyWeatherArgs::try_parse_from(["tiny_weather", "config", "get", "--theme", "emoji"])
+                .unwrap();
+        assert_eq!(
+            args.command,
+            Command::Config(ConfigCommand::Get { key: None })
+        );
+        assert_eq!(args.theme, Some(Theme::Emoji));
+
+        assert!(TinyWeatherArgs::try_parse_from(["tiny_weather", "config", "set"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_favorite_command() {
+        let args = TinyWeatherArgs::try_parse_from([
+            "tiny_weather",
+            "fav",
+            "add",
+            "home",
+            "Berlin",
+            "--force",
+        ])
+        .unwrap();
+        assert_eq!(
+            args.command,
+            Command::Favorite(FavoriteCommand::Add {
+                alias: "home".to_string(),
+                location: "Berlin".to_string(),
+                force: true,
+            })
+        );
+
+        let args =
+            TinyWeatherArgs::try_parse_from(["tiny_weather", "favorite", "rename", "home", "flat"])
+                .unwrap();
+        assert_eq!(
+            args.command,
+            Command::Favorite(FavoriteCommand::Rename {
+                from: "home".to_string(),
+                to: "flat".to_string(),
+            })
+        );
+    }
+}
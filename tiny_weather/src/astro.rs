@@ -0,0 +1,614 @@
+//! Offline astronomical calculations: sun events, solar position and moon phase
+//!
+//! Sun times and positions follow the NOAA Solar Calculator (itself based on
+//! Meeus, *Astronomical Algorithms*), accurate to about a minute between ±72°
+//! latitude. The moon phase uses Meeus' low-precision lunar theory, good to a
+//! few hours. Nothing here needs the network, so daily sunrise and sunset do
+//! not have to be requested from the API:
+//!
+//! ```
+//! use chrono::NaiveDate;
+//! use tiny_weather::astro;
+//! use tiny_weather::client::types::Coordinates;
+//!
+//! let london = Coordinates::new(51.5074, -0.1278);
+//! let date = NaiveDate::from_ymd_opt(2024, 6, 21).unwrap();
+//! let sun = astro::sun_times(london, date);
+//! assert!(sun.sunrise.is_some());
+//!
+//! let moon = astro::moon_phase(sun.solar_noon);
+//! println!("{} {} ({:.0}%)", moon.name().icon(), moon.name(), moon.illumination * 100.0);
+//! ```
+//!
+//! [`ForecastResponse::add_sun_times`] fills the daily `sunrise`, `sunset` and
+//! `daylight_duration` columns of a response from these calculations.
+
+use std::fmt;
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Timelike, Utc};
+
+use crate::client::error::{OpenMeteoError, Result};
+use crate::client::types::Coordinates;
+use crate::client::types::forecast::ForecastResponse;
+
+/// Zenith angle of sunrise and sunset, allowing for refraction and the solar disc
+const SUNRISE_ZENITH: f64 = 90.833;
+
+/// Mean length of a lunation (days)
+const SYNODIC_MONTH: f64 = 29.530_588_853;
+
+/// Depth of the sun below the horizon that bounds a twilight
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Twilight {
+    /// Sun 6° below the horizon
+    Civil,
+    /// Sun 12° below the horizon
+    Nautical,
+    /// Sun 18° below the horizon
+    Astronomical,
+}
+
+impl Twilight {
+    /// Zenith angle of the sun at the start of dawn and end of dusk
+    fn zenith(self) -> f64 {
+        match self {
+            Self::Civil => 96.0,
+            Self::Nautical => 102.0,
+            Self::Astronomical => 108.0,
+        }
+    }
+}
+
+/// Sun events of one day, in UTC
+///
+/// Events that do not occur (polar day or night, or twilight lasting all night
+/// in summer at high latitudes) are `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SunTimes {
+    pub solar_noon: DateTime<Utc>,
+    pub sunrise: Option<DateTime<Utc>>,
+    pub sunset: Option<DateTime<Utc>>,
+    pub civil_dawn: Option<DateTime<Utc>>,
+    pub civil_dusk: Option<DateTime<Utc>>,
+    pub nautical_dawn: Option<DateTime<Utc>>,
+    pub nautical_dusk: Option<DateTime<Utc>>,
+    pub astronomical_dawn: Option<DateTime<Utc>>,
+    pub astronomical_dusk: Option<DateTime<Utc>>,
+    /// Time between sunrise and sunset; a full day under the midnight sun, zero in polar night
+    pub day_length: Duration,
+}
+
+impl SunTimes {
+    /// Dawn and dusk of a twilight
+    pub fn twilight(&self, twilight: Twilight) -> (Option<DateTime<Utc>>, Option<DateTime<Utc>>) {
+        match twilight {
+            Twilight::Civil => (self.civil_dawn, self.civil_dusk),
+            Twilight::Nautical => (self.nautical_dawn, self.nautical_dusk),
+            Twilight::Astronomical => (self.astronomical_dawn, self.astronomical_dusk),
+        }
+    }
+}
+
+/// Position of the sun in the sky
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolarPosition {
+    /// Degrees above the horizon, corrected for atmospheric refraction
+    pub elevation: f64,
+    /// Degrees clockwise from north
+    pub azimuth: f64,
+}
+
+/// Compute the sun events of a date
+///
+/// `date` is the local calendar date: events are those of the solar day centred on
+/// local solar noon, whatever the longitude.
+pub fn sun_times(coordinates: Coordinates, date: NaiveDate) -> SunTimes {
+    let event = |zenith: f64, rising: bool| sun_event(coordinates, date, zenith, rising);
+    let sunrise = event(SUNRISE_ZENITH, true);
+    let sunset = event(SUNRISE_ZENITH, false);
+    let solar_noon = solar_noon(coordinates, date);
+
+    let day_length = match (sunrise, sunset) {
+        (Some(sunrise), Some(sunset)) => sunset - sunrise,
+        _ if solar_position(coordinates, solar_noon).elevation > 0.0 => Duration::days(1),
+        _ => Duration::zero(),
+    };
+
+    SunTimes {
+        solar_noon,
+        sunrise,
+        sunset,
+        civil_dawn: event(Twilight::Civil.zenith(), true),
+        civil_dusk: event(Twilight::Civil.zenith(), false),
+        nautical_dawn: event(Twilight::Nautical.zenith(), true),
+        nautical_dusk: event(Twilight::Nautical.zenith(), false),
+        astronomical_dawn: event(Twilight::Astronomical.zenith(), true),
+        astronomical_dusk: event(Twilight::Astronomical.zenith(), false),
+        day_length,
+    }
+}
+
+/// Compute the position of the sun at a given time
+pub fn solar_position(coordinates: Coordinates, time: DateTime<Utc>) -> SolarPosition {
+    let sun = Sun::at(julian_century(julian_day(time)));
+    let latitude = coordinates.latitude.to_radians();
+    let declination = sun.declination.to_radians();
+
+    let minutes = f64::from(time.num_seconds_from_midnight()) / 60.0;
+    let true_solar_time =
+        (minutes + sun.equation_of_time + 4.0 * coordinates.longitude).rem_euclid(1440.0);
+    let hour_angle = (true_solar_time / 4.0 - 180.0).to_radians();
+
+    let cos_zenith =
+        latitude.sin() * declination.sin() + latitude.cos() * declination.cos() * hour_angle.cos();
+    let zenith = cos_zenith.clamp(-1.0, 1.0).acos();
+
+    let azimuth = {
+        let denominator = latitude.cos() * zenith.sin();
+        let cos_azimuth = if denominator.abs() < 1e-12 {
+            // Sun at the zenith or an observer at a pole: azimuth is arbitrary
+            1.0
+        } else {
+            (latitude.sin() * zenith.cos() - declination.sin()) / denominator
+        };
+        let angle = cos_azimuth.clamp(-1.0, 1.0).acos().to_degrees();
+        if hour_angle > 0.0 {
+            (angle + 180.0).rem_euclid(360.0)
+        } else {
+            (540.0 - angle).rem_euclid(360.0)
+        }
+    };
+
+    let elevation = 90.0 - zenith.to_degrees();
+    SolarPosition {
+        elevation: elevation + refraction(elevation),
+        azimuth,
+    }
+}
+
+/// Named phase of the moon
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MoonPhaseName {
+    New,
+    WaxingCrescent,
+    FirstQuarter,
+    WaxingGibbous,
+    Full,
+    WaningGibbous,
+    LastQuarter,
+    WaningCrescent,
+}
+
+impl MoonPhaseName {
+    /// Name of the phase for a position in the lunation (0 new, 0.5 full)
+    ///
+    /// The four principal phases each cover an eighth of the cycle centred on the
+    /// exact phase, so a moon within about two days of full is reported as full.
+    pub fn from_phase(phase: f64) -> Self {
+        const NAMES: [MoonPhaseName; 8] = [
+            MoonPhaseName::New,
+            MoonPhaseName::WaxingCrescent,
+            MoonPhaseName::FirstQuarter,
+            MoonPhaseName::WaxingGibbous,
+            MoonPhaseName::Full,
+            MoonPhaseName::WaningGibbous,
+            MoonPhaseName::LastQuarter,
+            MoonPhaseName::WaningCrescent,
+        ];
+        let index = (phase.rem_euclid(1.0) * 8.0 + 0.5).floor() as usize % 8;
+        NAMES[index]
+    }
+
+    /// English name of the phase
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::New => "New moon",
+            Self::WaxingCrescent => "Waxing crescent",
+            Self::FirstQuarter => "First quarter",
+            Self::WaxingGibbous => "Waxing gibbous",
+            Self::Full => "Full moon",
+            Self::WaningGibbous => "Waning gibbous",
+            Self::LastQuarter => "Last quarter",
+            Self::WaningCrescent => "Waning crescent",
+        }
+    }
+
+    /// Emoji icon for the phase, as seen from the northern hemisphere
+    pub fn icon(self) -> &'static str {
+        match self {
+            Self::New => "🌑",
+            Self::WaxingCrescent => "🌒",
+            Self::FirstQuarter => "🌓",
+            Self::WaxingGibbous => "🌔",
+            Self::Full => "🌕",
+            Self::WaningGibbous => "🌖",
+            Self::LastQuarter => "🌗",
+            Self::WaningCrescent => "🌘",
+        }
+    }
+}
+
+impl fmt::Display for MoonPhaseName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+/// Phase and illumination of the moon
+///
+/// The phase is the same everywhere on Earth, so no coordinates are needed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoonPhase {
+    /// Position in the lunation, from 0 (new) through 0.5 (full) to 1
+    pub phase: f64,
+    /// Illuminated fraction of the disc (0–1)
+    pub illumination: f64,
+    /// Approximate days since the last new moon
+    pub age: f64,
+}
+
+impl MoonPhase {
+    /// Named phase
+    pub fn name(&self) -> MoonPhaseName {
+        MoonPhaseName::from_phase(self.phase)
+    }
+
+    /// Whether the illuminated fraction is growing
+    pub fn is_waxing(&self) -> bool {
+        self.phase < 0.5
+    }
+}
+
+/// Compute the phase of the moon at a given time
+pub fn moon_phase(time: DateTime<Utc>) -> MoonPhase {
+    let t = julian_century(julian_day(time));
+
+    // Meeus chapter 48, low-precision phase angle
+    let elongation = (297.850_192_1
+        + t * (445_267.111_403_4 + t * (-0.001_881_9 + t * (1.0 / 545_868.0 - t / 113_065_000.0))))
+        .to_radians();
+    let sun_anomaly = (357.529_109_2
+        + t * (35_999.050_290_9 + t * (-0.000_153_6 + t / 24_490_000.0)))
+        .to_radians();
+    let moon_anomaly = (134.963_396_4
+        + t * (477_198.867_505_5 + t * (0.008_741_4 + t * (1.0 / 69_699.0 - t / 14_712_000.0))))
+        .to_radians();
+
+    let phase_angle = 180.0 - elongation.to_degrees() - 6.289 * moon_anomaly.sin()
+        + 2.100 * sun_anomaly.sin()
+        - 1.274 * (2.0 * elongation - moon_anomaly).sin()
+        - 0.658 * (2.0 * elongation).sin()
+        - 0.214 * (2.0 * moon_anomaly).sin()
+        - 0.110 * elongation.sin();
+
+    // Phase angle is 180° at new moon and 0° at full, decreasing while waxing
+    let phase = ((180.0 - phase_angle) / 360.0).rem_euclid(1.0);
+    MoonPhase {
+        phase,
+        illumination: (1.0 + phase_angle.to_radians().cos()) / 2.0,
+        age: phase * SYNODIC_MONTH,
+    }
+}
+
+/// Declination and equation of time of the sun
+struct Sun {
+    /// Degrees
+    declination: f64,
+    /// Minutes that apparent solar time runs ahead of mean solar time
+    equation_of_time: f64,
+}
+
+impl Sun {
+    /// Compute for a time in Julian centuries since J2000
+    fn at(t: f64) -> Self {
+        let mean_longitude = (280.466_46 + t * (36_000.769_83 + t * 0.000_303_2)).rem_euclid(360.0);
+        let mean_anomaly = 357.529_11 + t * (35_999.050_29 - 0.000_153_7 * t);
+        let eccentricity = 0.016_708_634 - t * (0.000_042_037 + 0.000_000_126_7 * t);
+
+        let m = mean_anomaly.to_radians();
+        let center = m.sin() * (1.914_602 - t * (0.004_817 + 0.000_014 * t))
+            + (2.0 * m).sin() * (0.019_993 - 0.000_101 * t)
+            + (3.0 * m).sin() * 0.000_289;
+        let omega = (125.04 - 1934.136 * t).to_radians();
+        let apparent_longitude =
+            (mean_longitude + center - 0.005_69 - 0.004_78 * omega.sin()).to_radians();
+
+        let mean_obliquity =
+            23.0 + (26.0 + (21.448 - t * (46.815 + t * (0.000_59 - t * 0.001_813))) / 60.0) / 60.0;
+        let obliquity = (mean_obliquity + 0.002_56 * omega.cos()).to_radians();
+        let declination = (obliquity.sin() * apparent_longitude.sin()).asin();
+
+        let y = (obliquity / 2.0).tan().powi(2);
+        let l0 = mean_longitude.to_radians();
+        let equation_of_time = y * (2.0 * l0).sin() - 2.0 * eccentricity * m.sin()
+            + 4.0 * eccentricity * y * m.sin() * (2.0 * l0).cos()
+            - 0.5 * y * y * (4.0 * l0).sin()
+            - 1.25 * eccentricity * eccentricity * (2.0 * m).sin();
+
+        Self {
+            declination: declination.to_degrees(),
+            equation_of_time: 4.0 * equation_of_time.to_degrees(),
+        }
+    }
+}
+
+fn julian_day(time: DateTime<Utc>) -> f64 {
+    time.timestamp_millis() as f64 / 86_400_000.0 + 2_440_587.5
+}
+
+fn julian_century(julian_day: f64) -> f64 {
+    (julian_day - 2_451_545.0) / 36_525.0
+}
+
+/// Midnight UTC of `date` plus a (possibly negative or over 24 h) number of minutes
+fn utc_minutes(date: NaiveDate, minutes: f64) -> DateTime<Utc> {
+    let midnight = NaiveDateTime::from(date).and_utc();
+    midnight + Duration::seconds((minutes * 60.0).round() as i64)
+}
+
+fn solar_noon(coordinates: Coordinates, date: NaiveDate) -> DateTime<Utc> {
+    let mut minutes = 720.0 - 4.0 * coordinates.longitude;
+    for _ in 0..2 {
+        let sun = Sun::at(julian_century(julian_day(utc_minutes(date, minutes))));
+        minutes = 720.0 - 4.0 * coordinates.longitude - sun.equation_of_time;
+    }
+    utc_minutes(date, minutes)
+}
+
+/// Time the sun crosses `zenith` while rising or setting, refined by re-evaluating
+/// the sun's position at each estimate
+fn sun_event(
+    coordinates: Coordinates,
+    date: NaiveDate,
+    zenith: f64,
+    rising: bool,
+) -> Option<DateTime<Utc>> {
+    let mut minutes = 720.0 - 4.0 * coordinates.longitude;
+    for _ in 0..3 {
+        let sun = Sun::at(julian_century(julian_day(utc_minutes(date, minutes))));
+        let hour_angle = hour_angle(coordinates.latitude, sun.declination, zenith)?;
+        let hour_angle = if rising { hour_angle } else { -hour_angle };
+        minutes = 720.0 - 4.0 * (coordinates.longitude + hour_angle) - sun.equation_of_time;
+    }
+    Some(utc_minutes(date, minutes))
+}
+
+/// Hour angle (degrees) at which the sun reaches `zenith`, if it ever does
+fn hour_angle(latitude: f64, declination: f64, zenith: f64) -> Option<f64> {
+    let (latitude, declination) = (latitude.to_radians(), declination.to_radians());
+    let cos_hour_angle = zenith.to_radians().cos() / (latitude.cos() * declination.cos())
+        - latitude.tan() * declination.tan();
+    (-1.0..=1.0)
+        .contains(&cos_hour_angle)
+        .then(|| cos_hour_angle.acos().to_degrees())
+}
+
+/// Atmospheric refraction (degrees) for an apparent elevation, as in the NOAA calculator
+fn refraction(elevation: f64) -> f64 {
+    let tan = elevation.to_radians().tan();
+    let arcseconds = if elevation > 85.0 {
+        0.0
+    } else if elevation > 5.0 {
+        58.1 / tan - 0.07 / tan.powi(3) + 0.000_086 / tan.powi(5)
+    } else if elevation > -0.575 {
+        1735.0
+            + elevation * (-518.2 + elevation * (103.4 + elevation * (-12.79 + elevation * 0.711)))
+    } else {
+        -20.772 / tan
+    };
+    arcseconds / 3600.0
+}
+
+impl ForecastResponse {
+    /// Fill the daily `sunrise`, `sunset` and `daylight_duration` columns offline
+    ///
+    /// Times are local (`utc_offset_seconds`) and formatted like the API's ISO 8601
+    /// output; days without a sunrise or sunset are `None`. Columns already
+    /// returned by the API are kept.
+    pub fn add_sun_times(&mut self) -> Result<()> {
+        let coordinates = Coordinates::new(self.latitude, self.longitude);
+        let offset = Duration::seconds(self.utc_offset_seconds.into());
+        let daily = self.daily.as_mut().ok_or_else(|| {
+            OpenMeteoError::InvalidParameter("sun times require daily data".to_string())
+        })?;
+
+        let days = daily
+            .time
+            .iter()
+            .map(|date| {
+                NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                    .map(|date| sun_times(coordinates, date))
+                    .map_err(|e| {
+                        OpenMeteoError::InvalidParameter(format!(
+                            "invalid daily time {}: {}",
+                            date, e
+                        ))
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let local = |time: Option<DateTime<Utc>>| {
+            time.map(|time| (time + offset).format("%Y-%m-%dT%H:%M").to_string())
+        };
+        daily
+            .time_variables
+            .entry("sunrise".to_string())
+            .or_insert_with(|| days.iter().map(|day| local(day.sunrise)).collect());
+        daily
+            .time_variables
+            .entry("sunset".to_string())
+            .or_insert_with(|| days.iter().map(|day| local(day.sunset)).collect());
+        daily
+            .variables
+            .entry("daylight_duration".to_string())
+            .or_insert_with(|| {
+                days.iter()
+                    .map(|day| Some(day.day_length.num_seconds() as f64))
+                    .collect()
+            });
+
+        let units = self.daily_units.get_or_insert_with(Default::default);
+        for (name, unit) in [
+            ("sunrise", "iso8601"),
+            ("sunset", "iso8601"),
+            ("daylight_duration", "s"),
+        ] {
+            units
+                .entry(name.to_string())
+                .or_insert_with(|| unit.to_string());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::types::forecast::fixtures;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn utc(text: &str) -> DateTime<Utc> {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M")
+            .unwrap()
+            .and_utc()
+    }
+
+    fn assert_near(actual: Option<DateTime<Utc>>, expected: &str) {
+        let actual = actual.unwrap();
+        let difference = (actual - utc(expected)).num_seconds().abs();
+        assert!(difference <= 60, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn test_sun_times_noaa_reference() {
+        // NOAA Solar Calculator, London on the 2024 June solstice (BST = UTC+1)
+        let london = sun_times(Coordinates::new(51.5074, -0.1278), date(2024, 6, 21));
+        assert_near(london.sunrise, "2024-06-21 03:43");
+        assert_near(london.sunset, "2024-06-21 20:21");
+        assert_near(Some(london.solar_noon), "2024-06-21 12:02");
+
+        // New York (EDT = UTC−4): sunset falls on the next UTC day
+        let new_york = sun_times(Coordinates::new(40.7128, -74.0060), date(2024, 6, 21));
+        assert_near(new_york.sunrise, "2024-06-21 09:25");
+        assert_near(new_york.sunset, "2024-06-22 00:31");
+        assert_near(new_york.civil_dawn, "2024-06-21 08:52");
+        assert_near(new_york.civil_dusk, "2024-06-22 01:04");
+        assert_near(Some(new_york.solar_noon), "2024-06-21 16:58");
+        assert_eq!(new_york.day_length.num_minutes(), 905);
+    }
+
+    #[test]
+    fn test_sun_times_ordering() {
+        // Sydney's local date starts on the previous UTC day
+        let sydney = sun_times(Coordinates::new(-33.8688, 151.2093), date(2024, 12, 21));
+        let events = [
+            sydney.astronomical_dawn,
+            sydney.nautical_dawn,
+            sydney.civil_dawn,
+            sydney.sunrise,
+            Some(sydney.solar_noon),
+            sydney.sunset,
+            sydney.civil_dusk,
+            sydney.nautical_dusk,
+            sydney.astronomical_dusk,
+        ];
+        let events: Vec<_> = events.into_iter().map(Option::unwrap).collect();
+        assert!(events.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(sydney.sunrise.unwrap().date_naive(), date(2024, 12, 20));
+        assert_eq!(
+            sydney.twilight(Twilight::Civil),
+            (sydney.civil_dawn, sydney.civil_dusk)
+        );
+    }
+
+    #[test]
+    fn test_polar_day_and_night() {
+        let tromso = Coordinates::new(69.6496, 18.9560);
+
+        let summer = sun_times(tromso, date(2024, 6, 21));
+        assert_eq!(summer.sunrise, None);
+        assert_eq!(summer.astronomical_dusk, None);
+        assert_eq!(summer.day_length, Duration::days(1));
+
+        let winter = sun_times(tromso, date(2024, 12, 21));
+        assert_eq!(winter.sunrise, None);
+        assert!(winter.civil_dawn.is_some());
+        assert_eq!(winter.day_length, Duration::zero());
+    }
+
+    #[test]
+    fn test_solar_position() {
+        let boulder = Coordinates::new(40.0, -105.0);
+
+        // Near solar noon on the solstice the sun is due south at 90° − 40° + 23.44°
+        let noon = solar_position(boulder, utc("2024-06-21 19:00"));
+        assert!((noon.elevation - 73.4).abs() < 0.1, "{:?}", noon);
+        assert!((noon.azimuth - 180.0).abs() < 3.0, "{:?}", noon);
+
+        // Mid-morning the sun is in the east, and below the horizon at midnight
+        let morning = solar_position(boulder, utc("2024-06-21 15:00"));
+        assert!((60.0..120.0).contains(&morning.azimuth), "{:?}", morning);
+        assert!(solar_position(boulder, utc("2024-06-21 07:00")).elevation < 0.0);
+    }
+
+    #[test]
+    fn test_moon_phase() {
+        // New moon (total solar eclipse) and full moon of April 2024
+        let new = moon_phase(utc("2024-04-08 18:21"));
+        assert_eq!(new.name(), MoonPhaseName::New);
+        assert!(new.illumination < 0.01);
+        assert!(new.age < 0.2);
+
+        let full = moon_phase(utc("2024-04-23 23:49"));
+        assert_eq!(full.name(), MoonPhaseName::Full);
+        assert!(full.illumination > 0.99);
+
+        let first_quarter = moon_phase(utc("2024-04-15 19:13"));
+        assert_eq!(first_quarter.name(), MoonPhaseName::FirstQuarter);
+        assert!((first_quarter.illumination - 0.5).abs() < 0.02);
+        assert!(first_quarter.is_waxing());
+
+        assert_eq!(MoonPhaseName::from_phase(0.97), MoonPhaseName::New);
+        assert_eq!(
+            MoonPhaseName::from_phase(0.85),
+            MoonPhaseName::WaningCrescent
+        );
+        assert_eq!(MoonPhaseName::LastQuarter.icon(), "🌗");
+    }
+
+    #[test]
+    fn test_add_sun_times() {
+        let mut response = fixtures::forecast(serde_json::json!({
+            "latitude": 51.5074,
+            "longitude": -0.1278,
+            "elevation": 11.0,
+            "utc_offset_seconds": 3600,
+            "timezone": "Europe/London",
+            "timezone_abbreviation": "BST",
+            "daily": {
+                "time": ["2024-06-21", "2024-06-22"],
+                "sunset": ["2024-06-21T21:21", "2024-06-22T21:21"]
+            }
+        }));
+        response.add_sun_times().unwrap();
+
+        let daily = response.daily.as_ref().unwrap();
+        assert_eq!(
+            daily.time_variables["sunrise"],
+            vec![
+                Some("2024-06-21T04:43".to_string()),
+                Some("2024-06-22T04:43".to_string())
+            ]
+        );
+        assert_eq!(
+            daily.time_variables["sunset"][0].as_deref(),
+            Some("2024-06-21T21:21")
+        );
+        assert!(daily.variables["daylight_duration"][0].unwrap() > 16.0 * 3600.0);
+        assert_eq!(response.daily_units.as_ref().unwrap()["sunrise"], "iso8601");
+    }
+}
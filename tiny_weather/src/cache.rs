@@ -0,0 +1,98 @@
+//! On-disk cache of JSON values with a time to live
+//!
+//! Each entry is a JSON file named by a hash of its key, holding the key and
+//! the value. An entry is fresh while its file is younger than the time to live
+//! the caller asks for, so the same cache can serve forecasts for minutes and
+//! geocoding results for weeks. Unreadable or stale entries are misses.
+
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::client::error::Result;
+
+/// Directory of cached values
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiskCache {
+    dir: PathBuf,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry<T> {
+    key: String,
+    value: T,
+}
+
+impl DiskCache {
+    /// Cache values in `dir`, which is created on the first write
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Value stored under `key`, if it was written less than `ttl` ago
+    pub fn get<T: DeserializeOwned>(&self, key: &str, ttl: Duration) -> Option<T> {
+        let path = self.path(key);
+        let age = SystemTime::now()
+            .duration_since(std::fs::metadata(&path).ok()?.modified().ok()?)
+            .unwrap_or_default();
+        if age >= ttl {
+            return None;
+        }
+        let entry: Entry<T> = serde_json::from_slice(&std::fs::read(path).ok()?).ok()?;
+        // Different keys may share a file name
+        (entry.key == key).then_some(entry.value)
+    }
+
+    /// Store `value` under `key`
+    pub fn put<T: Serialize>(&self, key: &str, value: &T) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let entry = Entry {
+            key: key.to_string(),
+            value,
+        };
+        std::fs::write(self.path(key), serde_json::to_vec(&entry)?)?;
+        Ok(())
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{:016x}.json", fnv1a(key)))
+    }
+}
+
+/// 64-bit FNV-1a, a file name hash that is stable across builds and platforms
+fn fnv1a(key: &str) -> u64 {
+    key.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disk_cache() {
+        let dir = std::env::temp_dir().join(format!("tiny_weather_cache_{}", std::process::id()));
+        let cache = DiskCache::new(&dir);
+        let hour = Duration::from_secs(3600);
+
+        assert_eq!(cache.get::<Vec<f64>>("berlin", hour), None);
+        cache.put("berlin", &vec![1.0, 2.5]).unwrap();
+        assert_eq!(cache.get("berlin", hour), Some(vec![1.0, 2.5]));
+        assert_eq!(cache.get::<Vec<f64>>("paris", hour), None);
+
+        // Expired, or stored as another type
+        assert_eq!(cache.get::<Vec<f64>>("berlin", Duration::ZERO), None);
+        assert_eq!(cache.get::<String>("berlin", hour), None);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_fnv1a() {
+        assert_eq!(fnv1a(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a("a"), 0xaf63_dc4c_8601_ec8c);
+    }
+}
@@ -0,0 +1,771 @@
+//! Column-oriented, zero-copy parsing of JSON forecast responses
+//!
+//! [`ForecastResponse`] stores every series as its own `Vec<Option<f64>>` in a
+//! `HashMap`, and the times as owned `String`s. For long requests (e.g. 92 past
+//! days plus 16 forecast days of 30 hourly variables at many locations) that is
+//! a lot of small allocations on top of the buffered body.
+//!
+//! [`parse`] instead deserializes straight from the body bytes:
+//! - ISO 8601 times and variable names borrow from the body instead of being copied
+//! - all values of a section share one contiguous, pre-sized `Vec<f64>`,
+//!   one column after another, with `NaN` marking missing values
+//! - string columns (daily `sunrise`/`sunset`) share a second buffer of
+//!   strings borrowed from the body
+//!
+//! The whole body is still read into memory first; what this saves is the
+//! `String` copy of it and the per-series `HashMap` values.
+//! [`ForecastClient::get_columnar`] fetches a body ready for parsing:
+//!
+//! ```no_run
+//! # async fn run() -> tiny_weather::client::Result<()> {
+//! use tiny_weather::client::ForecastClient;
+//! use tiny_weather::client::types::ForecastRequest;
+//!
+//! let request = ForecastRequest::new(52.52, 13.41)
+//!     .hourly(&["temperature_2m", "precipitation"])
+//!     .past_days(92);
+//! let body = ForecastClient::new().get_columnar(&request).await?;
+//!
+//! for forecast in body.parse()? {
+//!     let hourly = forecast.hourly.as_ref().unwrap();
+//!     let temperature = hourly.column("temperature_2m").unwrap();
+//!     println!("{} hours, first {:?}", hourly.len(), temperature.first());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`ForecastClient::get_columnar`]: crate::client::ForecastClient::get_columnar
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::Deserialize;
+use serde::de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor};
+
+use crate::client::error::Result;
+use crate::client::types::forecast::{CurrentData, DailyData, ForecastResponse, HourlyData};
+
+/// Parse a JSON forecast body, which is an array for multi-location requests
+pub fn parse(body: &[u8]) -> Result<Vec<ForecastColumns<'_>>> {
+    if body.trim_ascii_start().starts_with(b"[") {
+        Ok(serde_json::from_slice(body)?)
+    } else {
+        Ok(vec![serde_json::from_slice(body)?])
+    }
+}
+
+/// JSON forecast body returned by [`ForecastClient::get_columnar`]
+///
+/// [`ForecastColumns`] borrow from the body, so it is kept here and parsed on demand.
+///
+/// [`ForecastClient::get_columnar`]: crate::client::ForecastClient::get_columnar
+#[derive(Debug, Clone)]
+pub struct ColumnarBody {
+    body: bytes::Bytes,
+}
+
+impl ColumnarBody {
+    pub(crate) fn new(body: bytes::Bytes) -> Self {
+        Self { body }
+    }
+
+    /// Parse the forecast of every location, in request order
+    pub fn parse(&self) -> Result<Vec<ForecastColumns<'_>>> {
+        parse(&self.body)
+    }
+
+    /// The raw JSON body
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.body
+    }
+}
+
+/// Forecast for one location with column-oriented series borrowing from the body
+#[derive(Debug, Clone, Deserialize)]
+pub struct ForecastColumns<'a> {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub elevation: f64,
+    pub generationtime_ms: f64,
+    pub utc_offset_seconds: i32,
+    #[serde(borrow)]
+    pub timezone: Cow<'a, str>,
+    #[serde(borrow)]
+    pub timezone_abbreviation: Cow<'a, str>,
+
+    #[serde(borrow, default)]
+    pub hourly: Option<Columns<'a>>,
+
+    #[serde(default)]
+    pub hourly_units: Option<HashMap<String, String>>,
+
+    #[serde(borrow, default)]
+    pub daily: Option<Columns<'a>>,
+
+    #[serde(default)]
+    pub daily_units: Option<HashMap<String, String>>,
+
+    #[serde(default)]
+    pub current: Option<CurrentData>,
+
+    #[serde(default)]
+    pub current_units: Option<HashMap<String, String>>,
+}
+
+impl ForecastColumns<'_> {
+    /// Copy into the owned, map-based [`ForecastResponse`]
+    pub fn to_response(&self) -> ForecastResponse {
+        ForecastResponse {
+            latitude: self.latitude,
+            longitude: self.longitude,
+            elevation: self.elevation,
+            generationtime_ms: self.generationtime_ms,
+            utc_offset_seconds: self.utc_offset_seconds,
+            timezone: self.timezone.to_string(),
+            timezone_abbreviation: self.timezone_abbreviation.to_string(),
+            hourly: self.hourly.as_ref().map(|hourly| {
+                let (time, variables) = hourly.to_series();
+                HourlyData { time, variables }
+            }),
+            hourly_units: self.hourly_units.clone(),
+            daily: self.daily.as_ref().map(|daily| {
+                let (time, variables) = daily.to_series();
+                DailyData {
+                    time,
+                    variables,
+                    time_variables: daily.to_text_series(),
+                }
+            }),
+            daily_units: self.daily_units.clone(),
+            current: self.current.clone(),
+            current_units: self.current_units.clone(),
+        }
+    }
+}
+
+/// Time column of a section, as returned for the request's `timeformat`
+#[derive(Debug, Clone, PartialEq)]
+pub enum TimeColumn<'a> {
+    /// `timeformat=iso8601` (e.g., `2024-01-01T00:00`)
+    Iso8601(Vec<Cow<'a, str>>),
+    /// `timeformat=unixtime`, in seconds
+    Unixtime(Vec<i64>),
+}
+
+impl TimeColumn<'_> {
+    /// Number of time steps
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Iso8601(times) => times.len(),
+            Self::Unixtime(times) => times.len(),
+        }
+    }
+
+    /// Whether there are no time steps
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Render the time at `index` the way [`HourlyData::time`] stores it
+    pub fn get(&self, index: usize) -> Option<String> {
+        match self {
+            Self::Iso8601(times) => times.get(index).map(|t| t.to_string()),
+            Self::Unixtime(times) => times.get(index).map(|t| t.to_string()),
+        }
+    }
+}
+
+/// Hourly or daily series stored column by column in one contiguous buffer
+#[derive(Debug, Clone)]
+pub struct Columns<'a> {
+    time: TimeColumn<'a>,
+    names: Vec<Cow<'a, str>>,
+    /// Column-major values; column `i` is `values[i * rows..(i + 1) * rows]`
+    values: Vec<f64>,
+    text_names: Vec<Cow<'a, str>>,
+    /// Column-major strings of the text columns, laid out like `values`
+    texts: Vec<Option<Cow<'a, str>>>,
+}
+
+impl<'a> Columns<'a> {
+    /// Number of time steps (rows)
+    pub fn len(&self) -> usize {
+        self.time.len()
+    }
+
+    /// Whether there are no time steps
+    pub fn is_empty(&self) -> bool {
+        self.time.is_empty()
+    }
+
+    /// Time column
+    pub fn time(&self) -> &TimeColumn<'a> {
+        &self.time
+    }
+
+    /// Variable names, in response order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(|name| name.as_ref())
+    }
+
+    /// Values of a variable, with `NaN` marking missing values
+    pub fn column(&self, name: &str) -> Option<&[f64]> {
+        let index = self.names.iter().position(|n| n == name)?;
+        Some(self.column_at(index))
+    }
+
+    /// All variables with their values, in response order
+    pub fn columns(&self) -> impl Iterator<Item = (&str, &[f64])> {
+        self.names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.as_ref(), self.column_at(i)))
+    }
+
+    /// Value of a variable at a time step, or `None` if it is missing
+    pub fn value(&self, name: &str, row: usize) -> Option<f64> {
+        self.column(name)?.get(row).copied().filter(|v| !v.is_nan())
+    }
+
+    /// Copy into the owned time and variable layout of [`HourlyData`] and [`DailyData`]
+    pub fn to_series(&self) -> (Vec<String>, HashMap<String, Vec<Option<f64>>>) {
+        let time = (0..self.len()).filter_map(|i| self.time.get(i)).collect();
+        let variables = self
+            .columns()
+            .map(|(name, values)| {
+                let values = values
+                    .iter()
+                    .map(|v| if v.is_nan() { None } else { Some(*v) })
+                    .collect();
+                (name.to_string(), values)
+            })
+            .collect();
+        (time, variables)
+    }
+
+    /// Names of the string variables (e.g., `sunrise`), in response order
+    pub fn text_names(&self) -> impl Iterator<Item = &str> {
+        self.text_names.iter().map(|name| name.as_ref())
+    }
+
+    /// Values of a string variable, with `None` marking missing values
+    pub fn text_column(&self, name: &str) -> Option<&[Option<Cow<'a, str>>]> {
+        let index = self.text_names.iter().position(|n| n == name)?;
+        Some(self.text_column_at(index))
+    }
+
+    /// Copy the string variables into the owned layout of [`DailyData::time_variables`]
+    pub fn to_text_series(&self) -> HashMap<String, Vec<Option<String>>> {
+        self.text_names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let values = self
+                    .text_column_at(i)
+                    .iter()
+                    .map(|v| v.as_ref().map(|v| v.to_string()))
+                    .collect();
+                (name.to_string(), values)
+            })
+            .collect()
+    }
+
+    fn column_at(&self, index: usize) -> &[f64] {
+        let rows = self.len();
+        &self.values[index * rows..(index + 1) * rows]
+    }
+
+    fn text_column_at(&self, index: usize) -> &[Option<Cow<'a, str>>] {
+        let rows = self.len();
+        &self.texts[index * rows..(index + 1) * rows]
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for Columns<'a> {
+    fn deserialize<D: de::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        deserializer.deserialize_map(ColumnsVisitor(std::marker::PhantomData))
+    }
+}
+
+struct ColumnsVisitor<'a>(std::marker::PhantomData<&'a ()>);
+
+impl<'de: 'a, 'a> Visitor<'de> for ColumnsVisitor<'a> {
+    type Value = Columns<'a>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a map of a time array and value arrays")
+    }
+
+    fn visit_map<M: MapAccess<'de>>(
+        self,
+        mut map: M,
+    ) -> std::result::Result<Self::Value, M::Error> {
+        let mut time: Option<TimeColumn<'a>> = None;
+        let mut names = Vec::new();
+        let mut values = Vec::new();
+        let mut text_names = Vec::new();
+        let mut texts = Vec::new();
+        // Row count, known once the time column or the first variable is read
+        let mut rows: Option<usize> = None;
+
+        while let Some(key) = map.next_key_seed(StrSeed)? {
+            if key == "time" {
+                let column = map.next_value_seed(TimeSeed)?;
+                check_rows(&mut rows, column.len(), &key)?;
+                time = Some(column);
+                continue;
+            }
+
+            let (start, text_start) = (values.len(), texts.len());
+            let kind = map.next_value_seed(ValuesSeed {
+                values: &mut values,
+                texts: &mut texts,
+                rows,
+            })?;
+            match kind {
+                ColumnKind::Numbers => {
+                    check_rows(&mut rows, values.len() - start, &key)?;
+                    names.push(key);
+                }
+                ColumnKind::Texts => {
+                    check_rows(&mut rows, texts.len() - text_start, &key)?;
+                    text_names.push(key);
+                }
+            }
+        }
+
+        let time = time.ok_or_else(|| de::Error::missing_field("time"))?;
+        Ok(Columns {
+            time,
+            names,
+            values,
+            text_names,
+            texts,
+        })
+    }
+}
+
+fn check_rows<E: de::Error>(
+    rows: &mut Option<usize>,
+    len: usize,
+    name: &str,
+) -> std::result::Result<(), E> {
+    match *rows {
+        Some(expected) if expected != len => Err(E::custom(format!(
+            "`{}` has {} values, expected {}",
+            name, len, expected
+        ))),
+        _ => {
+            *rows = Some(len);
+            Ok(())
+        }
+    }
+}
+
+/// Deserialize a string, borrowing it from the body when it has no escapes
+struct StrSeed;
+
+impl<'de> DeserializeSeed<'de> for StrSeed {
+    type Value = Cow<'de, str>;
+
+    fn deserialize<D: de::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<Self::Value, D::Error> {
+        deserializer.deserialize_str(StrVisitor)
+    }
+}
+
+struct StrVisitor;
+
+impl<'de> Visitor<'de> for StrVisitor {
+    type Value = Cow<'de, str>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a string")
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> std::result::Result<Self::Value, E> {
+        Ok(Cow::Borrowed(v))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> std::result::Result<Self::Value, E> {
+        Ok(Cow::Owned(v.to_string()))
+    }
+}
+
+/// Deserialize a time array of either ISO 8601 strings or unix timestamps
+struct TimeSeed;
+
+impl<'de> DeserializeSeed<'de> for TimeSeed {
+    type Value = TimeColumn<'de>;
+
+    fn deserialize<D: de::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for TimeSeed {
+    type Value = TimeColumn<'de>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array of ISO 8601 strings or unix timestamps")
+    }
+
+    fn visit_seq<S: SeqAccess<'de>>(
+        self,
+        mut seq: S,
+    ) -> std::result::Result<Self::Value, S::Error> {
+        let capacity = seq.size_hint().unwrap_or_default();
+        let Some(first) = seq.next_element::<TimeValue>()? else {
+            return Ok(TimeColumn::Iso8601(Vec::new()));
+        };
+
+        match first {
+            TimeValue::Iso8601(first) => {
+                let mut times = Vec::with_capacity(capacity);
+                times.push(first);
+                while let Some(time) = seq.next_element_seed(StrSeed)? {
+                    times.push(time);
+                }
+                Ok(TimeColumn::Iso8601(times))
+            }
+            TimeValue::Unixtime(first) => {
+                let mut times = Vec::with_capacity(capacity);
+                times.push(first);
+                while let Some(time) = seq.next_element()? {
+                    times.push(time);
+                }
+                Ok(TimeColumn::Unixtime(times))
+            }
+        }
+    }
+}
+
+enum TimeValue<'a> {
+    Iso8601(Cow<'a, str>),
+    Unixtime(i64),
+}
+
+impl<'de> Deserialize<'de> for TimeValue<'de> {
+    fn deserialize<D: de::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        deserializer.deserialize_any(TimeValueVisitor)
+    }
+}
+
+struct TimeValueVisitor;
+
+impl<'de> Visitor<'de> for TimeValueVisitor {
+    type Value = TimeValue<'de>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an ISO 8601 string or unix timestamp")
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> std::result::Result<Self::Value, E> {
+        Ok(TimeValue::Iso8601(Cow::Borrowed(v)))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> std::result::Result<Self::Value, E> {
+        Ok(TimeValue::Iso8601(Cow::Owned(v.to_string())))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> std::result::Result<Self::Value, E> {
+        Ok(TimeValue::Unixtime(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> std::result::Result<Self::Value, E> {
+        i64::try_from(v)
+            .map(TimeValue::Unixtime)
+            .map_err(|_| E::custom("unix timestamp out of range"))
+    }
+}
+
+/// Append a value array to the shared number or string column buffer
+///
+/// The first non-null value decides the kind of the column; `null`s become
+/// `NaN` in number columns and `None` in string columns.
+struct ValuesSeed<'v, 'a> {
+    values: &'v mut Vec<f64>,
+    texts: &'v mut Vec<Option<Cow<'a, str>>>,
+    rows: Option<usize>,
+}
+
+/// Buffer a value array was appended to
+enum ColumnKind {
+    Numbers,
+    Texts,
+}
+
+impl<'de: 'a, 'a> DeserializeSeed<'de> for ValuesSeed<'_, 'a> {
+    type Value = ColumnKind;
+
+    fn deserialize<D: de::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<ColumnKind, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de: 'a, 'a> Visitor<'de> for ValuesSeed<'_, 'a> {
+    type Value = ColumnKind;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array of numbers, strings or nulls")
+    }
+
+    fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> std::result::Result<ColumnKind, S::Error> {
+        let rows = self.rows.or(seq.size_hint());
+        let mut kind = None;
+        let mut leading_nulls = 0;
+        while let Some(cell) = seq.next_element::<Cell<'de>>()? {
+            match (cell, &kind) {
+                (Cell::Null, None) => leading_nulls += 1,
+                (Cell::Null, Some(ColumnKind::Numbers)) => self.values.push(f64::NAN),
+                (Cell::Null, Some(ColumnKind::Texts)) => self.texts.push(None),
+                (Cell::Number(value), None) => {
+                    self.values.reserve(rows.unwrap_or_default());
+                    self.values
+                        .extend(std::iter::repeat_n(f64::NAN, leading_nulls));
+                    self.values.push(value);
+                    kind = Some(ColumnKind::Numbers);
+                }
+                (Cell::Number(value), Some(ColumnKind::Numbers)) => self.values.push(value),
+                (Cell::Text(text), None) => {
+                    self.texts.reserve(rows.unwrap_or_default());
+                    self.texts.extend(std::iter::repeat_n(None, leading_nulls));
+                    self.texts.push(Some(text));
+                    kind = Some(ColumnKind::Texts);
+                }
+                (Cell::Text(text), Some(ColumnKind::Texts)) => self.texts.push(Some(text)),
+                _ => return Err(de::Error::custom("array mixes numbers and strings")),
+            }
+        }
+
+        // An all-null array is a number column without values
+        if kind.is_none() {
+            self.values
+                .extend(std::iter::repeat_n(f64::NAN, leading_nulls));
+        }
+        Ok(kind.unwrap_or(ColumnKind::Numbers))
+    }
+}
+
+/// One element of a value array
+enum Cell<'a> {
+    Null,
+    Number(f64),
+    Text(Cow<'a, str>),
+}
+
+impl<'de> Deserialize<'de> for Cell<'de> {
+    fn deserialize<D: de::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        deserializer.deserialize_any(CellVisitor)
+    }
+}
+
+struct CellVisitor;
+
+impl<'de> Visitor<'de> for CellVisitor {
+    type Value = Cell<'de>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a number, string or null")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> std::result::Result<Self::Value, E> {
+        Ok(Cell::Null)
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> std::result::Result<Self::Value, E> {
+        Ok(Cell::Number(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> std::result::Result<Self::Value, E> {
+        Ok(Cell::Number(v as f64))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> std::result::Result<Self::Value, E> {
+        Ok(Cell::Number(v as f64))
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> std::result::Result<Self::Value, E> {
+        Ok(Cell::Text(Cow::Borrowed(v)))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> std::result::Result<Self::Value, E> {
+        Ok(Cell::Text(Cow::Owned(v.to_string())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &str = r#"{
+        "latitude": 52.52,
+        "longitude": 13.419,
+        "elevation": 44.812,
+        "generationtime_ms": 2.2119,
+        "utc_offset_seconds": 7200,
+        "timezone": "Europe/Berlin",
+        "timezone_abbreviation": "CEST",
+        "hourly": {
+            "time": ["2022-07-01T00:00", "2022-07-01T01:00", "2022-07-01T02:00"],
+            "temperature_2m": [13.0, null, 12.4],
+            "precipitation": [0.0, 0.1, 0.0]
+        },
+        "hourly_units": {
+            "time": "iso8601",
+            "temperature_2m": "°C",
+            "precipitation": "mm"
+        },
+        "daily": {
+            "time": ["2022-07-01"],
+            "temperature_2m_max": [24.1]
+        }
+    }"#;
+
+    #[test]
+    fn test_parse_borrows_from_body() {
+        let forecasts = parse(BODY.as_bytes()).unwrap();
+        assert_eq!(forecasts.len(), 1);
+
+        let forecast = &forecasts[0];
+        assert_eq!(forecast.timezone, "Europe/Berlin");
+        assert!(matches!(forecast.timezone, Cow::Borrowed(_)));
+
+        let hourly = forecast.hourly.as_ref().unwrap();
+        assert_eq!(hourly.len(), 3);
+        assert_eq!(
+            hourly.names().collect::<Vec<_>>(),
+            vec!["temperature_2m", "precipitation"]
+        );
+        match hourly.time() {
+            TimeColumn::Iso8601(times) => {
+                assert_eq!(times[1], "2022-07-01T01:00");
+                assert!(matches!(times[1], Cow::Borrowed(_)));
+            }
+            other => panic!("unexpected time column: {:?}", other),
+        }
+
+        assert_eq!(hourly.column("precipitation"), Some(&[0.0, 0.1, 0.0][..]));
+        assert!(hourly.column("temperature_2m").unwrap()[1].is_nan());
+        assert_eq!(hourly.value("temperature_2m", 0), Some(13.0));
+        assert_eq!(hourly.value("temperature_2m", 1), None);
+        assert_eq!(hourly.value("temperature_2m", 3), None);
+        assert_eq!(hourly.column("wind_speed_10m"), None);
+
+        let daily = forecast.daily.as_ref().unwrap();
+        assert_eq!(daily.value("temperature_2m_max", 0), Some(24.1));
+    }
+
+    #[test]
+    fn test_to_response_matches_map_layout() {
+        let expected: ForecastResponse = serde_json::from_str(BODY).unwrap();
+        let response = parse(BODY.as_bytes()).unwrap()[0].to_response();
+
+        let hourly = response.hourly.unwrap();
+        let expected_hourly = expected.hourly.unwrap();
+        assert_eq!(hourly.time, expected_hourly.time);
+        assert_eq!(hourly.variables, expected_hourly.variables);
+        assert_eq!(response.hourly_units, expected.hourly_units);
+        assert_eq!(
+            response.daily.unwrap().variables,
+            expected.daily.unwrap().variables
+        );
+    }
+
+    #[test]
+    fn test_parse_daily_sunrise() {
+        let body = r#"{"latitude": 1.0, "longitude": 2.0, "elevation": 0.0,
+            "generationtime_ms": 0.1, "utc_offset_seconds": 0,
+            "timezone": "GMT", "timezone_abbreviation": "GMT",
+            "daily": {
+                "time": ["2022-07-01", "2022-07-02"],
+                "sunrise": [null, "2022-07-02T04:45"],
+                "temperature_2m_max": [24.1, 25.3],
+                "sunset": ["2022-07-01T21:33", "2022-07-02T21:33"]
+            }}"#;
+        let forecast = &parse(body.as_bytes()).unwrap()[0];
+
+        let daily = forecast.daily.as_ref().unwrap();
+        assert_eq!(
+            daily.names().collect::<Vec<_>>(),
+            vec!["temperature_2m_max"]
+        );
+        assert_eq!(
+            daily.text_names().collect::<Vec<_>>(),
+            vec!["sunrise", "sunset"]
+        );
+        let sunrise = daily.text_column("sunrise").unwrap();
+        assert_eq!(sunrise[0], None);
+        assert!(matches!(
+            sunrise[1],
+            Some(Cow::Borrowed("2022-07-02T04:45"))
+        ));
+        assert_eq!(daily.value("temperature_2m_max", 1), Some(25.3));
+
+        let expected: ForecastResponse = serde_json::from_str(body).unwrap();
+        let response = forecast.to_response();
+        assert_eq!(
+            response.daily.unwrap().time_variables,
+            expected.daily.unwrap().time_variables
+        );
+    }
+
+    #[test]
+    fn test_parse_multiple_locations_unixtime() {
+        let body = r#"[
+            {"latitude": 1.0, "longitude": 2.0, "elevation": 0.0, "generationtime_ms": 0.1,
+             "utc_offset_seconds": 0, "timezone": "GMT", "timezone_abbreviation": "GMT",
+             "hourly": {"time": [1656633600, 1656637200], "temperature_2m": [1.0, 2.0]}},
+            {"latitude": 3.0, "longitude": 4.0, "elevation": 0.0, "generationtime_ms": 0.1,
+             "utc_offset_seconds": 0, "timezone": "GMT", "timezone_abbreviation": "GMT",
+             "hourly": {"time": [1656633600, 1656637200], "temperature_2m": [3.0, 4.0]}}
+        ]"#;
+
+        let forecasts = parse(body.as_bytes()).unwrap();
+        assert_eq!(forecasts.len(), 2);
+
+        let hourly = forecasts[1].hourly.as_ref().unwrap();
+        assert_eq!(
+            hourly.time(),
+            &TimeColumn::Unixtime(vec![1656633600, 1656637200])
+        );
+        assert_eq!(hourly.column("temperature_2m"), Some(&[3.0, 4.0][..]));
+        assert_eq!(hourly.time().get(0).as_deref(), Some("1656633600"));
+    }
+
+    #[test]
+    fn test_parse_rejects_ragged_columns() {
+        let body = r#"{"latitude": 1.0, "longitude": 2.0, "elevation": 0.0,
+            "generationtime_ms": 0.1, "utc_offset_seconds": 0,
+            "timezone": "GMT", "timezone_abbreviation": "GMT",
+            "hourly": {"time": ["2022-07-01T00:00"], "temperature_2m": [1.0, 2.0]}}"#;
+        let error = parse(body.as_bytes()).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("`temperature_2m` has 2 values, expected 1")
+        );
+
+        let body = r#"{"latitude": 1.0, "longitude": 2.0, "elevation": 0.0,
+            "generationtime_ms": 0.1, "utc_offset_seconds": 0,
+            "timezone": "GMT", "timezone_abbreviation": "GMT",
+            "hourly": {"temperature_2m": [1.0]}}"#;
+        assert!(parse(body.as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,135 @@
+//! Endpoint URLs for the Open-Meteo sub-APIs
+//!
+//! Open-Meteo serves each API from its own host (`api.open-meteo.com`,
+//! `geocoding-api.open-meteo.com`, ...), commercial subscriptions use the
+//! `customer-` prefixed hosts, and a self-hosted instance serves every API
+//! under a single host.
+
+pub(crate) const FORECAST_API_BASE: &str = "https://api.open-meteo.com/v1/forecast";
+pub(crate) const GEOCODING_API_BASE: &str = "https://geocoding-api.open-meteo.com/v1/search";
+
+/// Paths of each sub-API relative to its host
+const FORECAST_PATH: &str = "/v1/forecast";
+const GEOCODING_PATH: &str = "/v1/search";
+const ARCHIVE_PATH: &str = "/v1/archive";
+const HISTORICAL_FORECAST_PATH: &str = "/v1/forecast";
+const ENSEMBLE_PATH: &str = "/v1/ensemble";
+const AIR_QUALITY_PATH: &str = "/v1/air-quality";
+const MARINE_PATH: &str = "/v1/marine";
+const FLOOD_PATH: &str = "/v1/flood";
+const ELEVATION_PATH: &str = "/v1/elevation";
+
+/// Full endpoint URL for every Open-Meteo sub-API
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoints {
+    pub forecast: String,
+    pub geocoding: String,
+    pub archive: String,
+    pub historical_forecast: String,
+    pub ensemble: String,
+    pub air_quality: String,
+    pub marine: String,
+    pub flood: String,
+    pub elevation: String,
+}
+
+impl Endpoints {
+    /// Public endpoints of the free Open-Meteo API
+    pub fn open_meteo() -> Self {
+        Self::from_hosts("")
+    }
+
+    /// Endpoints for commercial subscriptions (`customer-*.open-meteo.com`)
+    pub fn customer() -> Self {
+        Self::from_hosts("customer-")
+    }
+
+    /// Endpoints of a self-hosted instance serving every API from one host
+    ///
+    /// `host` is a scheme and authority such as `http://weather.internal:8080`;
+    /// each sub-API path (`/v1/forecast`, `/v1/search`, ...) is derived from it.
+    pub fn self_hosted(host: impl AsRef<str>) -> Self {
+        let host = host.as_ref().trim_end_matches('/');
+        Self {
+            forecast: format!("{}{}", host, FORECAST_PATH),
+            geocoding: format!("{}{}", host, GEOCODING_PATH),
+            archive: format!("{}{}", host, ARCHIVE_PATH),
+            historical_forecast: format!("{}{}", host, HISTORICAL_FORECAST_PATH),
+            ensemble: format!("{}{}", host, ENSEMBLE_PATH),
+            air_quality: format!("{}{}", host, AIR_QUALITY_PATH),
+            marine: format!("{}{}", host, MARINE_PATH),
+            flood: format!("{}{}", host, FLOOD_PATH),
+            elevation: format!("{}{}", host, ELEVATION_PATH),
+        }
+    }
+
+    fn from_hosts(prefix: &str) -> Self {
+        let url = |subdomain: &str, path: &str| {
+            format!("https://{}{}.open-meteo.com{}", prefix, subdomain, path)
+        };
+        Self {
+            forecast: url("api", FORECAST_PATH),
+            geocoding: url("geocoding-api", GEOCODING_PATH),
+            archive: url("archive-api", ARCHIVE_PATH),
+            historical_forecast: url("historical-forecast-api", HISTORICAL_FORECAST_PATH),
+            ensemble: url("ensemble-api", ENSEMBLE_PATH),
+            air_quality: url("air-quality-api", AIR_QUALITY_PATH),
+            marine: url("marine-api", MARINE_PATH),
+            flood: url("flood-api", FLOOD_PATH),
+            elevation: url("api", ELEVATION_PATH),
+        }
+    }
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self::open_meteo()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_meteo_endpoints() {
+        let endpoints = Endpoints::open_meteo();
+        assert_eq!(endpoints.forecast, FORECAST_API_BASE);
+        assert_eq!(endpoints.geocoding, GEOCODING_API_BASE);
+        assert_eq!(
+            endpoints.archive,
+            "https://archive-api.open-meteo.com/v1/archive"
+        );
+    }
+
+    #[test]
+    fn test_customer_endpoints() {
+        let endpoints = Endpoints::customer();
+        assert_eq!(
+            endpoints.forecast,
+            "https://customer-api.open-meteo.com/v1/forecast"
+        );
+        assert_eq!(
+            endpoints.geocoding,
+            "https://customer-geocoding-api.open-meteo.com/v1/search"
+        );
+    }
+
+    #[test]
+    fn test_self_hosted_endpoints() {
+        let endpoints = Endpoints::self_hosted("http://weather.internal:8080/");
+        assert_eq!(
+            endpoints.forecast,
+            "http://weather.internal:8080/v1/forecast"
+        );
+        assert_eq!(
+            endpoints.geocoding,
+            "http://weather.internal:8080/v1/search"
+        );
+        assert_eq!(endpoints.archive, "http://weather.internal:8080/v1/archive");
+        assert_eq!(
+            endpoints.air_quality,
+            "http://weather.internal:8080/v1/air-quality"
+        );
+    }
+}
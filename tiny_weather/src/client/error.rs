@@ -1,5 +1,8 @@
 //! Error types for Open-Meteo API clients
 
+use std::time::Duration;
+
+use reqwest::StatusCode;
 use thiserror::Error;
 
 /// Errors that can occur when interacting with Open-Meteo APIs
@@ -9,14 +12,49 @@ pub enum OpenMeteoError {
     #[error("HTTP request failed: {0}")]
     RequestFailed(#[from] reqwest::Error),
 
-    /// API returned an error response
-    #[error("API error: {reason}")]
-    ApiError { reason: String },
+    /// Request did not complete before the HTTP client timeout
+    #[error("Request timed out: {url}")]
+    Timeout { url: String },
+
+    /// API rejected the request (4xx other than 429, or an unexpected status)
+    #[error("API error ({status}): {reason}")]
+    ApiError {
+        status: StatusCode,
+        url: String,
+        reason: String,
+    },
+
+    /// API is rate limiting this client (HTTP 429)
+    #[error("Rate limited by API{}", retry_after.map(|d| format!(", retry after {}s", d.as_secs())).unwrap_or_default())]
+    RateLimited {
+        url: String,
+        retry_after: Option<Duration>,
+    },
+
+    /// API failed to handle the request (5xx)
+    #[error("Server error ({status}): {reason}")]
+    ServerError {
+        status: StatusCode,
+        url: String,
+        reason: String,
+    },
 
     /// Failed to deserialize API response
     #[error("Failed to deserialize response: {0}")]
     DeserializationError(#[from] serde_json::Error),
 
+    /// Response body could not be decoded (non-JSON formats)
+    #[error("Invalid response: {0}")]
+    InvalidResponse(String),
+
+    /// Failed to write a downloaded response to disk
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Failed to convert or export forecast data (Arrow, Polars, Parquet)
+    #[error("Export failed: {0}")]
+    Export(String),
+
     /// Invalid parameter provided to API call
     #[error("Invalid parameter: {0}")]
     InvalidParameter(String),
@@ -26,5 +64,97 @@ pub enum OpenMeteoError {
     NoResults,
 }
 
+impl OpenMeteoError {
+    /// HTTP status returned by the API, if the request got that far
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Self::ApiError { status, .. } | Self::ServerError { status, .. } => Some(*status),
+            Self::RateLimited { .. } => Some(StatusCode::TOO_MANY_REQUESTS),
+            Self::RequestFailed(e) => e.status(),
+            _ => None,
+        }
+    }
+
+    /// Request URL (with secrets redacted), if known
+    pub fn url(&self) -> Option<&str> {
+        match self {
+            Self::Timeout { url }
+            | Self::ApiError { url, .. }
+            | Self::RateLimited { url, .. }
+            | Self::ServerError { url, .. } => Some(url),
+            _ => None,
+        }
+    }
+
+    /// How long the API asked us to wait before retrying (from `Retry-After`)
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::RateLimited { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// Whether repeating the same request later may succeed
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Timeout { .. } | Self::RateLimited { .. } | Self::ServerError { .. } => true,
+            Self::RequestFailed(e) => e.is_connect() || e.is_timeout(),
+            _ => false,
+        }
+    }
+
+    /// Whether the request itself was invalid and must be changed before retrying
+    ///
+    /// `RateLimited` (HTTP 429) is not a client error despite its 4xx status:
+    /// the same request succeeds once the limit resets, see [`Self::is_retryable`].
+    pub fn is_client_error(&self) -> bool {
+        match self {
+            Self::ApiError { status, .. } => status.is_client_error(),
+            Self::InvalidParameter(_) => true,
+            _ => false,
+        }
+    }
+}
+
 /// Result type alias for Open-Meteo operations
 pub type Result<T> = std::result::Result<T, OpenMeteoError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_classification() {
+        let bad_request = OpenMeteoError::ApiError {
+            status: StatusCode::BAD_REQUEST,
+            url: "https://example.com".to_string(),
+            reason: "Invalid latitude".to_string(),
+        };
+        assert!(bad_request.is_client_error());
+        assert!(!bad_request.is_retryable());
+        assert_eq!(bad_request.status(), Some(StatusCode::BAD_REQUEST));
+
+        let rate_limited = OpenMeteoError::RateLimited {
+            url: "https://example.com".to_string(),
+            retry_after: Some(Duration::from_secs(30)),
+        };
+        assert!(!rate_limited.is_client_error());
+        assert!(rate_limited.is_retryable());
+        assert_eq!(rate_limited.retry_after(), Some(Duration::from_secs(30)));
+        assert_eq!(
+            rate_limited.to_string(),
+            "Rate limited by API, retry after 30s"
+        );
+
+        let unavailable = OpenMeteoError::ServerError {
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            url: "https://example.com".to_string(),
+            reason: "Maintenance".to_string(),
+        };
+        assert!(unavailable.is_retryable());
+        assert_eq!(unavailable.status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+
+        assert!(!OpenMeteoError::NoResults.is_retryable());
+        assert!(!OpenMeteoError::NoResults.is_client_error());
+    }
+}
@@ -0,0 +1,934 @@
+//! Decoder for the Open-Meteo FlatBuffers response format (`format=flatbuffers`)
+//!
+//! The API returns one size-prefixed `WeatherApiResponse` message per location
+//! and model (schema: <https://github.com/open-meteo/sdk>). Each variable is encoded as an
+//! enum plus altitude, pressure level, depth and aggregation attributes; this
+//! module reassembles the JSON variable names (e.g. `temperature_2m_max`) so the
+//! result decodes into the same [`ForecastResponse`] types as JSON. Messages of
+//! several models for one location are merged into one response with the
+//! variables suffixed by model id, as the JSON format does.
+//!
+//! The reader is bounds-checked and does not depend on generated code.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::DateTime;
+use tracing::warn;
+
+use crate::client::error::{OpenMeteoError, Result};
+use crate::client::types::common::TimeFormat;
+use crate::client::types::forecast::{CurrentData, DailyData, ForecastResponse, HourlyData};
+use crate::client::types::model::WeatherModel;
+
+/// Vtable slots of the `WeatherApiResponse` table
+mod response {
+    pub const LATITUDE: usize = 0;
+    pub const LONGITUDE: usize = 1;
+    pub const ELEVATION: usize = 2;
+    pub const GENERATION_TIME_MS: usize = 3;
+    pub const LOCATION_ID: usize = 4;
+    pub const MODEL: usize = 5;
+    pub const UTC_OFFSET_SECONDS: usize = 6;
+    pub const TIMEZONE: usize = 7;
+    pub const TIMEZONE_ABBREVIATION: usize = 8;
+    pub const CURRENT: usize = 9;
+    pub const DAILY: usize = 10;
+    pub const HOURLY: usize = 11;
+}
+
+/// Vtable slots of the `VariablesWithTime` table
+mod series {
+    pub const TIME: usize = 0;
+    pub const TIME_END: usize = 1;
+    pub const INTERVAL: usize = 2;
+    pub const VARIABLES: usize = 3;
+}
+
+/// Vtable slots of the `VariableWithValues` table
+mod variable {
+    pub const VARIABLE: usize = 0;
+    pub const UNIT: usize = 1;
+    pub const VALUE: usize = 2;
+    pub const VALUES: usize = 3;
+    pub const VALUES_INT64: usize = 4;
+    pub const ALTITUDE: usize = 5;
+    pub const AGGREGATION: usize = 6;
+    pub const PRESSURE_LEVEL: usize = 7;
+    pub const DEPTH: usize = 8;
+    pub const DEPTH_TO: usize = 9;
+    pub const ENSEMBLE_MEMBER: usize = 10;
+}
+
+/// `Variable` enum of the SDK schema, indexed by its discriminant
+const VARIABLE_NAMES: &[&str] = &[
+    "undefined",
+    "apparent_temperature",
+    "cape",
+    "cloud_cover",
+    "cloud_cover_high",
+    "cloud_cover_low",
+    "cloud_cover_mid",
+    "daylight_duration",
+    "dew_point",
+    "diffuse_radiation",
+    "diffuse_radiation_instant",
+    "direct_normal_irradiance",
+    "direct_normal_irradiance_instant",
+    "direct_radiation",
+    "direct_radiation_instant",
+    "et0_fao_evapotranspiration",
+    "evapotranspiration",
+    "freezing_level_height",
+    "growing_degree_days",
+    "is_day",
+    "latent_heat_flux",
+    "leaf_wetness_probability",
+    "lifted_index",
+    "lightning_potential",
+    "precipitation",
+    "precipitation_hours",
+    "precipitation_probability",
+    "pressure_msl",
+    "rain",
+    "relative_humidity",
+    "runoff",
+    "sensible_heat_flux",
+    "shortwave_radiation",
+    "shortwave_radiation_instant",
+    "showers",
+    "snow_depth",
+    "snow_height",
+    "snowfall",
+    "snowfall_height",
+    "snowfall_water_equivalent",
+    "sunrise",
+    "sunset",
+    "soil_moisture",
+    "soil_moisture_index",
+    "soil_temperature",
+    "surface_pressure",
+    "surface_temperature",
+    "temperature",
+    "terrestrial_radiation",
+    "terrestrial_radiation_instant",
+    "total_column_integrated_water_vapour",
+    "updraft",
+    "uv_index",
+    "uv_index_clear_sky",
+    "vapour_pressure_deficit",
+    "visibility",
+    "weather_code",
+    "wind_direction",
+    "wind_gusts",
+    "wind_speed",
+    "vertical_velocity",
+    "geopotential_height",
+    "wet_bulb_temperature",
+];
+
+/// `Unit` enum of the SDK schema mapped to the unit strings used by the JSON format
+const UNIT_NAMES: &[&str] = &[
+    "",
+    "°C",
+    "cm",
+    "m³/m³",
+    "m³/s",
+    "°",
+    "",
+    "",
+    "EAQI",
+    "°F",
+    "ft",
+    "",
+    "GDD",
+    "gpm",
+    "grains/m³",
+    "g/kg",
+    "hPa",
+    "h",
+    "inch",
+    "iso8601",
+    "J/kg",
+    "K",
+    "kPa",
+    "kg/m²",
+    "km/h",
+    "kn",
+    "MJ/m²",
+    "μg/m³",
+    "mp/h",
+    "mm",
+    "m",
+    "m/s",
+    "min",
+    "%",
+    "s",
+    "unixtime",
+    "USAQI",
+    "W/m²",
+    "wmo code",
+    "ppm",
+];
+
+/// `Aggregation` enum of the SDK schema mapped to JSON variable name suffixes
+const AGGREGATION_SUFFIXES: &[&str] = &[
+    "",
+    "_min",
+    "_max",
+    "_mean",
+    "_p10",
+    "_p25",
+    "_median",
+    "_p75",
+    "_p90",
+    "_dominant",
+    "_sum",
+    "_spread",
+];
+
+/// `Model` enum discriminants of the SDK schema for every [`WeatherModel`]
+const MODEL_IDS: &[(u8, WeatherModel)] = &[
+    (1, WeatherModel::BestMatch),
+    (2, WeatherModel::GfsSeamless),
+    (3, WeatherModel::GfsGlobal),
+    (4, WeatherModel::GfsHrrr),
+    (5, WeatherModel::MeteoFranceSeamless),
+    (7, WeatherModel::MeteoFranceArpegeWorld),
+    (8, WeatherModel::MeteoFranceArpegeEurope),
+    (10, WeatherModel::MeteoFranceAromeFrance),
+    (11, WeatherModel::MeteoFranceAromeFranceHd),
+    (20, WeatherModel::IconSeamless),
+    (21, WeatherModel::IconGlobal),
+    (22, WeatherModel::IconEu),
+    (23, WeatherModel::IconD2),
+    (53, WeatherModel::CmaGrapesGlobal),
+    (54, WeatherModel::BomAccessGlobal),
+    (60, WeatherModel::EcmwfIfs025),
+    (80, WeatherModel::UkmoGlobal10km),
+    (81, WeatherModel::UkmoUk2km),
+    (82, WeatherModel::UkmoSeamless),
+];
+
+/// Decode a FlatBuffers response body into one [`ForecastResponse`] per location
+///
+/// `timeformat` controls how timestamps are rendered into the `time` columns,
+/// matching what the JSON API would have returned for the same request.
+pub fn decode_forecasts(bytes: &[u8], timeformat: TimeFormat) -> Result<Vec<ForecastResponse>> {
+    Ok(decode_locations(bytes, timeformat)?
+        .into_iter()
+        .map(|(_, forecast)| forecast)
+        .collect())
+}
+
+/// Decode a body into one response per location, ordered by and paired with
+/// the index of the location in the request (`location_id`)
+pub(crate) fn decode_locations(
+    bytes: &[u8],
+    timeformat: TimeFormat,
+) -> Result<Vec<(usize, ForecastResponse)>> {
+    let mut messages: BTreeMap<i64, Vec<Message>> = BTreeMap::new();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let len = read_u32(bytes, pos)? as usize;
+        let start = pos + 4;
+        let message = bytes
+            .get(start..start + len)
+            .ok_or_else(|| invalid("message length exceeds body"))?;
+        let message = decode_message(message, timeformat)?;
+        messages
+            .entry(message.location_id)
+            .or_default()
+            .push(message);
+        pos = start + len;
+    }
+
+    messages
+        .into_iter()
+        .map(|(location_id, messages)| {
+            let location_id =
+                usize::try_from(location_id).map_err(|_| invalid("negative location id"))?;
+            Ok((location_id, merge_models(messages)?))
+        })
+        .collect()
+}
+
+/// One decoded `WeatherApiResponse`: a single location and model
+struct Message {
+    location_id: i64,
+    model: u8,
+    forecast: ForecastResponse,
+}
+
+/// Merge the messages of one location into a single response
+///
+/// A lone message is returned as is; with several models every variable is
+/// suffixed with its model id (`temperature_2m_icon_d2`), and a model missing
+/// from [`MODEL_IDS`] is an error rather than a silently dropped series.
+fn merge_models(mut messages: Vec<Message>) -> Result<ForecastResponse> {
+    if messages.len() == 1 {
+        return Ok(messages.remove(0).forecast);
+    }
+
+    let mut merged: Option<ForecastResponse> = None;
+    for message in messages {
+        // Without a name its variables could not be told apart from the others
+        let &(_, model) = MODEL_IDS
+            .iter()
+            .find(|(id, _)| *id == message.model)
+            .ok_or_else(|| invalid(&format!("unknown model id {}", message.model)))?;
+        let forecast = suffix_variables(message.forecast, model.id());
+        let Some(target) = &mut merged else {
+            merged = Some(forecast);
+            continue;
+        };
+        merge(&mut target.hourly, forecast.hourly, |into, from| {
+            into.variables.extend(from.variables)
+        });
+        merge(&mut target.daily, forecast.daily, |into, from| {
+            into.variables.extend(from.variables);
+            into.time_variables.extend(from.time_variables);
+        });
+        merge(&mut target.current, forecast.current, |into, from| {
+            into.variables.extend(from.variables)
+        });
+        merge(
+            &mut target.hourly_units,
+            forecast.hourly_units,
+            Extend::extend,
+        );
+        merge(
+            &mut target.daily_units,
+            forecast.daily_units,
+            Extend::extend,
+        );
+        merge(
+            &mut target.current_units,
+            forecast.current_units,
+            Extend::extend,
+        );
+    }
+    merged.ok_or_else(|| invalid("no message of a known model"))
+}
+
+fn merge<T>(into: &mut Option<T>, from: Option<T>, extend: impl FnOnce(&mut T, T)) {
+    let Some(from) = from else {
+        return;
+    };
+    match into {
+        Some(into) => extend(into, from),
+        None => *into = Some(from),
+    }
+}
+
+/// Rename every variable to `<variable>_<model>`, keeping `time` and `interval`
+fn suffix_variables(forecast: ForecastResponse, model: &str) -> ForecastResponse {
+    fn suffix<V>(map: HashMap<String, V>, model: &str) -> HashMap<String, V> {
+        map.into_iter()
+            .map(|(name, value)| match name.as_str() {
+                "time" | "interval" => (name, value),
+                _ => (format!("{}_{}", name, model), value),
+            })
+            .collect()
+    }
+
+    ForecastResponse {
+        hourly: forecast.hourly.map(|hourly| HourlyData {
+            time: hourly.time,
+            variables: suffix(hourly.variables, model),
+        }),
+        hourly_units: forecast.hourly_units.map(|units| suffix(units, model)),
+        daily: forecast.daily.map(|daily| DailyData {
+            time: daily.time,
+            variables: suffix(daily.variables, model),
+            time_variables: suffix(daily.time_variables, model),
+        }),
+        daily_units: forecast.daily_units.map(|units| suffix(units, model)),
+        current: forecast.current.map(|current| CurrentData {
+            time: current.time,
+            variables: suffix(current.variables, model),
+        }),
+        current_units: forecast.current_units.map(|units| suffix(units, model)),
+        ..forecast
+    }
+}
+
+fn decode_message(buf: &[u8], timeformat: TimeFormat) -> Result<Message> {
+    let root = Table::new(buf, read_u32(buf, 0)? as usize)?;
+    let utc_offset_seconds = root.i32(response::UTC_OFFSET_SECONDS)?;
+    let decoder = SeriesDecoder {
+        utc_offset_seconds,
+        timeformat,
+    };
+
+    let mut forecast = ForecastResponse {
+        latitude: f64::from(root.f32(response::LATITUDE)?),
+        longitude: f64::from(root.f32(response::LONGITUDE)?),
+        elevation: f64::from(root.f32(response::ELEVATION)?),
+        generationtime_ms: f64::from(root.f32(response::GENERATION_TIME_MS)?),
+        utc_offset_seconds,
+        timezone: root
+            .string(response::TIMEZONE)?
+            .unwrap_or("GMT")
+            .to_string(),
+        timezone_abbreviation: root
+            .string(response::TIMEZONE_ABBREVIATION)?
+            .unwrap_or("GMT")
+            .to_string(),
+        hourly: None,
+        hourly_units: None,
+        daily: None,
+        daily_units: None,
+        current: None,
+        current_units: None,
+    };
+
+    if let Some(hourly) = root.table(response::HOURLY)? {
+        let (time, variables, _, units) = decoder.series(&hourly, "%Y-%m-%dT%H:%M")?;
+        forecast.hourly = Some(HourlyData { time, variables });
+        forecast.hourly_units = Some(units);
+    }
+
+    if let Some(daily) = root.table(response::DAILY)? {
+        let (time, variables, time_variables, units) = decoder.series(&daily, "%Y-%m-%d")?;
+        forecast.daily = Some(DailyData {
+            time,
+            variables,
+            time_variables,
+        });
+        forecast.daily_units = Some(units);
+    }
+
+    if let Some(current) = root.table(response::CURRENT)? {
+        let (time, variables, units) = decoder.current(&current)?;
+        forecast.current = Some(CurrentData { time, variables });
+        forecast.current_units = Some(units);
+    }
+
+    Ok(Message {
+        location_id: root.i64(response::LOCATION_ID)?,
+        model: root.u8(response::MODEL)?,
+        forecast,
+    })
+}
+
+type Series = (
+    Vec<String>,
+    HashMap<String, Vec<Option<f64>>>,
+    HashMap<String, Vec<Option<String>>>,
+    HashMap<String, String>,
+);
+
+type Current = (
+    String,
+    HashMap<String, Option<f64>>,
+    HashMap<String, String>,
+);
+
+struct SeriesDecoder {
+    utc_offset_seconds: i32,
+    timeformat: TimeFormat,
+}
+
+impl SeriesDecoder {
+    fn series(&self, table: &Table<'_>, date_format: &str) -> Result<Series> {
+        let start = table.i64(series::TIME)?;
+        let end = table.i64(series::TIME_END)?;
+        let interval = i64::from(table.i32(series::INTERVAL)?);
+        if interval <= 0 {
+            return Err(invalid("non-positive time interval"));
+        }
+
+        let len = usize::try_from((end - start) / interval).unwrap_or(0);
+        let time = (0..len as i64)
+            .map(|i| self.format_time(start + i * interval, date_format))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut variables = HashMap::new();
+        let mut time_variables = HashMap::new();
+        let mut units = self.units_with_time();
+        for var in table.tables(series::VARIABLES)? {
+            let Some(name) = variable_name(&var)? else {
+                continue;
+            };
+            units.insert(name.clone(), unit_name(&var)?);
+            if let Some(values) = var.f32s(variable::VALUES)? {
+                variables.insert(name, values.map(finite).collect());
+                continue;
+            }
+
+            // Int64 values are timestamps (sunrise, sunset), rendered like `time`
+            let values: Vec<i64> = var
+                .i64s(variable::VALUES_INT64)?
+                .map(|values| values.collect())
+                .unwrap_or_default();
+            match self.timeformat {
+                TimeFormat::Unixtime => {
+                    variables.insert(name, values.iter().map(|v| Some(*v as f64)).collect());
+                }
+                TimeFormat::Iso8601 => {
+                    let times = values
+                        .iter()
+                        .map(|v| self.format_time(*v, "%Y-%m-%dT%H:%M").map(Some))
+                        .collect::<Result<Vec<_>>>()?;
+                    time_variables.insert(name, times);
+                }
+            }
+        }
+
+        Ok((time, variables, time_variables, units))
+    }
+
+    fn current(&self, table: &Table<'_>) -> Result<Current> {
+        let time = self.format_time(table.i64(series::TIME)?, "%Y-%m-%dT%H:%M")?;
+
+        let mut variables = HashMap::new();
+        let mut units = self.units_with_time();
+        variables.insert(
+            "interval".to_string(),
+            Some(f64::from(table.i32(series::INTERVAL)?)),
+        );
+        units.insert("interval".to_string(), "seconds".to_string());
+        for var in table.tables(series::VARIABLES)? {
+            let Some(name) = variable_name(&var)? else {
+                continue;
+            };
+            units.insert(name.clone(), unit_name(&var)?);
+            variables.insert(name, finite(var.f32(variable::VALUE)?));
+        }
+
+        Ok((time, variables, units))
+    }
+
+    fn units_with_time(&self) -> HashMap<String, String> {
+        let time_unit = match self.timeformat {
+            TimeFormat::Iso8601 => "iso8601",
+            TimeFormat::Unixtime => "unixtime",
+        };
+        HashMap::from([("time".to_string(), time_unit.to_string())])
+    }
+
+    fn format_time(&self, unix: i64, date_format: &str) -> Result<String> {
+        match self.timeformat {
+            TimeFormat::Unixtime => Ok(unix.to_string()),
+            TimeFormat::Iso8601 => {
+                let local = unix + i64::from(self.utc_offset_seconds);
+                let time = DateTime::from_timestamp(local, 0)
+                    .ok_or_else(|| invalid("timestamp out of range"))?;
+                Ok(time.naive_utc().format(date_format).to_string())
+            }
+        }
+    }
+}
+
+/// Rebuild the JSON variable name from the enum and its attributes
+fn variable_name(var: &Table<'_>) -> Result<Option<String>> {
+    let id = var.u8(variable::VARIABLE)? as usize;
+    let Some(base) = VARIABLE_NAMES.get(id).filter(|_| id != 0) else {
+        warn!("Skipping unknown FlatBuffers variable {}", id);
+        return Ok(None);
+    };
+
+    let mut name = base.to_string();
+    let altitude = var.i16(variable::ALTITUDE)?;
+    let pressure_level = var.i16(variable::PRESSURE_LEVEL)?;
+    if altitude > 0 {
+        name.push_str(&format!("_{}m", altitude));
+    } else if pressure_level > 0 {
+        name.push_str(&format!("_{}hPa", pressure_level));
+    } else if *base == "soil_moisture" {
+        let depth = var.i16(variable::DEPTH)?;
+        let depth_to = var.i16(variable::DEPTH_TO)?;
+        name.push_str(&format!("_{}_to_{}cm", depth, depth_to));
+    } else if *base == "soil_temperature" {
+        name.push_str(&format!("_{}cm", var.i16(variable::DEPTH)?));
+    }
+
+    let aggregation = var.u8(variable::AGGREGATION)? as usize;
+    name.push_str(AGGREGATION_SUFFIXES.get(aggregation).copied().unwrap_or(""));
+
+    let member = var.i16(variable::ENSEMBLE_MEMBER)?;
+    if member > 0 {
+        name.push_str(&format!("_member{:02}", member));
+    }
+
+    Ok(Some(name))
+}
+
+fn unit_name(var: &Table<'_>) -> Result<String> {
+    let id = var.u8(variable::UNIT)? as usize;
+    Ok(UNIT_NAMES.get(id).copied().unwrap_or("").to_string())
+}
+
+/// Missing values are encoded as NaN
+fn finite(value: f32) -> Option<f64> {
+    value.is_finite().then(|| f64::from(value))
+}
+
+fn invalid(reason: &str) -> OpenMeteoError {
+    OpenMeteoError::InvalidResponse(format!("FlatBuffers: {}", reason))
+}
+
+fn read_bytes<const N: usize>(buf: &[u8], pos: usize) -> Result<[u8; N]> {
+    buf.get(pos..pos + N)
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| invalid("read out of bounds"))
+}
+
+fn read_u32(buf: &[u8], pos: usize) -> Result<u32> {
+    read_bytes(buf, pos).map(u32::from_le_bytes)
+}
+
+/// A FlatBuffers table: a position in the buffer plus its vtable
+struct Table<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    vtable: usize,
+    vtable_len: usize,
+}
+
+impl<'a> Table<'a> {
+    fn new(buf: &'a [u8], pos: usize) -> Result<Self> {
+        let soffset = i32::from_le_bytes(read_bytes(buf, pos)?);
+        let vtable = usize::try_from(pos as i64 - i64::from(soffset))
+            .map_err(|_| invalid("vtable out of bounds"))?;
+        let vtable_len = u16::from_le_bytes(read_bytes(buf, vtable)?) as usize;
+        Ok(Self {
+            buf,
+            pos,
+            vtable,
+            vtable_len,
+        })
+    }
+
+    /// Absolute position of a field, or `None` if it is absent (default value)
+    fn field(&self, slot: usize) -> Result<Option<usize>> {
+        let entry = 4 + 2 * slot;
+        if entry + 2 > self.vtable_len {
+            return Ok(None);
+        }
+        let offset = u16::from_le_bytes(read_bytes(self.buf, self.vtable + entry)?) as usize;
+        Ok((offset != 0).then_some(self.pos + offset))
+    }
+
+    fn scalar<const N: usize>(&self, slot: usize) -> Result<Option<[u8; N]>> {
+        self.field(slot)?
+            .map(|pos| read_bytes(self.buf, pos))
+            .transpose()
+    }
+
+    fn u8(&self, slot: usize) -> Result<u8> {
+        Ok(self.scalar(slot)?.map(u8::from_le_bytes).unwrap_or(0))
+    }
+
+    fn i16(&self, slot: usize) -> Result<i16> {
+        Ok(self.scalar(slot)?.map(i16::from_le_bytes).unwrap_or(0))
+    }
+
+    fn i32(&self, slot: usize) -> Result<i32> {
+        Ok(self.scalar(slot)?.map(i32::from_le_bytes).unwrap_or(0))
+    }
+
+    fn i64(&self, slot: usize) -> Result<i64> {
+        Ok(self.scalar(slot)?.map(i64::from_le_bytes).unwrap_or(0))
+    }
+
+    fn f32(&self, slot: usize) -> Result<f32> {
+        Ok(self.scalar(slot)?.map(f32::from_le_bytes).unwrap_or(0.0))
+    }
+
+    /// Follow a uoffset field to the position it references
+    fn indirect(&self, slot: usize) -> Result<Option<usize>> {
+        self.field(slot)?
+            .map(|pos| Ok(pos + read_u32(self.buf, pos)? as usize))
+            .transpose()
+    }
+
+    fn table(&self, slot: usize) -> Result<Option<Table<'a>>> {
+        self.indirect(slot)?
+            .map(|pos| Table::new(self.buf, pos))
+            .transpose()
+    }
+
+    /// Start and element count of a vector field
+    fn vector(&self, slot: usize, elem_size: usize) -> Result<Option<(usize, usize)>> {
+        let Some(pos) = self.indirect(slot)? else {
+            return Ok(None);
+        };
+        let len = read_u32(self.buf, pos)? as usize;
+        let start = pos + 4;
+        if self.buf.len() < start + len * elem_size {
+            return Err(invalid("vector out of bounds"));
+        }
+        Ok(Some((start, len)))
+    }
+
+    fn string(&self, slot: usize) -> Result<Option<&'a str>> {
+        let Some((start, len)) = self.vector(slot, 1)? else {
+            return Ok(None);
+        };
+        std::str::from_utf8(&self.buf[start..start + len])
+            .map(Some)
+            .map_err(|_| invalid("string is not UTF-8"))
+    }
+
+    fn f32s(&self, slot: usize) -> Result<Option<impl Iterator<Item = f32> + 'a>> {
+        let buf = self.buf;
+        Ok(self.vector(slot, 4)?.map(move |(start, len)| {
+            buf[start..start + len * 4]
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        }))
+    }
+
+    fn i64s(&self, slot: usize) -> Result<Option<impl Iterator<Item = i64> + 'a>> {
+        let buf = self.buf;
+        Ok(self.vector(slot, 8)?.map(move |(start, len)| {
+            buf[start..start + len * 8]
+                .chunks_exact(8)
+                .map(|b| i64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]))
+        }))
+    }
+
+    fn tables(&self, slot: usize) -> Result<Vec<Table<'a>>> {
+        let Some((start, len)) = self.vector(slot, 4)? else {
+            return Ok(Vec::new());
+        };
+        (0..len)
+            .map(|i| {
+                let pos = start + i * 4;
+                Table::new(self.buf, pos + read_u32(self.buf, pos)? as usize)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flatbuffers::FlatBufferBuilder;
+
+    /// Build one size-prefixed `WeatherApiResponse` with hourly temperature_2m
+    /// and precipitation, daily temperature_2m_max and current temperature_2m
+    fn build_message(latitude: f32, longitude: f32) -> Vec<u8> {
+        build_model_message(latitude, longitude, 0, 1)
+    }
+
+    /// Like [`build_message`], for one location and model of a larger request
+    fn build_model_message(latitude: f32, longitude: f32, location_id: i64, model: u8) -> Vec<u8> {
+        let slot = |i: usize| (4 + 2 * i) as u16;
+        let mut fbb = FlatBufferBuilder::new();
+
+        let variable_table = |fbb: &mut FlatBufferBuilder<'_>,
+                              var: u8,
+                              unit: u8,
+                              altitude: i16,
+                              aggregation: u8,
+                              values: Option<&[f32]>,
+                              value: f32| {
+            let values = values.map(|v| fbb.create_vector(v));
+            let start = fbb.start_table();
+            if let Some(values) = values {
+                fbb.push_slot_always(slot(variable::VALUES), values);
+            }
+            fbb.push_slot(slot(variable::VALUE), value, 0.0);
+            fbb.push_slot(slot(variable::ALTITUDE), altitude, 0);
+            fbb.push_slot(slot(variable::VARIABLE), var, 0);
+            fbb.push_slot(slot(variable::UNIT), unit, 0);
+            fbb.push_slot(slot(variable::AGGREGATION), aggregation, 0);
+            fbb.end_table(start)
+        };
+
+        let temperature = variable_table(&mut fbb, 47, 1, 2, 0, Some(&[13.0, 12.5, f32::NAN]), 0.0);
+        let precipitation = variable_table(&mut fbb, 24, 29, 0, 0, Some(&[0.0, 0.4, 1.2]), 0.0);
+        let temperature_max = variable_table(&mut fbb, 47, 1, 2, 2, Some(&[18.5]), 0.0);
+        let current_temperature = variable_table(&mut fbb, 47, 1, 2, 0, None, 14.5);
+
+        let series_table =
+            |fbb: &mut FlatBufferBuilder<'_>,
+             time: i64,
+             time_end: i64,
+             interval: i32,
+             vars: &[flatbuffers::WIPOffset<flatbuffers::TableFinishedWIPOffset>]| {
+                let vars = fbb.create_vector(vars);
+                let start = fbb.start_table();
+                fbb.push_slot(slot(series::TIME), time, 0);
+                fbb.push_slot(slot(series::TIME_END), time_end, 0);
+                fbb.push_slot_always(slot(series::VARIABLES), vars);
+                fbb.push_slot(slot(series::INTERVAL), interval, 0);
+                fbb.end_table(start)
+            };
+
+        // 2022-07-01T00:00Z, three hours / one day, UTC+2
+        let hourly = series_table(
+            &mut fbb,
+            1_656_633_600,
+            1_656_644_400,
+            3600,
+            &[temperature, precipitation],
+        );
+        let daily = series_table(
+            &mut fbb,
+            1_656_633_600,
+            1_656_720_000,
+            86400,
+            &[temperature_max],
+        );
+        let current = series_table(
+            &mut fbb,
+            1_656_633_600,
+            1_656_634_500,
+            900,
+            &[current_temperature],
+        );
+        let timezone = fbb.create_string("Europe/Berlin");
+        let abbreviation = fbb.create_string("CEST");
+
+        let start = fbb.start_table();
+        fbb.push_slot(slot(response::LATITUDE), latitude, 0.0);
+        fbb.push_slot(slot(response::LONGITUDE), longitude, 0.0);
+        fbb.push_slot(slot(response::ELEVATION), 38.0f32, 0.0);
+        fbb.push_slot(slot(response::GENERATION_TIME_MS), 1.5f32, 0.0);
+        fbb.push_slot(slot(response::LOCATION_ID), location_id, 0);
+        fbb.push_slot(slot(response::UTC_OFFSET_SECONDS), 7200i32, 0);
+        fbb.push_slot_always(slot(response::TIMEZONE), timezone);
+        fbb.push_slot_always(slot(response::TIMEZONE_ABBREVIATION), abbreviation);
+        fbb.push_slot_always(slot(response::HOURLY), hourly);
+        fbb.push_slot_always(slot(response::DAILY), daily);
+        fbb.push_slot_always(slot(response::CURRENT), current);
+        fbb.push_slot(slot(response::MODEL), model, 0);
+        let root = fbb.end_table(start);
+        fbb.finish_size_prefixed(root, None);
+        fbb.finished_data().to_vec()
+    }
+
+    #[test]
+    fn test_decode_single_location() {
+        let bytes = build_message(52.5, 13.25);
+        let forecasts = decode_forecasts(&bytes, TimeFormat::Iso8601).unwrap();
+        assert_eq!(forecasts.len(), 1);
+
+        let forecast = &forecasts[0];
+        assert_eq!(forecast.latitude, 52.5);
+        assert_eq!(forecast.longitude, 13.25);
+        assert_eq!(forecast.utc_offset_seconds, 7200);
+        assert_eq!(forecast.timezone, "Europe/Berlin");
+
+        let hourly = forecast.hourly.as_ref().unwrap();
+        assert_eq!(
+            hourly.time,
+            vec!["2022-07-01T02:00", "2022-07-01T03:00", "2022-07-01T04:00"]
+        );
+        assert_eq!(
+            hourly.variables["temperature_2m"],
+            vec![Some(13.0), Some(12.5), None]
+        );
+        assert_eq!(
+            hourly.variables["precipitation"],
+            vec![
+                Some(0.0),
+                Some(0.4000000059604645),
+                Some(1.2000000476837158)
+            ]
+        );
+        assert_eq!(
+            forecast.hourly_units.as_ref().unwrap()["temperature_2m"],
+            "°C"
+        );
+        assert_eq!(
+            forecast.hourly_units.as_ref().unwrap()["precipitation"],
+            "mm"
+        );
+
+        let daily = forecast.daily.as_ref().unwrap();
+        assert_eq!(daily.time, vec!["2022-07-01"]);
+        assert_eq!(daily.variables["temperature_2m_max"], vec![Some(18.5)]);
+
+        let current = forecast.current.as_ref().unwrap();
+        assert_eq!(current.time, "2022-07-01T02:00");
+        assert_eq!(current.variables["temperature_2m"], Some(14.5));
+        assert_eq!(current.variables["interval"], Some(900.0));
+    }
+
+    #[test]
+    fn test_decode_multiple_locations_unixtime() {
+        let mut bytes = build_message(52.5, 13.25);
+        bytes.extend(build_model_message(48.125, 11.5, 1, 1));
+
+        let forecasts = decode_forecasts(&bytes, TimeFormat::Unixtime).unwrap();
+        assert_eq!(forecasts.len(), 2);
+        assert_eq!(forecasts[1].latitude, 48.125);
+        assert_eq!(forecasts[1].hourly.as_ref().unwrap().time[0], "1656633600");
+    }
+
+    #[test]
+    fn test_decode_multiple_models() {
+        // Two locations with ECMWF IFS 0.25° (60) and GFS Seamless (2) each
+        let mut bytes = Vec::new();
+        for (location_id, (latitude, longitude)) in
+            [(52.5, 13.25), (48.125, 11.5)].iter().enumerate()
+        {
+            for model in [60, 2] {
+                bytes.extend(build_model_message(
+                    *latitude,
+                    *longitude,
+                    location_id as i64,
+                    model,
+                ));
+            }
+        }
+
+        let forecasts = decode_locations(&bytes, TimeFormat::Iso8601).unwrap();
+        assert_eq!(forecasts.len(), 2);
+        assert_eq!(forecasts[1].0, 1);
+        assert_eq!(forecasts[1].1.latitude, 48.125);
+
+        let forecast = &forecasts[0].1;
+        assert_eq!(
+            forecast.models(),
+            vec![WeatherModel::EcmwfIfs025, WeatherModel::GfsSeamless]
+        );
+        let temperature = forecast.hourly_by_model("temperature_2m");
+        assert_eq!(temperature.len(), 2);
+        assert_eq!(
+            temperature[&WeatherModel::GfsSeamless],
+            &[Some(13.0), Some(12.5), None]
+        );
+        assert_eq!(
+            forecast.hourly_units.as_ref().unwrap()["precipitation_ecmwf_ifs025"],
+            "mm"
+        );
+        assert_eq!(forecast.hourly_units.as_ref().unwrap()["time"], "iso8601");
+
+        let current = forecast.current.as_ref().unwrap();
+        assert_eq!(current.variables["temperature_2m_gfs_seamless"], Some(14.5));
+        assert_eq!(current.variables["interval"], Some(900.0));
+        assert_eq!(
+            forecast
+                .for_model(WeatherModel::EcmwfIfs025)
+                .unwrap()
+                .daily
+                .unwrap()
+                .variables["temperature_2m_max"],
+            vec![Some(18.5)]
+        );
+    }
+
+    #[test]
+    fn test_decode_unknown_model() {
+        for model in WeatherModel::ALL {
+            assert!(MODEL_IDS.iter().any(|(_, known)| *known == model));
+        }
+
+        let mut bytes = build_model_message(52.5, 13.25, 0, 60);
+        bytes.extend(build_model_message(52.5, 13.25, 0, 250));
+        let result = decode_locations(&bytes, TimeFormat::Iso8601);
+        assert!(matches!(result, Err(OpenMeteoError::InvalidResponse(_))));
+    }
+
+    #[test]
+    fn test_decode_truncated_body() {
+        let bytes = build_message(52.5, 13.25);
+        let result = decode_forecasts(&bytes[..bytes.len() / 2], TimeFormat::Iso8601);
+        assert!(matches!(result, Err(OpenMeteoError::InvalidResponse(_))));
+    }
+}
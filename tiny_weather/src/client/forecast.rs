@@ -1,26 +1,51 @@
 //! Weather Forecast API client
 
+use std::fmt;
+
 use reqwest::Client;
 use tracing::{debug, trace, warn};
 
+use crate::client::columnar::ColumnarBody;
+use crate::client::config::default_http_client;
+use crate::client::endpoints::{Endpoints, FORECAST_API_BASE};
 use crate::client::error::{OpenMeteoError, Result};
+use crate::client::flatbuffer;
+use crate::client::http;
+use crate::client::tabular;
+use crate::client::types::common::{Coordinates, ResponseFormat};
 use crate::client::types::forecast::{ForecastRequest, ForecastResponse};
 
-const FORECAST_API_BASE: &str = "https://api.open-meteo.com/v1/forecast";
+/// Default grid point distance (km) above which [`ForecastClient`] logs a warning
+pub const DEFAULT_SNAP_WARNING_KM: f64 = 10.0;
 
 /// Client for the Open-Meteo Weather Forecast API
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct ForecastClient {
     client: Client,
     pub(crate) base_url: String,
+    api_key: Option<String>,
+    snap_warning_km: f64,
+}
+
+impl fmt::Debug for ForecastClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ForecastClient")
+            .field("client", &self.client)
+            .field("base_url", &self.base_url)
+            .field("api_key", &http::redact_secret(&self.api_key))
+            .field("snap_warning_km", &self.snap_warning_km)
+            .finish()
+    }
 }
 
 impl ForecastClient {
-    /// Create a new forecast client with default settings
+    /// Create a new forecast client with default settings (see [`crate::client::ClientConfig`])
     pub fn new() -> Self {
         Self {
-            client: Client::new(),
+            client: default_http_client(),
             base_url: FORECAST_API_BASE.to_string(),
+            api_key: None,
+            snap_warning_km: DEFAULT_SNAP_WARNING_KM,
         }
     }
 
@@ -29,28 +54,202 @@ impl ForecastClient {
         Self {
             client,
             base_url: FORECAST_API_BASE.to_string(),
+            api_key: None,
+            snap_warning_km: DEFAULT_SNAP_WARNING_KM,
         }
     }
 
     /// Create a new forecast client with a custom base URL (for testing)
     pub fn with_base_url(base_url: impl Into<String>) -> Self {
         Self {
-            client: Client::new(),
+            client: default_http_client(),
             base_url: base_url.into(),
+            api_key: None,
+            snap_warning_km: DEFAULT_SNAP_WARNING_KM,
         }
     }
 
+    pub(crate) fn with_client_and_base_url(client: Client, base_url: impl Into<String>) -> Self {
+        Self {
+            client,
+            base_url: base_url.into(),
+            api_key: None,
+            snap_warning_km: DEFAULT_SNAP_WARNING_KM,
+        }
+    }
+
+    /// Set the API key for a commercial Open-Meteo subscription
+    ///
+    /// If the client uses the default free host, it is switched to the customer host.
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        if self.base_url == FORECAST_API_BASE {
+            self.base_url = Endpoints::customer().forecast;
+        }
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Log a warning when a location is snapped to a grid point farther away than this (km)
+    ///
+    /// Use `f64::INFINITY` to disable the warning.
+    pub fn snap_warning(mut self, threshold_km: f64) -> Self {
+        self.snap_warning_km = threshold_km;
+        self
+    }
+
     /// Get weather forecast for the given request
+    ///
+    /// For multi-location requests this returns the first location; use
+    /// [`ForecastClient::get_many`] to get all of them.
     pub async fn get(&self, request: ForecastRequest) -> Result<ForecastResponse> {
+        self.get_many(request)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or(OpenMeteoError::NoResults)
+    }
+
+    /// Get weather forecasts for every location in the request, in request order
+    pub async fn get_many(&self, request: ForecastRequest) -> Result<Vec<ForecastResponse>> {
+        let url = self.url(&request)?;
+
+        debug!("Fetching forecast from: {}", http::redact_url(&url));
+
+        let response = http::get(&self.client, url).await?;
+
+        // Responses paired with the index of their location in the request
+        let in_order =
+            |forecasts: Vec<ForecastResponse>| forecasts.into_iter().enumerate().collect();
+        let forecasts: Vec<(usize, ForecastResponse)> = match request.format {
+            ResponseFormat::Json => {
+                let body = http::bytes(response).await?;
+                trace!("Response body: {} bytes of JSON", body.len());
+                in_order(parse_json(&body)?)
+            }
+            ResponseFormat::FlatBuffers => {
+                let body = http::bytes(response).await?;
+                trace!("Response body: {} bytes of FlatBuffers", body.len());
+                flatbuffer::decode_locations(&body, request.timeformat)?
+            }
+            ResponseFormat::Csv => {
+                let body = http::text(response).await?;
+                trace!("Response body: {}", body);
+                in_order(tabular::parse_csv(&body, &request)?)
+            }
+            ResponseFormat::Xlsx => {
+                let body = http::bytes(response).await?;
+                trace!("Response body: {} bytes of XLSX", body.len());
+                in_order(parse_xlsx(&body, &request)?)
+            }
+        };
+
+        let locations = request.locations();
+        for (location_id, forecast) in &forecasts {
+            let Some(requested) = locations.get(*location_id) else {
+                warn!("Forecast for unknown location {}", location_id);
+                continue;
+            };
+            debug!(
+                "Successfully fetched forecast for ({}, {})",
+                forecast.latitude, forecast.longitude
+            );
+            let distance = forecast.grid_distance_km(requested);
+            if distance > self.snap_warning_km {
+                warn!(
+                    "Forecast for ({}, {}) is for the grid point ({}, {}), {:.1} km away",
+                    requested.latitude,
+                    requested.longitude,
+                    forecast.latitude,
+                    forecast.longitude,
+                    distance
+                );
+            }
+        }
+
+        Ok(forecasts
+            .into_iter()
+            .map(|(_, forecast)| forecast)
+            .collect())
+    }
+
+    /// Get the raw response body exactly as the API produced it, in the requested format
+    ///
+    /// For large JSON responses, pass the body to [`crate::client::columnar::parse`]
+    /// to read the series without copying them into per-variable maps.
+    pub async fn get_raw(&self, request: &ForecastRequest) -> Result<bytes::Bytes> {
+        let url = self.url(request)?;
+
+        debug!("Fetching raw forecast from: {}", http::redact_url(&url));
+
+        let response = http::get(&self.client, url).await?;
+        http::bytes(response).await
+    }
+
+    /// Get the JSON response body for zero-copy parsing with [`ColumnarBody::parse`]
+    ///
+    /// Skips building a [`ForecastResponse`], which pays off for long, wide requests.
+    pub async fn get_columnar(&self, request: &ForecastRequest) -> Result<ColumnarBody> {
+        if request.format != ResponseFormat::Json {
+            return Err(OpenMeteoError::InvalidParameter(format!(
+                "columnar parsing requires the JSON format, not {:?}",
+                request.format
+            )));
+        }
+        Ok(ColumnarBody::new(self.get_raw(request).await?))
+    }
+
+    /// Save the raw response body to a file, returning the number of bytes written
+    pub async fn download(
+        &self,
+        request: &ForecastRequest,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<usize> {
+        let body = self.get_raw(request).await?;
+        std::fs::write(path.as_ref(), &body)?;
+
+        debug!(
+            "Saved {} byte forecast to {}",
+            body.len(),
+            path.as_ref().display()
+        );
+
+        Ok(body.len())
+    }
+
+    /// Build the request URL including all query parameters
+    pub(crate) fn url(&self, request: &ForecastRequest) -> Result<reqwest::Url> {
+        let locations = request.locations();
+        let join = |f: fn(&Coordinates) -> f64| {
+            locations
+                .iter()
+                .map(|c| f(c).to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
         let mut params = vec![
-            ("latitude", request.latitude.to_string()),
-            ("longitude", request.longitude.to_string()),
+            ("latitude", join(|c| c.latitude)),
+            ("longitude", join(|c| c.longitude)),
         ];
 
         if let Some(elevation) = request.elevation {
             params.push(("elevation", elevation.to_string()));
         }
 
+        if let Some(tilt) = request.tilt {
+            params.push(("tilt", tilt.to_string()));
+        }
+
+        if let Some(azimuth) = request.azimuth {
+            params.push(("azimuth", azimuth.to_string()));
+        }
+
+        if let Some(cell_selection) = request.cell_selection {
+            params.push((
+                "cell_selection",
+                format!("{:?}", cell_selection).to_lowercase(),
+            ));
+        }
+
         if let Some(hourly) = &request.hourly {
             params.push(("hourly", hourly.join(",")));
         }
@@ -77,7 +276,10 @@ impl ForecastClient {
         ));
         params.push((
             "timeformat",
-            if matches!(request.timeformat, crate::client::types::TimeFormat::Iso8601) {
+            if matches!(
+                request.timeformat,
+                crate::client::types::TimeFormat::Iso8601
+            ) {
                 "iso8601"
             } else {
                 "unixtime"
@@ -97,47 +299,29 @@ impl ForecastClient {
             params.push(("forecast_days", forecast_days.to_string()));
         }
 
-        if let Some(models) = &request.models {
-            params.push(("models", models.clone()));
+        if let Some(start_date) = request.start_date {
+            params.push(("start_date", start_date.to_string()));
         }
 
-        let url = reqwest::Url::parse_with_params(&self.base_url, &params)
-            .map_err(|e| OpenMeteoError::InvalidParameter(e.to_string()))?;
-
-        debug!("Fetching forecast from: {}", url);
-
-        let response = self.client.get(url.clone()).send().await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            warn!("API error ({}): {}", status, error_text);
-
-            // Try to parse as API error
-            if let Ok(error) = serde_json::from_str::<serde_json::Value>(&error_text) {
-                if let Some(reason) = error.get("reason").and_then(|r| r.as_str()) {
-                    return Err(OpenMeteoError::ApiError {
-                        reason: reason.to_string(),
-                    });
-                }
-            }
-
-            return Err(OpenMeteoError::ApiError {
-                reason: format!("HTTP {}: {}", status, error_text),
-            });
+        if let Some(end_date) = request.end_date {
+            params.push(("end_date", end_date.to_string()));
         }
 
-        let body = response.text().await?;
-        trace!("Response body: {}", body);
+        if !request.models.is_empty() {
+            let models: Vec<&str> = request.models.iter().map(|model| model.id()).collect();
+            params.push(("models", models.join(",")));
+        }
 
-        let forecast: ForecastResponse = serde_json::from_str(&body)?;
+        if request.format != ResponseFormat::Json {
+            params.push(("format", format!("{:?}", request.format).to_lowercase()));
+        }
 
-        debug!(
-            "Successfully fetched forecast for ({}, {})",
-            forecast.latitude, forecast.longitude
-        );
+        if let Some(api_key) = &self.api_key {
+            params.push(("apikey", api_key.clone()));
+        }
 
-        Ok(forecast)
+        reqwest::Url::parse_with_params(&self.base_url, &params)
+            .map_err(|e| OpenMeteoError::InvalidParameter(e.to_string()))
     }
 }
 
@@ -147,9 +331,32 @@ impl Default for ForecastClient {
     }
 }
 
+/// Parse a JSON body, which is an array for multi-location requests
+fn parse_json(body: &[u8]) -> Result<Vec<ForecastResponse>> {
+    if body.trim_ascii_start().starts_with(b"[") {
+        Ok(serde_json::from_slice(body)?)
+    } else {
+        Ok(vec![serde_json::from_slice(body)?])
+    }
+}
+
+#[cfg(feature = "xlsx")]
+fn parse_xlsx(body: &[u8], request: &ForecastRequest) -> Result<Vec<ForecastResponse>> {
+    tabular::parse_xlsx(body, request)
+}
+
+#[cfg(not(feature = "xlsx"))]
+fn parse_xlsx(_body: &[u8], _request: &ForecastRequest) -> Result<Vec<ForecastResponse>> {
+    Err(OpenMeteoError::InvalidParameter(
+        "parsing XLSX responses requires the `xlsx` feature; use `download` to save the file"
+            .to_string(),
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::client::types::{CellSelection, WeatherModel};
 
     #[test]
     fn test_forecast_client_creation() {
@@ -163,6 +370,71 @@ mod tests {
         assert_eq!(client.base_url, "https://example.com/api");
     }
 
+    #[test]
+    fn test_forecast_client_api_key_switches_host() {
+        let client = ForecastClient::new().api_key("secret");
+        assert_eq!(client.base_url, Endpoints::customer().forecast);
+        assert_eq!(client.api_key.as_deref(), Some("secret"));
+
+        let client = ForecastClient::with_base_url("https://example.com/api").api_key("secret");
+        assert_eq!(client.base_url, "https://example.com/api");
+    }
+
+    #[test]
+    fn test_forecast_url_models() {
+        let request = ForecastRequest::new(52.52, 13.41)
+            .models(&[WeatherModel::EcmwfIfs025, WeatherModel::GfsSeamless])
+            .model(WeatherModel::IconD2)
+            .model(WeatherModel::GfsSeamless);
+        let url = ForecastClient::new().url(&request).unwrap();
+        let models = url
+            .query_pairs()
+            .find(|(name, _)| name == "models")
+            .map(|(_, value)| value.into_owned());
+        assert_eq!(models.as_deref(), Some("ecmwf_ifs025,gfs_seamless,icon_d2"));
+
+        let url = ForecastClient::new()
+            .url(&ForecastRequest::new(52.52, 13.41))
+            .unwrap();
+        assert!(!url.query_pairs().any(|(name, _)| name == "models"));
+    }
+
+    #[test]
+    fn test_forecast_url_panel_orientation() {
+        let request = ForecastRequest::new(52.52, 13.41)
+            .hourly(&["global_tilted_irradiance"])
+            .tilt(35.0)
+            .azimuth(-10.0);
+        let url = ForecastClient::new().url(&request).unwrap();
+        let query: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        assert!(query.contains(&("tilt".to_string(), "35".to_string())));
+        assert!(query.contains(&("azimuth".to_string(), "-10".to_string())));
+    }
+
+    #[test]
+    fn test_forecast_url_cell_selection() {
+        let request = ForecastRequest::new(43.3, 5.37).cell_selection(CellSelection::Sea);
+        let url = ForecastClient::new().url(&request).unwrap();
+        let query: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        assert!(query.contains(&("cell_selection".to_string(), "sea".to_string())));
+
+        let url = ForecastClient::new()
+            .url(&ForecastRequest::new(43.3, 5.37))
+            .unwrap();
+        assert!(!url.query_pairs().any(|(name, _)| name == "cell_selection"));
+    }
+
+    #[test]
+    fn test_forecast_url_date_range() {
+        let start = chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let end = chrono::NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+        let request = ForecastRequest::new(52.52, 13.41).date_range(start, end);
+        let url = ForecastClient::new().url(&request).unwrap();
+        let query: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        assert!(query.contains(&("start_date".to_string(), "2024-01-01".to_string())));
+        assert!(query.contains(&("end_date".to_string(), "2024-01-31".to_string())));
+    }
+
     // Note: Integration tests with actual API calls should be in separate test files
     // and marked with #[ignore] to avoid hitting the API during normal test runs
 }
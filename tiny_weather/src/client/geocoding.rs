@@ -1,26 +1,41 @@
 //! Geocoding API client for location search
 
+use std::fmt;
+
 use reqwest::Client;
-use tracing::{debug, trace, warn};
+use tracing::{debug, trace};
 
+use crate::client::config::default_http_client;
+use crate::client::endpoints::{Endpoints, GEOCODING_API_BASE};
 use crate::client::error::{OpenMeteoError, Result};
+use crate::client::http;
 use crate::client::types::geocoding::{GeocodingRequest, GeocodingResponse};
 
-const GEOCODING_API_BASE: &str = "https://geocoding-api.open-meteo.com/v1/search";
-
 /// Client for the Open-Meteo Geocoding API
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct GeocodingClient {
     client: Client,
     pub(crate) base_url: String,
+    api_key: Option<String>,
+}
+
+impl fmt::Debug for GeocodingClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GeocodingClient")
+            .field("client", &self.client)
+            .field("base_url", &self.base_url)
+            .field("api_key", &http::redact_secret(&self.api_key))
+            .finish()
+    }
 }
 
 impl GeocodingClient {
-    /// Create a new geocoding client with default settings
+    /// Create a new geocoding client with default settings (see [`crate::client::ClientConfig`])
     pub fn new() -> Self {
         Self {
-            client: Client::new(),
+            client: default_http_client(),
             base_url: GEOCODING_API_BASE.to_string(),
+            api_key: None,
         }
     }
 
@@ -29,15 +44,36 @@ impl GeocodingClient {
         Self {
             client,
             base_url: GEOCODING_API_BASE.to_string(),
+            api_key: None,
         }
     }
 
     /// Create a new geocoding client with a custom base URL (for testing)
     pub fn with_base_url(base_url: impl Into<String>) -> Self {
         Self {
-            client: Client::new(),
+            client: default_http_client(),
+            base_url: base_url.into(),
+            api_key: None,
+        }
+    }
+
+    pub(crate) fn with_client_and_base_url(client: Client, base_url: impl Into<String>) -> Self {
+        Self {
+            client,
             base_url: base_url.into(),
+            api_key: None,
+        }
+    }
+
+    /// Set the API key for a commercial Open-Meteo subscription
+    ///
+    /// If the client uses the default free host, it is switched to the customer host.
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        if self.base_url == GEOCODING_API_BASE {
+            self.base_url = Endpoints::customer().geocoding;
         }
+        self.api_key = Some(api_key.into());
+        self
     }
 
     /// Search for locations by name
@@ -52,28 +88,30 @@ impl GeocodingClient {
             params.push(("language", language.clone()));
         }
 
+        if let Some(country_code) = &request.country_code {
+            params.push(("countryCode", country_code.clone()));
+        }
+
         if let Some(format) = &request.format {
             params.push(("format", format.clone()));
         }
 
+        if let Some(api_key) = &self.api_key {
+            params.push(("apikey", api_key.clone()));
+        }
+
         let url = reqwest::Url::parse_with_params(&self.base_url, &params)
             .map_err(|e| OpenMeteoError::InvalidParameter(e.to_string()))?;
 
-        debug!("Searching for location: {} at {}", request.name, url);
-
-        let response = self.client.get(url.clone()).send().await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            warn!("API error ({}): {}", status, error_text);
+        debug!(
+            "Searching for location: {} at {}",
+            request.name,
+            http::redact_url(&url)
+        );
 
-            return Err(OpenMeteoError::ApiError {
-                reason: format!("HTTP {}: {}", status, error_text),
-            });
-        }
+        let response = http::get(&self.client, url).await?;
 
-        let body = response.text().await?;
+        let body = http::text(response).await?;
         trace!("Response body: {}", body);
 
         let geocoding: GeocodingResponse = serde_json::from_str(&body)?;
@@ -115,6 +153,16 @@ mod tests {
         assert_eq!(client.base_url, "https://example.com/api");
     }
 
+    #[test]
+    fn test_geocoding_client_api_key_switches_host() {
+        let client = GeocodingClient::new().api_key("secret");
+        assert_eq!(client.base_url, Endpoints::customer().geocoding);
+        assert_eq!(client.api_key.as_deref(), Some("secret"));
+
+        let client = GeocodingClient::with_base_url("https://example.com/api").api_key("secret");
+        assert_eq!(client.base_url, "https://example.com/api");
+    }
+
     // Note: Integration tests with actual API calls should be in separate test files
     // and marked with #[ignore] to avoid hitting the API during normal test runs
 }
@@ -4,12 +4,22 @@
 //! - `ForecastClient`: Weather forecast data (hourly, daily, current)
 //! - `GeocodingClient`: Location search and geocoding
 //! - `OpenMeteoClient`: Unified client combining both APIs
+//!
+//! Endpoint URLs for the free, commercial and self-hosted APIs live in [`Endpoints`].
 
+pub mod columnar;
+pub mod config;
+pub mod endpoints;
 pub mod error;
+pub mod flatbuffer;
 pub mod forecast;
 pub mod geocoding;
+mod http;
+pub mod tabular;
 pub mod types;
 
+pub use config::ClientConfig;
+pub use endpoints::Endpoints;
 pub use error::{OpenMeteoError, Result};
 pub use forecast::ForecastClient;
 pub use geocoding::GeocodingClient;
@@ -20,23 +30,87 @@ pub use geocoding::GeocodingClient;
 #[derive(Debug, Clone)]
 pub struct OpenMeteoClient {
     forecast: ForecastClient,
+    archive: ForecastClient,
     geocoding: GeocodingClient,
+    endpoints: Endpoints,
 }
 
 impl OpenMeteoClient {
     /// Create a new unified Open-Meteo client
     pub fn new() -> Self {
-        Self {
-            forecast: ForecastClient::new(),
-            geocoding: GeocodingClient::new(),
-        }
+        Self::with_endpoints(Endpoints::open_meteo())
     }
 
     /// Create a new unified client with a custom HTTP client
     pub fn with_client(client: reqwest::Client) -> Self {
+        Self::from_parts(client, Endpoints::open_meteo())
+    }
+
+    /// Create a new unified client using the given endpoint URLs
+    pub fn with_endpoints(endpoints: Endpoints) -> Self {
+        Self::from_parts(config::default_http_client(), endpoints)
+    }
+
+    /// Create a new unified client from a [`ClientConfig`]
+    ///
+    /// All sub-clients share one HTTP client (and its connection pool) built from the config.
+    pub fn with_config(config: ClientConfig) -> Result<Self> {
+        let mut client = Self::from_parts(config.build_http_client()?, config.endpoints);
+        client.forecast = client.forecast.snap_warning(config.snap_warning_km);
+        client.archive = client.archive.snap_warning(config.snap_warning_km);
+        Ok(match config.api_key {
+            Some(api_key) => client.api_key(api_key),
+            None => client,
+        })
+    }
+
+    /// Create a new unified client for a self-hosted instance
+    ///
+    /// Every sub-API URL is derived from `host` (e.g., `http://weather.internal:8080`).
+    pub fn with_base_host(host: impl AsRef<str>) -> Self {
+        Self::with_endpoints(Endpoints::self_hosted(host))
+    }
+
+    fn from_parts(client: reqwest::Client, endpoints: Endpoints) -> Self {
+        Self {
+            forecast: ForecastClient::with_client_and_base_url(
+                client.clone(),
+                endpoints.forecast.clone(),
+            ),
+            archive: ForecastClient::with_client_and_base_url(
+                client.clone(),
+                endpoints.archive.clone(),
+            ),
+            geocoding: GeocodingClient::with_client_and_base_url(
+                client,
+                endpoints.geocoding.clone(),
+            ),
+            endpoints,
+        }
+    }
+
+    /// Set the API key for a commercial Open-Meteo subscription
+    ///
+    /// A client on the free endpoints is switched to [`Endpoints::customer`];
+    /// custom and self-hosted endpoints are kept. Every sub-client takes its
+    /// URL from the resolved endpoints.
+    pub fn api_key(self, api_key: impl Into<String>) -> Self {
+        let api_key = api_key.into();
+        let endpoints = if self.endpoints == Endpoints::open_meteo() {
+            Endpoints::customer()
+        } else {
+            self.endpoints
+        };
+        let (mut forecast, mut archive, mut geocoding) =
+            (self.forecast, self.archive, self.geocoding);
+        forecast.base_url = endpoints.forecast.clone();
+        archive.base_url = endpoints.archive.clone();
+        geocoding.base_url = endpoints.geocoding.clone();
         Self {
-            forecast: ForecastClient::with_client(client.clone()),
-            geocoding: GeocodingClient::with_client(client),
+            forecast: forecast.api_key(api_key.clone()),
+            archive: archive.api_key(api_key.clone()),
+            geocoding: geocoding.api_key(api_key),
+            endpoints,
         }
     }
 
@@ -45,10 +119,25 @@ impl OpenMeteoClient {
         &self.forecast
     }
 
+    /// Get a forecast client for the historical weather (archive) API
+    ///
+    /// The archive API takes the same requests as the forecast API, with the
+    /// days given by [`ForecastRequest::date_range`].
+    ///
+    /// [`ForecastRequest::date_range`]: crate::client::types::ForecastRequest::date_range
+    pub fn archive(&self) -> &ForecastClient {
+        &self.archive
+    }
+
     /// Get the geocoding client
     pub fn geocoding(&self) -> &GeocodingClient {
         &self.geocoding
     }
+
+    /// Get the endpoint URLs used by this client
+    pub fn endpoints(&self) -> &Endpoints {
+        &self.endpoints
+    }
 }
 
 impl Default for OpenMeteoClient {
@@ -67,4 +156,73 @@ mod tests {
         assert!(client.forecast().base_url.contains("forecast"));
         assert!(client.geocoding().base_url.contains("geocoding"));
     }
+
+    #[test]
+    fn test_unified_client_with_config() {
+        let client = OpenMeteoClient::with_config(
+            ClientConfig::new()
+                .base_host("http://weather.internal:8080")
+                .api_key("secret"),
+        )
+        .unwrap();
+        assert_eq!(
+            client.forecast().base_url,
+            "http://weather.internal:8080/v1/forecast"
+        );
+
+        let client = OpenMeteoClient::with_config(ClientConfig::new().api_key("secret")).unwrap();
+        assert_eq!(client.endpoints(), &Endpoints::customer());
+    }
+
+    #[test]
+    fn test_unified_client_api_key() {
+        let client = OpenMeteoClient::new().api_key("secret");
+        assert!(
+            client
+                .forecast()
+                .base_url
+                .starts_with("https://customer-api.")
+        );
+        assert!(
+            client
+                .geocoding()
+                .base_url
+                .starts_with("https://customer-geocoding-api.")
+        );
+        assert_eq!(client.archive().base_url, Endpoints::customer().archive);
+        assert_eq!(client.endpoints(), &Endpoints::customer());
+
+        // The key never shows up in debug logs
+        let debug = format!("{:?}", client);
+        assert!(!debug.contains("secret"));
+        assert!(debug.contains(r#"api_key: Some("***")"#));
+    }
+
+    #[test]
+    fn test_unified_client_base_host() {
+        let client = OpenMeteoClient::with_base_host("http://weather.internal:8080");
+        assert_eq!(
+            client.forecast().base_url,
+            "http://weather.internal:8080/v1/forecast"
+        );
+        assert_eq!(
+            client.geocoding().base_url,
+            "http://weather.internal:8080/v1/search"
+        );
+        assert_eq!(
+            client.endpoints().archive,
+            "http://weather.internal:8080/v1/archive"
+        );
+
+        // Self-hosted instances keep their host when an API key is set
+        let client = client.api_key("secret");
+        assert_eq!(
+            client.forecast().base_url,
+            "http://weather.internal:8080/v1/forecast"
+        );
+        assert_eq!(
+            client.archive().base_url,
+            "http://weather.internal:8080/v1/archive"
+        );
+    }
 }
@@ -0,0 +1,515 @@
+//! Parsers for the CSV (`format=csv`) and XLSX (`format=xlsx`) response formats
+//!
+//! Both formats use the same layout: a location metadata section followed by
+//! one section per series (current, hourly, daily), separated by blank rows.
+//! Series headers carry the unit in parentheses, e.g. `temperature_2m (°C)`:
+//!
+//! ```text
+//! latitude,longitude,elevation,utc_offset_seconds,timezone,timezone_abbreviation
+//! 52.52,13.419998,38.0,3600,Europe/Berlin,GMT+1
+//!
+//! time,temperature_2m (°C),precipitation (mm)
+//! 2024-01-01T00:00,1.2,0.0
+//! ```
+//!
+//! Multi-location responses prefix every section with a `location_id` column.
+//!
+//! Section headers do not name their series, so the parsers take the request
+//! to tell them apart: a section belongs to the series whose variables its
+//! columns were requested under. Timestamps keep the request's time format.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::client::error::{OpenMeteoError, Result};
+use crate::client::types::TimeFormat;
+use crate::client::types::forecast::{
+    CurrentData, DailyData, ForecastRequest, ForecastResponse, HourlyData,
+};
+
+/// Series a section belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Series {
+    Current,
+    Hourly,
+    Daily,
+}
+
+/// Parse a CSV body for `request` into one [`ForecastResponse`] per location
+pub fn parse_csv(body: &str, request: &ForecastRequest) -> Result<Vec<ForecastResponse>> {
+    let mut rows = Vec::new();
+    // The csv reader skips blank lines, so split sections before parsing them
+    for section in body.replace("\r\n", "\n").split("\n\n") {
+        let mut reader = ::csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .trim(::csv::Trim::All)
+            .from_reader(section.as_bytes());
+        for record in reader.records() {
+            let record = record.map_err(|e| invalid(&e.to_string()))?;
+            rows.push(record.iter().map(str::to_string).collect());
+        }
+        rows.push(Vec::new());
+    }
+
+    parse_rows(rows, request)
+}
+
+/// Parse an XLSX body for `request` into one [`ForecastResponse`] per location
+#[cfg(feature = "xlsx")]
+pub fn parse_xlsx(body: &[u8], request: &ForecastRequest) -> Result<Vec<ForecastResponse>> {
+    use calamine::{Data, Reader, Xlsx};
+
+    let mut workbook =
+        Xlsx::new(std::io::Cursor::new(body)).map_err(|e| invalid(&e.to_string()))?;
+    let sheet = workbook
+        .worksheet_range_at(0)
+        .ok_or_else(|| invalid("workbook has no sheets"))?
+        .map_err(|e| invalid(&e.to_string()))?;
+
+    let rows = sheet
+        .rows()
+        .map(|row| {
+            let cells: Vec<String> = row
+                .iter()
+                .map(|cell| match cell {
+                    Data::DateTime(date) if date.is_datetime() => excel_datetime(date),
+                    _ => cell.to_string().trim().to_string(),
+                })
+                .collect();
+            if cells.iter().all(String::is_empty) {
+                Vec::new()
+            } else {
+                cells
+            }
+        })
+        .collect();
+
+    parse_rows(rows, request)
+}
+
+/// Date cell as an ISO 8601 timestamp, rounded to the minute
+///
+/// Cells hold fractional days, so 01:00 may come back as 00:59:59.999.
+#[cfg(feature = "xlsx")]
+fn excel_datetime(date: &calamine::ExcelDateTime) -> String {
+    let (year, month, day, hour, minute, second, milli) = date.to_ymd_hms_milli();
+    chrono::NaiveDate::from_ymd_opt(year.into(), month.into(), day.into())
+        .and_then(|date| {
+            date.and_hms_milli_opt(hour.into(), minute.into(), second.into(), milli.into())
+        })
+        .map(|time| {
+            (time + chrono::Duration::seconds(30))
+                .format("%Y-%m-%dT%H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|| date.to_string())
+}
+
+/// Parse rows of cells, where an empty row separates sections
+fn parse_rows(rows: Vec<Vec<String>>, request: &ForecastRequest) -> Result<Vec<ForecastResponse>> {
+    let mut sections = rows
+        .split(|row| row.is_empty())
+        .filter(|section| !section.is_empty());
+
+    let metadata = sections.next().ok_or_else(|| invalid("empty body"))?;
+    let mut forecasts = parse_metadata(metadata)?;
+
+    for section in sections {
+        parse_series(section, request, &mut forecasts)?;
+    }
+
+    Ok(forecasts.into_values().collect())
+}
+
+/// Parse the location section into forecasts keyed by location id
+fn parse_metadata(section: &[Vec<String>]) -> Result<BTreeMap<u32, ForecastResponse>> {
+    let (header, rows) = section
+        .split_first()
+        .ok_or_else(|| invalid("missing location header"))?;
+    let column = |name: &str| header.iter().position(|h| h == name);
+    let latitude = column("latitude").ok_or_else(|| invalid("missing latitude column"))?;
+    let longitude = column("longitude").ok_or_else(|| invalid("missing longitude column"))?;
+
+    rows.iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let cell = |index: Option<usize>| index.and_then(|i| row.get(i)).map(String::as_str);
+            let number = |index: Option<usize>| cell(index).and_then(|v| v.parse::<f64>().ok());
+            let location_id = location_id(cell(column("location_id")), i)?;
+
+            let forecast = ForecastResponse {
+                latitude: number(Some(latitude)).ok_or_else(|| invalid("invalid latitude"))?,
+                longitude: number(Some(longitude)).ok_or_else(|| invalid("invalid longitude"))?,
+                elevation: number(column("elevation")).unwrap_or_default(),
+                generationtime_ms: 0.0,
+                utc_offset_seconds: number(column("utc_offset_seconds")).unwrap_or_default() as i32,
+                timezone: cell(column("timezone")).unwrap_or("GMT").to_string(),
+                timezone_abbreviation: cell(column("timezone_abbreviation"))
+                    .unwrap_or("GMT")
+                    .to_string(),
+                hourly: None,
+                hourly_units: None,
+                daily: None,
+                daily_units: None,
+                current: None,
+                current_units: None,
+            };
+            Ok((location_id, forecast))
+        })
+        .collect()
+}
+
+/// Parse one series section and attach it to the matching forecasts
+fn parse_series(
+    section: &[Vec<String>],
+    request: &ForecastRequest,
+    forecasts: &mut BTreeMap<u32, ForecastResponse>,
+) -> Result<()> {
+    let (header, rows) = section
+        .split_first()
+        .ok_or_else(|| invalid("missing series header"))?;
+    let has_location = header.first().is_some_and(|h| h == "location_id");
+    let time_column = header
+        .iter()
+        .position(|h| h == "time")
+        .ok_or_else(|| invalid("missing time column"))?;
+    let columns: Vec<(usize, String, String)> = header
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != time_column && !(has_location && *i == 0))
+        .map(|(i, h)| {
+            let (name, unit) = split_header(h);
+            (i, name, unit)
+        })
+        .collect();
+
+    // Group rows by location, preserving their order
+    let mut grouped: BTreeMap<u32, Vec<&Vec<String>>> = BTreeMap::new();
+    for row in rows {
+        let id = if has_location {
+            location_id(row.first().map(String::as_str), 0)?
+        } else {
+            0
+        };
+        grouped.entry(id).or_default().push(row);
+    }
+
+    let names: Vec<&str> = columns.iter().map(|(_, name, _)| name.as_str()).collect();
+    let times: Vec<&str> = rows
+        .iter()
+        .filter_map(|row| row.get(time_column).map(String::as_str))
+        .collect();
+    let series = classify(&names, &times, request);
+    let time_unit = match request.timeformat {
+        TimeFormat::Iso8601 => "iso8601",
+        TimeFormat::Unixtime => "unixtime",
+    };
+    for (id, rows) in grouped {
+        let forecast = forecasts
+            .get_mut(&id)
+            .ok_or_else(|| invalid(&format!("unknown location_id {}", id)))?;
+        let time: Vec<String> = rows
+            .iter()
+            .map(|row| {
+                let time = row.get(time_column).map(String::as_str).unwrap_or_default();
+                normalize_time(time, series, request.timeformat)
+            })
+            .collect();
+        let mut units: HashMap<String, String> = columns
+            .iter()
+            .map(|(_, name, unit)| (name.clone(), unit.clone()))
+            .collect();
+        units.insert("time".to_string(), time_unit.to_string());
+
+        if series == Series::Current {
+            let row = rows
+                .first()
+                .ok_or_else(|| invalid("empty current section"))?;
+            forecast.current = Some(CurrentData {
+                time: time.into_iter().next().unwrap_or_default(),
+                variables: columns
+                    .iter()
+                    .map(|(i, name, _)| (name.clone(), parse_value(row.get(*i))))
+                    .collect(),
+            });
+            forecast.current_units = Some(units);
+            continue;
+        }
+
+        let mut variables = HashMap::new();
+        let mut time_variables = HashMap::new();
+        for (i, name, unit) in &columns {
+            if unit == "iso8601" || unit == "unixtime" {
+                let values = rows
+                    .iter()
+                    .map(|row| row.get(*i).filter(|v| !v.is_empty()).cloned())
+                    .collect();
+                time_variables.insert(name.clone(), values);
+            } else {
+                let values = rows.iter().map(|row| parse_value(row.get(*i))).collect();
+                variables.insert(name.clone(), values);
+            }
+        }
+
+        if series == Series::Hourly {
+            forecast.hourly = Some(HourlyData { time, variables });
+            forecast.hourly_units = Some(units);
+        } else {
+            forecast.daily = Some(DailyData {
+                time,
+                variables,
+                time_variables,
+            });
+            forecast.daily_units = Some(units);
+        }
+    }
+
+    Ok(())
+}
+
+/// Series of a section with the variables `names` and timestamps `times`
+///
+/// The current section has an `interval` column. Otherwise the section belongs
+/// to the series whose requested variables cover all of its columns, where a
+/// column may carry a model suffix (`temperature_2m_icon_d2`). Variables
+/// requested for several series, such as `weather_code`, fall back to the
+/// timestamps: hourly ones are less than a day apart or carry a time of day.
+fn classify(names: &[&str], times: &[&str], request: &ForecastRequest) -> Series {
+    if names.contains(&"interval") {
+        return Series::Current;
+    }
+    let covers = |variables: &Option<Vec<String>>| {
+        let variables = variables.as_deref().unwrap_or_default();
+        !names.is_empty()
+            && names.iter().all(|name| {
+                variables.iter().any(|variable| {
+                    name == variable
+                        || name
+                            .strip_prefix(variable.as_str())
+                            .is_some_and(|model| model.starts_with('_'))
+                })
+            })
+    };
+    match (
+        covers(&request.hourly),
+        covers(&request.daily),
+        covers(&request.current),
+    ) {
+        (true, false, _) => Series::Hourly,
+        (false, true, _) => Series::Daily,
+        (false, false, true) => Series::Current,
+        _ => match request.timeformat {
+            TimeFormat::Iso8601 => {
+                // XLSX date cells carry a time of day even for daily series
+                if times
+                    .iter()
+                    .any(|t| t.contains('T') && !t.ends_with("T00:00"))
+                {
+                    Series::Hourly
+                } else {
+                    Series::Daily
+                }
+            }
+            TimeFormat::Unixtime => {
+                let seconds: Vec<i64> = times.iter().filter_map(|t| t.parse().ok()).collect();
+                match seconds.as_slice() {
+                    [first, second, ..] if second - first < 86_400 => Series::Hourly,
+                    _ => Series::Daily,
+                }
+            }
+        },
+    }
+}
+
+/// Daily ISO 8601 timestamps are plain dates, the others have a time of day
+fn normalize_time(time: &str, series: Series, timeformat: TimeFormat) -> String {
+    match (timeformat, series, time.split_once('T')) {
+        (TimeFormat::Iso8601, Series::Daily, Some((date, _))) => date.to_string(),
+        (TimeFormat::Iso8601, Series::Hourly | Series::Current, None) if !time.is_empty() => {
+            format!("{}T00:00", time)
+        }
+        _ => time.to_string(),
+    }
+}
+
+/// Split `temperature_2m (°C)` into the variable name and unit
+fn split_header(header: &str) -> (String, String) {
+    match header.split_once(" (") {
+        Some((name, unit)) => (name.to_string(), unit.trim_end_matches(')').to_string()),
+        None => (header.to_string(), String::new()),
+    }
+}
+
+fn location_id(cell: Option<&str>, default: usize) -> Result<u32> {
+    match cell {
+        Some(id) => id
+            .parse()
+            .map_err(|_| invalid(&format!("invalid location_id {}", id))),
+        None => Ok(default as u32),
+    }
+}
+
+/// Missing values are empty cells; non-numeric values are treated as missing
+fn parse_value(cell: Option<&String>) -> Option<f64> {
+    cell.and_then(|v| v.parse().ok())
+}
+
+fn invalid(reason: &str) -> OpenMeteoError {
+    OpenMeteoError::InvalidResponse(format!("tabular body: {}", reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_single_location() {
+        let body = "latitude,longitude,elevation,utc_offset_seconds,timezone,timezone_abbreviation\n\
+                    52.52,13.419998,38.0,3600,Europe/Berlin,GMT+1\n\
+                    \n\
+                    time,interval,temperature_2m (°C)\n\
+                    2024-01-01T12:00,900,3.1\n\
+                    \n\
+                    time,temperature_2m (°C),precipitation (mm)\n\
+                    2024-01-01T00:00,1.2,0.0\n\
+                    2024-01-01T01:00,,0.4\n\
+                    \n\
+                    time,temperature_2m_max (°C),sunrise (iso8601)\n\
+                    2024-01-01,4.5,2024-01-01T08:17\n";
+
+        let request = ForecastRequest::new(52.52, 13.42)
+            .current(&["temperature_2m"])
+            .hourly(&["temperature_2m", "precipitation"])
+            .daily(&["temperature_2m_max", "sunrise"]);
+        let forecasts = parse_csv(body, &request).unwrap();
+        assert_eq!(forecasts.len(), 1);
+
+        let forecast = &forecasts[0];
+        assert_eq!(forecast.latitude, 52.52);
+        assert_eq!(forecast.utc_offset_seconds, 3600);
+        assert_eq!(forecast.timezone, "Europe/Berlin");
+
+        let current = forecast.current.as_ref().unwrap();
+        assert_eq!(current.time, "2024-01-01T12:00");
+        assert_eq!(current.variables["temperature_2m"], Some(3.1));
+
+        let hourly = forecast.hourly.as_ref().unwrap();
+        assert_eq!(hourly.time, vec!["2024-01-01T00:00", "2024-01-01T01:00"]);
+        assert_eq!(hourly.variables["temperature_2m"], vec![Some(1.2), None]);
+        assert_eq!(
+            hourly.variables["precipitation"],
+            vec![Some(0.0), Some(0.4)]
+        );
+        assert_eq!(
+            forecast.hourly_units.as_ref().unwrap()["temperature_2m"],
+            "°C"
+        );
+        assert_eq!(forecast.hourly_units.as_ref().unwrap()["time"], "iso8601");
+
+        let daily = forecast.daily.as_ref().unwrap();
+        assert_eq!(daily.time, vec!["2024-01-01"]);
+        assert_eq!(daily.variables["temperature_2m_max"], vec![Some(4.5)]);
+        assert_eq!(
+            daily.time_variables["sunrise"],
+            vec![Some("2024-01-01T08:17".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_parse_multiple_locations() {
+        let body = "location_id,latitude,longitude,elevation,utc_offset_seconds,timezone,timezone_abbreviation\n\
+                    0,52.52,13.419998,38.0,0,GMT,GMT\n\
+                    1,48.14,11.58,519.0,0,GMT,GMT\n\
+                    \n\
+                    location_id,time,temperature_2m (°C)\n\
+                    0,2024-01-01T00:00,1.2\n\
+                    0,2024-01-01T01:00,1.0\n\
+                    1,2024-01-01T00:00,-2.5\n\
+                    1,2024-01-01T01:00,-3.0\n";
+
+        let request = ForecastRequest::new(52.52, 13.42)
+            .location(48.14, 11.58)
+            .hourly(&["temperature_2m"]);
+        let forecasts = parse_csv(body, &request).unwrap();
+        assert_eq!(forecasts.len(), 2);
+        assert_eq!(forecasts[1].latitude, 48.14);
+        assert_eq!(forecasts[1].elevation, 519.0);
+
+        let hourly = forecasts[1].hourly.as_ref().unwrap();
+        assert_eq!(hourly.time.len(), 2);
+        assert_eq!(
+            hourly.variables["temperature_2m"],
+            vec![Some(-2.5), Some(-3.0)]
+        );
+    }
+
+    #[test]
+    fn test_parse_unixtime() {
+        let body = "latitude,longitude,utc_offset_seconds\n\
+                    52.52,13.42,3600\n\
+                    \n\
+                    time,weather_code (wmo code),temperature_2m_icon_d2 (°C)\n\
+                    1704063600,3,1.2\n\
+                    1704067200,61,1.0\n\
+                    \n\
+                    time,weather_code (wmo code),sunrise (unixtime)\n\
+                    1704063600,61,1704093420\n";
+
+        // weather_code is requested for both series, the model suffix for hourly only
+        let request = ForecastRequest::new(52.52, 13.42)
+            .hourly(&["weather_code", "temperature_2m"])
+            .daily(&["weather_code", "sunrise"])
+            .timeformat(TimeFormat::Unixtime);
+        let forecast = parse_csv(body, &request).unwrap().remove(0);
+
+        let hourly = forecast.hourly.as_ref().unwrap();
+        assert_eq!(hourly.time, vec!["1704063600", "1704067200"]);
+        assert_eq!(
+            hourly.variables["weather_code"],
+            vec![Some(3.0), Some(61.0)]
+        );
+        assert_eq!(forecast.hourly_units.as_ref().unwrap()["time"], "unixtime");
+
+        let daily = forecast.daily.as_ref().unwrap();
+        assert_eq!(daily.time, vec!["1704063600"]);
+        assert_eq!(
+            daily.time_variables["sunrise"],
+            vec![Some("1704093420".to_string())]
+        );
+        assert_eq!(forecast.daily_units.as_ref().unwrap()["time"], "unixtime");
+    }
+
+    #[cfg(feature = "xlsx")]
+    #[test]
+    fn test_parse_xlsx_date_cells() {
+        // Times are Excel date cells, so none of them contain a 'T'
+        let body = include_bytes!("../../tests/fixtures/forecast.xlsx");
+        let request = ForecastRequest::new(52.52, 13.42)
+            .hourly(&["temperature_2m"])
+            .daily(&["weather_code"]);
+        let forecast = parse_xlsx(body, &request).unwrap().remove(0);
+        assert_eq!(forecast.utc_offset_seconds, 3600);
+        assert_eq!(forecast.timezone, "Europe/Berlin");
+
+        let hourly = forecast.hourly.as_ref().unwrap();
+        assert_eq!(hourly.time, vec!["2024-01-01T00:00", "2024-01-01T01:00"]);
+        assert_eq!(
+            hourly.variables["temperature_2m"],
+            vec![Some(1.2), Some(1.0)]
+        );
+
+        let daily = forecast.daily.as_ref().unwrap();
+        assert_eq!(daily.time, vec!["2024-01-01", "2024-01-02"]);
+        assert_eq!(daily.variables["weather_code"], vec![Some(61.0), Some(3.0)]);
+    }
+
+    #[test]
+    fn test_parse_invalid_body() {
+        let request = ForecastRequest::new(52.52, 13.42);
+        assert!(matches!(
+            parse_csv("", &request),
+            Err(OpenMeteoError::InvalidResponse(_))
+        ));
+        assert!(parse_csv("foo,bar\n1,2\n", &request).is_err());
+    }
+}
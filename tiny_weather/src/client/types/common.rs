@@ -17,28 +17,49 @@ impl Coordinates {
             longitude,
         }
     }
+
+    /// Great-circle distance to other coordinates in kilometers (haversine)
+    pub fn distance_km(&self, other: &Coordinates) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let dlat = lat2 - lat1;
+        let dlon = (other.longitude - self.longitude).to_radians();
+        let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+    }
+}
+
+/// Mean Earth radius (km)
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// How the API picks the grid cell for the requested coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CellSelection {
+    /// Prefer a land cell with an elevation close to the requested one (API default)
+    #[default]
+    Land,
+    /// Prefer a sea cell, e.g., for marine or coastal forecasts
+    Sea,
+    /// The nearest cell, regardless of surface type or elevation
+    Nearest,
 }
 
 /// Temperature unit for API responses
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum TemperatureUnit {
+    #[default]
     Celsius,
     Fahrenheit,
 }
 
-impl Default for TemperatureUnit {
-    fn default() -> Self {
-        Self::Celsius
-    }
-}
-
 /// Wind speed unit for API responses
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum WindSpeedUnit {
     /// Kilometers per hour
     #[serde(rename = "kmh")]
+    #[default]
     Kmh,
     /// Meters per second
     #[serde(rename = "ms")]
@@ -51,42 +72,59 @@ pub enum WindSpeedUnit {
     Kn,
 }
 
-impl Default for WindSpeedUnit {
-    fn default() -> Self {
-        Self::Kmh
-    }
-}
-
 /// Precipitation unit for API responses
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum PrecipitationUnit {
     /// Millimeters
     #[serde(rename = "mm")]
+    #[default]
     Mm,
     /// Inches
     #[serde(rename = "inch")]
     Inch,
 }
 
-impl Default for PrecipitationUnit {
-    fn default() -> Self {
-        Self::Mm
-    }
-}
-
 /// Time format for API responses
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum TimeFormat {
     #[serde(rename = "iso8601")]
+    #[default]
     Iso8601,
     #[serde(rename = "unixtime")]
     Unixtime,
 }
 
-impl Default for TimeFormat {
-    fn default() -> Self {
-        Self::Iso8601
+/// Response body format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResponseFormat {
+    #[default]
+    Json,
+    /// Compact binary format, much faster to decode for large or multi-location requests
+    #[serde(rename = "flatbuffers")]
+    FlatBuffers,
+    /// Comma-separated values, one section per series
+    Csv,
+    /// Excel workbook with the same layout as CSV (parsing requires the `xlsx` feature)
+    Xlsx,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance_km() {
+        let berlin = Coordinates::new(52.52, 13.41);
+        let paris = Coordinates::new(48.8566, 2.3522);
+        let distance = berlin.distance_km(&paris);
+        assert!((distance - 877.5).abs() < 1.0, "{}", distance);
+        assert_eq!(berlin.distance_km(&berlin), 0.0);
+
+        // One degree of latitude is about 111 km
+        let north = Coordinates::new(53.52, 13.41);
+        assert!((berlin.distance_km(&north) - 111.19).abs() < 0.01);
     }
 }
@@ -1,16 +1,27 @@
 //! Forecast API request and response types
 
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use super::common::{PrecipitationUnit, TemperatureUnit, TimeFormat, WindSpeedUnit};
+use super::common::{
+    CellSelection, Coordinates, PrecipitationUnit, ResponseFormat, TemperatureUnit, TimeFormat,
+    WindSpeedUnit,
+};
+use super::model::WeatherModel;
+use super::units::{Quantity, Unit, UnitSystem};
+use super::weather_code::{self, WEATHER_CODE, WeatherCode};
 
 /// Request builder for weather forecast API
 #[derive(Debug, Clone)]
 pub struct ForecastRequest {
     pub latitude: f64,
     pub longitude: f64,
+    pub additional_locations: Vec<Coordinates>,
     pub elevation: Option<f64>,
+    pub tilt: Option<f64>,
+    pub azimuth: Option<f64>,
+    pub cell_selection: Option<CellSelection>,
     pub hourly: Option<Vec<String>>,
     pub daily: Option<Vec<String>>,
     pub current: Option<Vec<String>>,
@@ -21,7 +32,10 @@ pub struct ForecastRequest {
     pub timezone: Option<String>,
     pub past_days: Option<u8>,
     pub forecast_days: Option<u8>,
-    pub models: Option<String>,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    pub models: Vec<WeatherModel>,
+    pub format: ResponseFormat,
 }
 
 impl ForecastRequest {
@@ -30,7 +44,11 @@ impl ForecastRequest {
         Self {
             latitude,
             longitude,
+            additional_locations: Vec::new(),
             elevation: None,
+            tilt: None,
+            azimuth: None,
+            cell_selection: None,
             hourly: None,
             daily: None,
             current: None,
@@ -41,16 +59,56 @@ impl ForecastRequest {
             timezone: None,
             past_days: None,
             forecast_days: None,
-            models: None,
+            start_date: None,
+            end_date: None,
+            models: Vec::new(),
+            format: ResponseFormat::default(),
         }
     }
 
+    /// Add another location, turning this into a multi-location request
+    pub fn location(mut self, latitude: f64, longitude: f64) -> Self {
+        self.additional_locations
+            .push(Coordinates::new(latitude, longitude));
+        self
+    }
+
+    /// All requested locations, starting with the primary one
+    pub fn locations(&self) -> Vec<Coordinates> {
+        std::iter::once(Coordinates::new(self.latitude, self.longitude))
+            .chain(self.additional_locations.iter().copied())
+            .collect()
+    }
+
     /// Set elevation for statistical downscaling (meters)
     pub fn elevation(mut self, elevation: f64) -> Self {
         self.elevation = Some(elevation);
         self
     }
 
+    /// Set panel tilt for `global_tilted_irradiance` (degrees, 0 horizontal to 90 vertical)
+    pub fn tilt(mut self, tilt: f64) -> Self {
+        self.tilt = Some(tilt);
+        self
+    }
+
+    /// Set panel azimuth for `global_tilted_irradiance`
+    ///
+    /// Degrees from south: 0 faces south, −90 east, 90 west and ±180 north.
+    pub fn azimuth(mut self, azimuth: f64) -> Self {
+        self.azimuth = Some(azimuth);
+        self
+    }
+
+    /// Set how the grid cell is picked for the coordinates (API default: land)
+    ///
+    /// See [`ForecastResponse::grid_distance_km`] for how far the returned grid
+    /// point is from the requested location.
+    pub fn cell_selection(mut self, selection: CellSelection) -> Self {
+        self.cell_selection = Some(selection);
+        self
+    }
+
     /// Set hourly weather variables to fetch
     pub fn hourly(mut self, variables: &[&str]) -> Self {
         self.hourly = Some(variables.iter().map(|s| s.to_string()).collect());
@@ -111,9 +169,36 @@ impl ForecastRequest {
         self
     }
 
-    /// Set specific weather model
-    pub fn models(mut self, models: impl Into<String>) -> Self {
-        self.models = Some(models.into());
+    /// Request the days from `start` to `end` (inclusive) instead of
+    /// `past_days`/`forecast_days`
+    ///
+    /// Required by the archive API; see [`crate::client::OpenMeteoClient::archive`].
+    pub fn date_range(mut self, start: NaiveDate, end: NaiveDate) -> Self {
+        self.start_date = Some(start);
+        self.end_date = Some(end);
+        self
+    }
+
+    /// Select the weather models to return (default: best match for the location)
+    ///
+    /// With more than one model, response variables are suffixed with the model
+    /// id; see [`ForecastResponse::by_model`].
+    pub fn models(mut self, models: &[WeatherModel]) -> Self {
+        self.models = models.to_vec();
+        self
+    }
+
+    /// Add a weather model to the selection
+    pub fn model(mut self, model: WeatherModel) -> Self {
+        if !self.models.contains(&model) {
+            self.models.push(model);
+        }
+        self
+    }
+
+    /// Set response body format (default: JSON)
+    pub fn format(mut self, format: ResponseFormat) -> Self {
+        self.format = format;
         self
     }
 }
@@ -128,53 +213,286 @@ pub struct ForecastResponse {
     pub utc_offset_seconds: i32,
     pub timezone: String,
     pub timezone_abbreviation: String,
-    
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub hourly: Option<HourlyData>,
-    
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub hourly_units: Option<HashMap<String, String>>,
-    
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub daily: Option<DailyData>,
-    
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub daily_units: Option<HashMap<String, String>>,
-    
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub current: Option<CurrentData>,
-    
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub current_units: Option<HashMap<String, String>>,
 }
 
+impl ForecastResponse {
+    /// Coordinates of the grid cell the forecast was computed for
+    pub fn grid_point(&self) -> Coordinates {
+        Coordinates::new(self.latitude, self.longitude)
+    }
+
+    /// Distance in kilometers from the requested coordinates to the returned grid point
+    ///
+    /// The API snaps every location to a model grid cell, so this is the error
+    /// in position of the forecast: a few kilometers on fine grids, more near
+    /// coasts with [`CellSelection::Land`] or [`CellSelection::Sea`].
+    pub fn grid_distance_km(&self, requested: &Coordinates) -> f64 {
+        requested.distance_km(&self.grid_point())
+    }
+
+    /// Unit of an hourly variable, parsed from `hourly_units`
+    pub fn hourly_unit(&self, variable: &str) -> Option<Unit> {
+        unit_of(self.hourly_units.as_ref(), variable)
+    }
+
+    /// Unit of a daily variable, parsed from `daily_units`
+    pub fn daily_unit(&self, variable: &str) -> Option<Unit> {
+        unit_of(self.daily_units.as_ref(), variable)
+    }
+
+    /// Unit of a current variable, parsed from `current_units`
+    pub fn current_unit(&self, variable: &str) -> Option<Unit> {
+        unit_of(self.current_units.as_ref(), variable)
+    }
+
+    /// Hourly values of a variable tagged with their unit
+    ///
+    /// Returns `None` if the variable is missing or its unit cannot be converted.
+    pub fn hourly_quantities(&self, variable: &str) -> Option<Vec<Option<Quantity>>> {
+        let unit = self.hourly_unit(variable)?;
+        let values = self.hourly.as_ref()?.variables.get(variable)?;
+        Some(tag(values, unit))
+    }
+
+    /// Daily values of a variable tagged with their unit
+    ///
+    /// Returns `None` if the variable is missing or its unit cannot be converted.
+    pub fn daily_quantities(&self, variable: &str) -> Option<Vec<Option<Quantity>>> {
+        let unit = self.daily_unit(variable)?;
+        let values = self.daily.as_ref()?.variables.get(variable)?;
+        Some(tag(values, unit))
+    }
+
+    /// Current value of a variable tagged with its unit
+    pub fn current_quantity(&self, variable: &str) -> Option<Quantity> {
+        let unit = self.current_unit(variable)?;
+        let value = (*self.current.as_ref()?.variables.get(variable)?)?;
+        Some(Quantity::new(value, unit))
+    }
+
+    /// Convert every variable with a known unit to the units of `system`, in place
+    ///
+    /// Values and the `*_units` maps are updated together; variables without a
+    /// convertible unit (percentages, directions, codes, ...) are left untouched.
+    pub fn convert_to(&mut self, system: UnitSystem) {
+        if let (Some(hourly), Some(units)) = (&mut self.hourly, &mut self.hourly_units) {
+            for (variable, values) in &mut hourly.variables {
+                convert_values(units, variable, values, system);
+            }
+        }
+        if let (Some(daily), Some(units)) = (&mut self.daily, &mut self.daily_units) {
+            for (variable, values) in &mut daily.variables {
+                convert_values(units, variable, values, system);
+            }
+        }
+        if let (Some(current), Some(units)) = (&mut self.current, &mut self.current_units) {
+            for (variable, value) in &mut current.variables {
+                convert_values(units, variable, std::slice::from_mut(value), system);
+            }
+        }
+    }
+}
+
+fn unit_of(units: Option<&HashMap<String, String>>, variable: &str) -> Option<Unit> {
+    Unit::parse(units?.get(variable)?)
+}
+
+fn tag(values: &[Option<f64>], unit: Unit) -> Vec<Option<Quantity>> {
+    values
+        .iter()
+        .map(|v| v.map(|value| Quantity::new(value, unit)))
+        .collect()
+}
+
+fn convert_values(
+    units: &mut HashMap<String, String>,
+    variable: &str,
+    values: &mut [Option<f64>],
+    system: UnitSystem,
+) {
+    let Some(unit) = unit_of(Some(units), variable) else {
+        return;
+    };
+    let target = system.target(unit);
+    if target == unit {
+        return;
+    }
+
+    for value in values.iter_mut().flatten() {
+        *value = unit.convert(*value, target).unwrap_or(*value);
+    }
+    units.insert(variable.to_string(), target.symbol().to_string());
+}
+
 /// Hourly weather data with flexible variable support
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HourlyData {
     pub time: Vec<String>,
-    
+
     #[serde(flatten)]
     pub variables: HashMap<String, Vec<Option<f64>>>,
 }
 
+impl HourlyData {
+    /// Typed `weather_code` series, if it was requested
+    pub fn weather_codes(&self) -> Option<Vec<Option<WeatherCode>>> {
+        self.variables
+            .get(WEATHER_CODE)
+            .map(|values| weather_code::from_series(values))
+    }
+}
+
 /// Daily weather data with flexible variable support
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "RawDailyData")]
 pub struct DailyData {
     pub time: Vec<String>,
-    
+
     #[serde(flatten)]
     pub variables: HashMap<String, Vec<Option<f64>>>,
+
+    /// Variables reported as ISO 8601 times rather than numbers (e.g., `sunrise`, `sunset`)
+    #[serde(flatten)]
+    pub time_variables: HashMap<String, Vec<Option<String>>>,
+}
+
+/// Daily data as sent by the API, before splitting numeric and time variables
+#[derive(Deserialize)]
+struct RawDailyData {
+    time: Vec<String>,
+
+    #[serde(flatten)]
+    series: HashMap<String, Vec<Option<SeriesValue>>>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SeriesValue {
+    Number(f64),
+    Text(String),
+}
+
+impl From<RawDailyData> for DailyData {
+    fn from(raw: RawDailyData) -> Self {
+        let mut variables = HashMap::new();
+        let mut time_variables = HashMap::new();
+
+        for (name, values) in raw.series {
+            let is_text = values
+                .iter()
+                .any(|v| matches!(v, Some(SeriesValue::Text(_))));
+            if is_text {
+                let values = values
+                    .into_iter()
+                    .map(|v| match v? {
+                        SeriesValue::Text(text) => Some(text),
+                        SeriesValue::Number(number) => Some(number.to_string()),
+                    })
+                    .collect();
+                time_variables.insert(name, values);
+            } else {
+                let values = values
+                    .into_iter()
+                    .map(|v| match v? {
+                        SeriesValue::Number(number) => Some(number),
+                        SeriesValue::Text(_) => None,
+                    })
+                    .collect();
+                variables.insert(name, values);
+            }
+        }
+
+        Self {
+            time: raw.time,
+            variables,
+            time_variables,
+        }
+    }
+}
+
+impl DailyData {
+    /// Typed `weather_code` series (most severe condition of each day), if it was requested
+    pub fn weather_codes(&self) -> Option<Vec<Option<WeatherCode>>> {
+        self.variables
+            .get(WEATHER_CODE)
+            .map(|values| weather_code::from_series(values))
+    }
 }
 
 /// Current weather conditions
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CurrentData {
     pub time: String,
-    
+
     #[serde(flatten)]
     pub variables: HashMap<String, Option<f64>>,
 }
 
+impl CurrentData {
+    /// Typed current `weather_code`, if it was requested
+    pub fn weather_code(&self) -> Option<WeatherCode> {
+        self.variables
+            .get(WEATHER_CODE)
+            .copied()
+            .flatten()
+            .and_then(WeatherCode::from_value)
+    }
+
+    /// Whether it is currently daytime, if `is_day` was requested
+    pub fn is_day(&self) -> Option<bool> {
+        self.variables
+            .get("is_day")
+            .copied()
+            .flatten()
+            .map(|v| v != 0.0)
+    }
+}
+
+/// Forecast responses for unit tests
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use super::ForecastResponse;
+
+    /// Response for Berlin (52.52, 13.41) in GMT with the given sections
+    ///
+    /// `sections` is a JSON object of `hourly`, `daily`, `current` and their
+    /// `*_units`; it may also override header fields such as `utc_offset_seconds`.
+    pub(crate) fn forecast(sections: serde_json::Value) -> ForecastResponse {
+        let mut response = serde_json::json!({
+            "latitude": 52.52,
+            "longitude": 13.41,
+            "elevation": 38.0,
+            "generationtime_ms": 0.1,
+            "utc_offset_seconds": 0,
+            "timezone": "GMT",
+            "timezone_abbreviation": "GMT"
+        });
+        let serde_json::Value::Object(sections) = sections else {
+            panic!("forecast sections must be a JSON object");
+        };
+        response.as_object_mut().unwrap().extend(sections);
+        serde_json::from_value(response).unwrap()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -193,6 +511,22 @@ mod tests {
         assert_eq!(request.timezone.as_ref().unwrap(), "auto");
     }
 
+    #[test]
+    fn test_forecast_request_multiple_locations() {
+        let request = ForecastRequest::new(52.52, 13.41)
+            .location(48.14, 11.58)
+            .format(ResponseFormat::FlatBuffers);
+
+        assert_eq!(
+            request.locations(),
+            vec![
+                Coordinates::new(52.52, 13.41),
+                Coordinates::new(48.14, 11.58)
+            ]
+        );
+        assert_eq!(request.format, ResponseFormat::FlatBuffers);
+    }
+
     #[test]
     fn test_forecast_response_deserialization() {
         let json = r#"{
@@ -215,9 +549,115 @@ mod tests {
         let response: ForecastResponse = serde_json::from_str(json).unwrap();
         assert_eq!(response.latitude, 52.52);
         assert_eq!(response.timezone, "Europe/Berlin");
-        
+
         let hourly = response.hourly.unwrap();
         assert_eq!(hourly.time.len(), 2);
         assert_eq!(hourly.variables.get("temperature_2m").unwrap().len(), 2);
     }
+
+    #[test]
+    fn test_forecast_response_unit_conversion() {
+        let mut response = fixtures::forecast(serde_json::json!({
+            "hourly": {
+                "time": ["2022-07-01T00:00", "2022-07-01T01:00"],
+                "temperature_2m": [20.0, null],
+                "wind_speed_10m": [18.52, 0.0],
+                "visibility": [1000.0, 24140.0],
+                "relative_humidity_2m": [50.0, 60.0]
+            },
+            "hourly_units": {
+                "time": "iso8601",
+                "temperature_2m": "°C",
+                "wind_speed_10m": "km/h",
+                "visibility": "m",
+                "relative_humidity_2m": "%"
+            },
+            "current": {"time": "2022-07-01T00:00", "precipitation": 25.4},
+            "current_units": {"time": "iso8601", "precipitation": "mm"}
+        }));
+
+        assert_eq!(response.hourly_unit("temperature_2m"), Some(Unit::Celsius));
+        assert_eq!(
+            response.hourly_quantities("temperature_2m").unwrap(),
+            vec![Some(Quantity::new(20.0, Unit::Celsius)), None]
+        );
+        assert!(response.hourly_quantities("relative_humidity_2m").is_none());
+
+        response.convert_to(UnitSystem::Imperial);
+        let temperature = response.hourly_quantities("temperature_2m").unwrap();
+        assert_eq!(temperature[0].unwrap().unit, Unit::Fahrenheit);
+        assert!((temperature[0].unwrap().value - 68.0).abs() < 1e-9);
+        assert_eq!(
+            response.hourly_units.as_ref().unwrap()["wind_speed_10m"],
+            "mp/h"
+        );
+        assert_eq!(response.hourly_unit("visibility"), Some(Unit::Feet));
+        assert_eq!(
+            response.hourly.as_ref().unwrap().variables["relative_humidity_2m"],
+            vec![Some(50.0), Some(60.0)]
+        );
+        let precipitation = response.current_quantity("precipitation").unwrap();
+        assert_eq!(precipitation.unit, Unit::Inches);
+        assert!((precipitation.value - 1.0).abs() < 1e-9);
+
+        response.convert_to(UnitSystem::Aviation);
+        let wind = response.hourly_quantities("wind_speed_10m").unwrap();
+        assert_eq!(wind[0].unwrap().unit, Unit::Knots);
+        assert!((wind[0].unwrap().value - 10.0).abs() < 1e-9);
+        assert_eq!(response.hourly_unit("temperature_2m"), Some(Unit::Celsius));
+    }
+
+    #[test]
+    fn test_daily_sunrise_sunset_deserialization() {
+        let daily: DailyData = serde_json::from_str(
+            r#"{"time": ["2024-06-21", "2024-06-22"],
+                "temperature_2m_max": [24.1, null],
+                "sunrise": ["2024-06-21T04:43", "2024-06-22T04:43"],
+                "sunset": ["2024-06-21T21:21", null]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            daily.variables["temperature_2m_max"],
+            vec![Some(24.1), None]
+        );
+        assert_eq!(
+            daily.time_variables["sunset"],
+            vec![Some("2024-06-21T21:21".to_string()), None]
+        );
+        assert!(!daily.variables.contains_key("sunrise"));
+
+        // Round-trips through the same flat JSON layout
+        let json = serde_json::to_value(&daily).unwrap();
+        assert_eq!(json["sunrise"][1], "2024-06-22T04:43");
+        let again: DailyData = serde_json::from_value(json).unwrap();
+        assert_eq!(again.time_variables, daily.time_variables);
+    }
+
+    #[test]
+    fn test_weather_code_accessors() {
+        let hourly: HourlyData = serde_json::from_str(
+            r#"{"time": ["2022-07-01T00:00", "2022-07-01T01:00", "2022-07-01T02:00"],
+                "weather_code": [3.0, null, 95.0]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            hourly.weather_codes(),
+            Some(vec![
+                Some(WeatherCode::Overcast),
+                None,
+                Some(WeatherCode::Thunderstorm)
+            ])
+        );
+
+        let current: CurrentData = serde_json::from_str(
+            r#"{"time": "2022-07-01T00:00", "weather_code": 61.0, "is_day": 0.0}"#,
+        )
+        .unwrap();
+        assert_eq!(current.weather_code(), Some(WeatherCode::SlightRain));
+        assert_eq!(current.is_day(), Some(false));
+
+        let daily: DailyData = serde_json::from_str(r#"{"time": ["2022-07-01"]}"#).unwrap();
+        assert_eq!(daily.weather_codes(), None);
+    }
 }
@@ -8,6 +8,7 @@ pub struct GeocodingRequest {
     pub name: String,
     pub count: Option<u8>,
     pub language: Option<String>,
+    pub country_code: Option<String>,
     pub format: Option<String>,
 }
 
@@ -18,6 +19,7 @@ impl GeocodingRequest {
             name: name.into(),
             count: None,
             language: None,
+            country_code: None,
             format: None,
         }
     }
@@ -34,6 +36,12 @@ impl GeocodingRequest {
         self
     }
 
+    /// Only return results in this country (ISO 3166-1 alpha-2, e.g., "DE")
+    pub fn country_code(mut self, country_code: impl Into<String>) -> Self {
+        self.country_code = Some(country_code.into());
+        self
+    }
+
     /// Set response format (default: "json")
     pub fn format(mut self, format: impl Into<String>) -> Self {
         self.format = Some(format.into());
@@ -55,34 +63,34 @@ pub struct Location {
     pub name: String,
     pub latitude: f64,
     pub longitude: f64,
-    
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub elevation: Option<f64>,
-    
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timezone: Option<String>,
-    
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub country_code: Option<String>,
-    
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub country: Option<String>,
-    
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub admin1: Option<String>,
-    
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub admin2: Option<String>,
-    
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub admin3: Option<String>,
-    
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub admin4: Option<String>,
-    
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub population: Option<u64>,
-    
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub postcodes: Option<Vec<String>>,
 }
@@ -95,11 +103,13 @@ mod tests {
     fn test_geocoding_request_builder() {
         let request = GeocodingRequest::new("Berlin")
             .count(5)
-            .language("en");
+            .language("en")
+            .country_code("DE");
 
         assert_eq!(request.name, "Berlin");
         assert_eq!(request.count, Some(5));
         assert_eq!(request.language.as_ref().unwrap(), "en");
+        assert_eq!(request.country_code.as_deref(), Some("DE"));
     }
 
     #[test]
@@ -123,7 +133,7 @@ mod tests {
 
         let response: GeocodingResponse = serde_json::from_str(json).unwrap();
         assert_eq!(response.results.len(), 1);
-        
+
         let location = &response.results[0];
         assert_eq!(location.name, "Berlin");
         assert_eq!(location.latitude, 52.52437);
@@ -3,9 +3,16 @@
 pub mod common;
 pub mod forecast;
 pub mod geocoding;
+pub mod model;
+pub mod units;
+pub mod weather_code;
 
-pub use common::{Coordinates, PrecipitationUnit, TemperatureUnit, TimeFormat, WindSpeedUnit};
-pub use forecast::{
-    CurrentData, DailyData, ForecastRequest, ForecastResponse, HourlyData,
+pub use common::{
+    CellSelection, Coordinates, PrecipitationUnit, ResponseFormat, TemperatureUnit, TimeFormat,
+    WindSpeedUnit,
 };
+pub use forecast::{CurrentData, DailyData, ForecastRequest, ForecastResponse, HourlyData};
 pub use geocoding::{GeocodingRequest, GeocodingResponse, Location};
+pub use model::WeatherModel;
+pub use units::{Dimension, Quantity, Unit, UnitSystem};
+pub use weather_code::WeatherCode;
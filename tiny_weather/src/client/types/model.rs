@@ -0,0 +1,447 @@
+//! Weather models (`models` parameter)
+//!
+//! By default Open-Meteo picks the best model for each location. Requesting
+//! specific [`WeatherModel`]s returns their series side by side: with more than
+//! one model every variable is suffixed with the model id (for example
+//! `temperature_2m_ecmwf_ifs025`). [`ForecastResponse::by_model`] and
+//! [`ForecastResponse::for_model`] undo the suffixes, giving one plain response
+//! per model that the rest of the crate (aggregation, diffs, frames) works on.
+//!
+//! ```no_run
+//! # async fn run() -> tiny_weather::client::Result<()> {
+//! use tiny_weather::client::ForecastClient;
+//! use tiny_weather::client::types::{ForecastRequest, WeatherModel};
+//!
+//! let request = ForecastRequest::new(52.52, 13.41)
+//!     .hourly(&["temperature_2m"])
+//!     .models(&[WeatherModel::EcmwfIfs025, WeatherModel::GfsSeamless])
+//!     .model(WeatherModel::IconSeamless);
+//! let forecast = ForecastClient::new().get(request).await?;
+//!
+//! for (model, series) in forecast.hourly_by_model("temperature_2m") {
+//!     println!("{}: {:?}", model, series.first());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::forecast::{CurrentData, DailyData, ForecastResponse, HourlyData};
+
+/// Global or regional forecast model served by the Forecast API
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum WeatherModel {
+    /// Best available model for the location (the API default)
+    #[serde(rename = "best_match")]
+    BestMatch,
+    #[serde(rename = "ecmwf_ifs025")]
+    EcmwfIfs025,
+    /// GFS blended with HRRR over the US
+    #[serde(rename = "gfs_seamless")]
+    GfsSeamless,
+    #[serde(rename = "gfs_global")]
+    GfsGlobal,
+    #[serde(rename = "gfs_hrrr")]
+    GfsHrrr,
+    /// ICON global blended with ICON-EU and ICON-D2 where available
+    #[serde(rename = "icon_seamless")]
+    IconSeamless,
+    #[serde(rename = "icon_global")]
+    IconGlobal,
+    #[serde(rename = "icon_eu")]
+    IconEu,
+    #[serde(rename = "icon_d2")]
+    IconD2,
+    #[serde(rename = "cma_grapes_global")]
+    CmaGrapesGlobal,
+    #[serde(rename = "bom_access_global")]
+    BomAccessGlobal,
+    /// ARPEGE blended with AROME over France
+    #[serde(rename = "meteofrance_seamless")]
+    MeteoFranceSeamless,
+    #[serde(rename = "meteofrance_arpege_world")]
+    MeteoFranceArpegeWorld,
+    #[serde(rename = "meteofrance_arpege_europe")]
+    MeteoFranceArpegeEurope,
+    #[serde(rename = "meteofrance_arome_france")]
+    MeteoFranceAromeFrance,
+    #[serde(rename = "meteofrance_arome_france_hd")]
+    MeteoFranceAromeFranceHd,
+    /// UK Met Office global blended with the UK 2 km model
+    #[serde(rename = "ukmo_seamless")]
+    UkmoSeamless,
+    #[serde(rename = "ukmo_global_deterministic_10km")]
+    UkmoGlobal10km,
+    #[serde(rename = "ukmo_uk_deterministic_2km")]
+    UkmoUk2km,
+}
+
+impl WeatherModel {
+    /// Every model, in declaration order
+    pub const ALL: [WeatherModel; 19] = [
+        Self::BestMatch,
+        Self::EcmwfIfs025,
+        Self::GfsSeamless,
+        Self::GfsGlobal,
+        Self::GfsHrrr,
+        Self::IconSeamless,
+        Self::IconGlobal,
+        Self::IconEu,
+        Self::IconD2,
+        Self::CmaGrapesGlobal,
+        Self::BomAccessGlobal,
+        Self::MeteoFranceSeamless,
+        Self::MeteoFranceArpegeWorld,
+        Self::MeteoFranceArpegeEurope,
+        Self::MeteoFranceAromeFrance,
+        Self::MeteoFranceAromeFranceHd,
+        Self::UkmoSeamless,
+        Self::UkmoGlobal10km,
+        Self::UkmoUk2km,
+    ];
+
+    /// Model id used in the `models` parameter and variable suffixes
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::BestMatch => "best_match",
+            Self::EcmwfIfs025 => "ecmwf_ifs025",
+            Self::GfsSeamless => "gfs_seamless",
+            Self::GfsGlobal => "gfs_global",
+            Self::GfsHrrr => "gfs_hrrr",
+            Self::IconSeamless => "icon_seamless",
+            Self::IconGlobal => "icon_global",
+            Self::IconEu => "icon_eu",
+            Self::IconD2 => "icon_d2",
+            Self::CmaGrapesGlobal => "cma_grapes_global",
+            Self::BomAccessGlobal => "bom_access_global",
+            Self::MeteoFranceSeamless => "meteofrance_seamless",
+            Self::MeteoFranceArpegeWorld => "meteofrance_arpege_world",
+            Self::MeteoFranceArpegeEurope => "meteofrance_arpege_europe",
+            Self::MeteoFranceAromeFrance => "meteofrance_arome_france",
+            Self::MeteoFranceAromeFranceHd => "meteofrance_arome_france_hd",
+            Self::UkmoSeamless => "ukmo_seamless",
+            Self::UkmoGlobal10km => "ukmo_global_deterministic_10km",
+            Self::UkmoUk2km => "ukmo_uk_deterministic_2km",
+        }
+    }
+
+    /// Model for an id, if known
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|model| model.id() == id)
+    }
+
+    /// Human-readable name
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::BestMatch => "Best match",
+            Self::EcmwfIfs025 => "ECMWF IFS 0.25°",
+            Self::GfsSeamless => "NCEP GFS Seamless",
+            Self::GfsGlobal => "NCEP GFS Global",
+            Self::GfsHrrr => "NCEP HRRR",
+            Self::IconSeamless => "DWD ICON Seamless",
+            Self::IconGlobal => "DWD ICON Global",
+            Self::IconEu => "DWD ICON-EU",
+            Self::IconD2 => "DWD ICON-D2",
+            Self::CmaGrapesGlobal => "CMA GRAPES Global",
+            Self::BomAccessGlobal => "BOM ACCESS Global",
+            Self::MeteoFranceSeamless => "Météo-France Seamless",
+            Self::MeteoFranceArpegeWorld => "Météo-France ARPEGE World",
+            Self::MeteoFranceArpegeEurope => "Météo-France ARPEGE Europe",
+            Self::MeteoFranceAromeFrance => "Météo-France AROME France",
+            Self::MeteoFranceAromeFranceHd => "Météo-France AROME France HD",
+            Self::UkmoSeamless => "UK Met Office Seamless",
+            Self::UkmoGlobal10km => "UK Met Office Global 10 km",
+            Self::UkmoUk2km => "UK Met Office UK 2 km",
+        }
+    }
+
+    /// Whether the model only covers part of the globe
+    pub fn is_regional(self) -> bool {
+        matches!(
+            self,
+            Self::GfsHrrr
+                | Self::IconEu
+                | Self::IconD2
+                | Self::MeteoFranceArpegeEurope
+                | Self::MeteoFranceAromeFrance
+                | Self::MeteoFranceAromeFranceHd
+                | Self::UkmoUk2km
+        )
+    }
+
+    /// Length of the model's forecast in hours, `None` for blends of several models
+    pub fn forecast_hours(self) -> Option<u32> {
+        match self {
+            Self::BestMatch
+            | Self::GfsSeamless
+            | Self::IconSeamless
+            | Self::MeteoFranceSeamless
+            | Self::UkmoSeamless => None,
+            Self::EcmwfIfs025 => Some(360),
+            Self::GfsGlobal => Some(384),
+            Self::GfsHrrr => Some(48),
+            Self::IconGlobal => Some(180),
+            Self::IconEu => Some(120),
+            Self::IconD2 => Some(48),
+            Self::CmaGrapesGlobal | Self::BomAccessGlobal => Some(240),
+            Self::MeteoFranceArpegeWorld | Self::MeteoFranceArpegeEurope => Some(102),
+            Self::MeteoFranceAromeFrance | Self::MeteoFranceAromeFranceHd => Some(42),
+            Self::UkmoGlobal10km => Some(168),
+            Self::UkmoUk2km => Some(120),
+        }
+    }
+
+    /// Model suffixing a variable name, with the variable name without the suffix
+    ///
+    /// Returns `None` for variables of single-model responses, which are not suffixed.
+    pub fn split_variable(name: &str) -> Option<(&str, Self)> {
+        Self::ALL
+            .into_iter()
+            .filter_map(|model| {
+                let variable = name.strip_suffix(model.id())?.strip_suffix('_')?;
+                Some((variable, model))
+            })
+            .max_by_key(|(_, model)| model.id().len())
+    }
+}
+
+impl fmt::Display for WeatherModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for WeatherModel {
+    type Err = String;
+
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        Self::from_id(id).ok_or_else(|| format!("unknown weather model: {}", id))
+    }
+}
+
+/// Entries of `map` suffixed with `model`, keyed by the unsuffixed name
+fn select<V: Clone>(map: &HashMap<String, V>, model: WeatherModel) -> HashMap<String, V> {
+    map.iter()
+        .filter_map(|(name, value)| match WeatherModel::split_variable(name)? {
+            (variable, m) if m == model => Some((variable.to_string(), value.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Series of one variable per model, from suffixed entries of `variables`
+fn series_by_model<'a>(
+    variables: &'a HashMap<String, Vec<Option<f64>>>,
+    variable: &str,
+) -> BTreeMap<WeatherModel, &'a [Option<f64>]> {
+    variables
+        .iter()
+        .filter_map(|(name, values)| match WeatherModel::split_variable(name)? {
+            (v, model) if v == variable => Some((model, values.as_slice())),
+            _ => None,
+        })
+        .collect()
+}
+
+impl ForecastResponse {
+    /// Models whose series are in this multi-model response
+    pub fn models(&self) -> Vec<WeatherModel> {
+        let hourly = self.hourly.iter().flat_map(|h| h.variables.keys());
+        let daily = self
+            .daily
+            .iter()
+            .flat_map(|d| d.variables.keys().chain(d.time_variables.keys()));
+        let current = self.current.iter().flat_map(|c| c.variables.keys());
+
+        let mut models: Vec<WeatherModel> = hourly
+            .chain(daily)
+            .chain(current)
+            .filter_map(|name| Some(WeatherModel::split_variable(name)?.1))
+            .collect();
+        models.sort();
+        models.dedup();
+        models
+    }
+
+    /// Response with only one model's series, under their unsuffixed names
+    ///
+    /// Returns `None` if the model is not in this multi-model response.
+    pub fn for_model(&self, model: WeatherModel) -> Option<ForecastResponse> {
+        if !self.models().contains(&model) {
+            return None;
+        }
+
+        let units = |units: &Option<HashMap<String, String>>| {
+            units.as_ref().map(|units| {
+                let mut selected = select(units, model);
+                if let Some(time) = units.get("time") {
+                    selected.insert("time".to_string(), time.clone());
+                }
+                selected
+            })
+        };
+        Some(ForecastResponse {
+            hourly: self.hourly.as_ref().map(|hourly| HourlyData {
+                time: hourly.time.clone(),
+                variables: select(&hourly.variables, model),
+            }),
+            hourly_units: units(&self.hourly_units),
+            daily: self.daily.as_ref().map(|daily| DailyData {
+                time: daily.time.clone(),
+                variables: select(&daily.variables, model),
+                time_variables: select(&daily.time_variables, model),
+            }),
+            daily_units: units(&self.daily_units),
+            current: self.current.as_ref().map(|current| CurrentData {
+                time: current.time.clone(),
+                variables: select(&current.variables, model),
+            }),
+            current_units: units(&self.current_units),
+            timezone: self.timezone.clone(),
+            timezone_abbreviation: self.timezone_abbreviation.clone(),
+            ..*self
+        })
+    }
+
+    /// Split a multi-model response into one plain response per model
+    pub fn by_model(&self) -> BTreeMap<WeatherModel, ForecastResponse> {
+        self.models()
+            .into_iter()
+            .filter_map(|model| Some((model, self.for_model(model)?)))
+            .collect()
+    }
+
+    /// Hourly series of one (unsuffixed) variable from every model
+    pub fn hourly_by_model(&self, variable: &str) -> BTreeMap<WeatherModel, &[Option<f64>]> {
+        self.hourly
+            .as_ref()
+            .map(|hourly| series_by_model(&hourly.variables, variable))
+            .unwrap_or_default()
+    }
+
+    /// Daily series of one (unsuffixed) variable from every model
+    pub fn daily_by_model(&self, variable: &str) -> BTreeMap<WeatherModel, &[Option<f64>]> {
+        self.daily
+            .as_ref()
+            .map(|daily| series_by_model(&daily.variables, variable))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::types::forecast::fixtures;
+
+    #[test]
+    fn test_model_ids() {
+        for model in WeatherModel::ALL {
+            assert_eq!(WeatherModel::from_id(model.id()), Some(model));
+            assert_eq!(
+                serde_json::to_value(model).unwrap(),
+                serde_json::Value::String(model.id().to_string())
+            );
+        }
+        assert_eq!("icon_d2".parse::<WeatherModel>(), Ok(WeatherModel::IconD2));
+        assert!("icon_d3".parse::<WeatherModel>().is_err());
+        assert!(WeatherModel::GfsHrrr.is_regional());
+        assert!(!WeatherModel::EcmwfIfs025.is_regional());
+    }
+
+    #[test]
+    fn test_split_variable() {
+        assert_eq!(
+            WeatherModel::split_variable("temperature_2m_ecmwf_ifs025"),
+            Some(("temperature_2m", WeatherModel::EcmwfIfs025))
+        );
+        // The longest matching id wins
+        assert_eq!(
+            WeatherModel::split_variable("rain_meteofrance_arome_france_hd"),
+            Some(("rain", WeatherModel::MeteoFranceAromeFranceHd))
+        );
+        assert_eq!(WeatherModel::split_variable("temperature_2m"), None);
+        assert_eq!(WeatherModel::split_variable("xicon_d2"), None);
+    }
+
+    fn multi_model() -> ForecastResponse {
+        fixtures::forecast(serde_json::json!({
+            "hourly": {
+                "time": ["2024-01-01T00:00", "2024-01-01T01:00"],
+                "temperature_2m_ecmwf_ifs025": [1.0, 2.0],
+                "temperature_2m_gfs_seamless": [1.5, 2.5],
+                "precipitation_gfs_seamless": [0.0, 0.2],
+                "temperature_2m_icon_d2": [0.5, null]
+            },
+            "hourly_units": {
+                "time": "iso8601",
+                "temperature_2m_ecmwf_ifs025": "°C",
+                "temperature_2m_gfs_seamless": "°C",
+                "precipitation_gfs_seamless": "mm",
+                "temperature_2m_icon_d2": "°C"
+            },
+            "daily": {
+                "time": ["2024-01-01"],
+                "sunrise_gfs_seamless": ["2024-01-01T08:17"]
+            }
+        }))
+    }
+
+    #[test]
+    fn test_group_by_model() {
+        let forecast = multi_model();
+        assert_eq!(
+            forecast.models(),
+            vec![
+                WeatherModel::EcmwfIfs025,
+                WeatherModel::GfsSeamless,
+                WeatherModel::IconD2
+            ]
+        );
+
+        let gfs = forecast.for_model(WeatherModel::GfsSeamless).unwrap();
+        let hourly = gfs.hourly.as_ref().unwrap();
+        assert_eq!(hourly.variables.len(), 2);
+        assert_eq!(
+            hourly.variables["precipitation"],
+            vec![Some(0.0), Some(0.2)]
+        );
+        assert_eq!(
+            gfs.hourly_unit("temperature_2m"),
+            forecast.hourly_unit("temperature_2m_gfs_seamless")
+        );
+        assert_eq!(gfs.hourly_units.as_ref().unwrap()["time"], "iso8601");
+        assert_eq!(
+            gfs.daily.as_ref().unwrap().time_variables["sunrise"],
+            vec![Some("2024-01-01T08:17".to_string())]
+        );
+        assert!(forecast.for_model(WeatherModel::GfsGlobal).is_none());
+
+        let by_model = forecast.by_model();
+        assert_eq!(by_model.len(), 3);
+        assert!(
+            by_model[&WeatherModel::IconD2]
+                .hourly
+                .as_ref()
+                .unwrap()
+                .variables
+                .contains_key("temperature_2m")
+        );
+
+        let temperature = forecast.hourly_by_model("temperature_2m");
+        assert_eq!(temperature.len(), 3);
+        assert_eq!(
+            temperature[&WeatherModel::EcmwfIfs025],
+            &[Some(1.0), Some(2.0)]
+        );
+        assert!(
+            forecast
+                .hourly_by_model("precipitation")
+                .contains_key(&WeatherModel::GfsSeamless)
+        );
+    }
+}
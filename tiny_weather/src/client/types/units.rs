@@ -0,0 +1,383 @@
+//! Physical units and unit-tagged quantities
+//!
+//! Responses report the unit of every variable as a string in the `*_units`
+//! maps (`"°C"`, `"km/h"`, `"mm"`, ...). [`Unit::parse`] turns those strings into
+//! a [`Unit`], and [`Quantity`] pairs a value with its unit so it can be
+//! converted locally instead of requesting the data again in another unit.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::common::{PrecipitationUnit, TemperatureUnit, WindSpeedUnit};
+
+/// Meters per second in one knot
+const KNOT_MS: f64 = 1852.0 / 3600.0;
+/// Meters per second in one mile per hour
+const MPH_MS: f64 = 0.44704;
+/// Hectopascals in one inch of mercury
+const INHG_HPA: f64 = 33.863_886_666_7;
+
+/// Physical quantity a unit measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dimension {
+    Temperature,
+    Speed,
+    Length,
+    Pressure,
+}
+
+/// Unit of a response variable that can be converted locally
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Unit {
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+    KilometersPerHour,
+    MetersPerSecond,
+    MilesPerHour,
+    Knots,
+    Millimeters,
+    Centimeters,
+    Inches,
+    Meters,
+    Feet,
+    Hectopascals,
+    InchesOfMercury,
+}
+
+impl Unit {
+    /// Parse a unit string from a `*_units` map
+    ///
+    /// Returns `None` for units that have nothing to convert to (`%`, `°`,
+    /// `W/m²`, `wmo code`, ...).
+    pub fn parse(unit: &str) -> Option<Self> {
+        Some(match unit.trim() {
+            "°C" => Self::Celsius,
+            "°F" => Self::Fahrenheit,
+            "K" => Self::Kelvin,
+            "km/h" => Self::KilometersPerHour,
+            "m/s" => Self::MetersPerSecond,
+            "mp/h" | "mph" => Self::MilesPerHour,
+            "kn" | "kt" => Self::Knots,
+            "mm" => Self::Millimeters,
+            "cm" => Self::Centimeters,
+            "inch" | "in" => Self::Inches,
+            "m" => Self::Meters,
+            "ft" => Self::Feet,
+            "hPa" => Self::Hectopascals,
+            "inHg" => Self::InchesOfMercury,
+            _ => return None,
+        })
+    }
+
+    /// Unit string as Open-Meteo writes it
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Celsius => "°C",
+            Self::Fahrenheit => "°F",
+            Self::Kelvin => "K",
+            Self::KilometersPerHour => "km/h",
+            Self::MetersPerSecond => "m/s",
+            Self::MilesPerHour => "mp/h",
+            Self::Knots => "kn",
+            Self::Millimeters => "mm",
+            Self::Centimeters => "cm",
+            Self::Inches => "inch",
+            Self::Meters => "m",
+            Self::Feet => "ft",
+            Self::Hectopascals => "hPa",
+            Self::InchesOfMercury => "inHg",
+        }
+    }
+
+    /// Physical quantity measured by this unit
+    pub fn dimension(self) -> Dimension {
+        match self {
+            Self::Celsius | Self::Fahrenheit | Self::Kelvin => Dimension::Temperature,
+            Self::KilometersPerHour | Self::MetersPerSecond | Self::MilesPerHour | Self::Knots => {
+                Dimension::Speed
+            }
+            Self::Millimeters | Self::Centimeters | Self::Inches | Self::Meters | Self::Feet => {
+                Dimension::Length
+            }
+            Self::Hectopascals | Self::InchesOfMercury => Dimension::Pressure,
+        }
+    }
+
+    /// Convert a value from this unit to `target`, or `None` if they measure different things
+    pub fn convert(self, value: f64, target: Unit) -> Option<f64> {
+        if self == target {
+            return Some(value);
+        }
+        if self.dimension() != target.dimension() {
+            return None;
+        }
+
+        let (scale, offset) = self.to_base();
+        let (target_scale, target_offset) = target.to_base();
+        Some((value * scale + offset - target_offset) / target_scale)
+    }
+
+    /// Scale and offset to the base unit of the dimension (°C, m/s, m, hPa)
+    fn to_base(self) -> (f64, f64) {
+        match self {
+            Self::Celsius => (1.0, 0.0),
+            Self::Fahrenheit => (5.0 / 9.0, -32.0 * 5.0 / 9.0),
+            Self::Kelvin => (1.0, -273.15),
+            Self::KilometersPerHour => (1.0 / 3.6, 0.0),
+            Self::MetersPerSecond => (1.0, 0.0),
+            Self::MilesPerHour => (MPH_MS, 0.0),
+            Self::Knots => (KNOT_MS, 0.0),
+            Self::Millimeters => (0.001, 0.0),
+            Self::Centimeters => (0.01, 0.0),
+            Self::Inches => (0.0254, 0.0),
+            Self::Meters => (1.0, 0.0),
+            Self::Feet => (0.3048, 0.0),
+            Self::Hectopascals => (1.0, 0.0),
+            Self::InchesOfMercury => (INHG_HPA, 0.0),
+        }
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+impl From<TemperatureUnit> for Unit {
+    fn from(unit: TemperatureUnit) -> Self {
+        match unit {
+            TemperatureUnit::Celsius => Self::Celsius,
+            TemperatureUnit::Fahrenheit => Self::Fahrenheit,
+        }
+    }
+}
+
+impl From<WindSpeedUnit> for Unit {
+    fn from(unit: WindSpeedUnit) -> Self {
+        match unit {
+            WindSpeedUnit::Kmh => Self::KilometersPerHour,
+            WindSpeedUnit::Ms => Self::MetersPerSecond,
+            WindSpeedUnit::Mph => Self::MilesPerHour,
+            WindSpeedUnit::Kn => Self::Knots,
+        }
+    }
+}
+
+impl From<PrecipitationUnit> for Unit {
+    fn from(unit: PrecipitationUnit) -> Self {
+        match unit {
+            PrecipitationUnit::Mm => Self::Millimeters,
+            PrecipitationUnit::Inch => Self::Inches,
+        }
+    }
+}
+
+/// Target units for converting a whole response
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnitSystem {
+    /// °C, km/h, mm/cm, m, hPa
+    #[default]
+    Metric,
+    /// °F, mph, inch, ft, inHg
+    Imperial,
+    /// °C, knots, mm/cm, ft (heights and distances), hPa
+    Aviation,
+}
+
+impl UnitSystem {
+    /// Every unit system
+    pub const ALL: [UnitSystem; 3] = [Self::Metric, Self::Imperial, Self::Aviation];
+
+    /// Lowercase name, as used in configuration and on the command line
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Metric => "metric",
+            Self::Imperial => "imperial",
+            Self::Aviation => "aviation",
+        }
+    }
+
+    /// Unit that values in `unit` are converted to in this system
+    pub fn target(self, unit: Unit) -> Unit {
+        use Unit::*;
+
+        match (self, unit.dimension()) {
+            (Self::Metric | Self::Aviation, Dimension::Temperature) => Celsius,
+            (Self::Imperial, Dimension::Temperature) => Fahrenheit,
+            (Self::Metric, Dimension::Speed) => KilometersPerHour,
+            (Self::Imperial, Dimension::Speed) => MilesPerHour,
+            (Self::Aviation, Dimension::Speed) => Knots,
+            (Self::Metric | Self::Aviation, Dimension::Pressure) => Hectopascals,
+            (Self::Imperial, Dimension::Pressure) => InchesOfMercury,
+            // Precipitation and snow amounts keep their scale; heights and distances don't
+            (Self::Metric, Dimension::Length) => match unit {
+                Meters | Feet => Meters,
+                Inches => Millimeters,
+                other => other,
+            },
+            (Self::Imperial, Dimension::Length) => match unit {
+                Meters | Feet => Feet,
+                _ => Inches,
+            },
+            (Self::Aviation, Dimension::Length) => match unit {
+                Meters | Feet => Feet,
+                Inches => Millimeters,
+                other => other,
+            },
+        }
+    }
+}
+
+impl fmt::Display for UnitSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for UnitSystem {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|system| system.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("unknown unit system: {}", name))
+    }
+}
+
+/// A value tagged with its unit
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Quantity {
+    pub value: f64,
+    pub unit: Unit,
+}
+
+impl Quantity {
+    /// Create a new quantity
+    pub fn new(value: f64, unit: Unit) -> Self {
+        Self { value, unit }
+    }
+
+    /// Convert to another unit, or `None` if it measures something else
+    pub fn to(self, unit: Unit) -> Option<Self> {
+        Some(Self::new(self.unit.convert(self.value, unit)?, unit))
+    }
+
+    /// Convert to the unit used for this quantity in a unit system
+    pub fn to_system(self, system: UnitSystem) -> Self {
+        let unit = system.target(self.unit);
+        self.to(unit).unwrap_or(self)
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match f.precision() {
+            Some(precision) => write!(f, "{:.*} {}", precision, self.value, self.unit),
+            None => write!(f, "{} {}", self.value, self.unit),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.unwrap();
+        assert!(
+            (actual - expected).abs() < 1e-6,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_unit_parse_roundtrip() {
+        for unit in [
+            Unit::Celsius,
+            Unit::Fahrenheit,
+            Unit::Kelvin,
+            Unit::KilometersPerHour,
+            Unit::MetersPerSecond,
+            Unit::MilesPerHour,
+            Unit::Knots,
+            Unit::Millimeters,
+            Unit::Centimeters,
+            Unit::Inches,
+            Unit::Meters,
+            Unit::Feet,
+            Unit::Hectopascals,
+            Unit::InchesOfMercury,
+        ] {
+            assert_eq!(Unit::parse(unit.symbol()), Some(unit));
+        }
+        assert_eq!(Unit::parse("%"), None);
+        assert_eq!(Unit::parse("wmo code"), None);
+    }
+
+    #[test]
+    fn test_unit_conversion() {
+        assert_close(Unit::Celsius.convert(100.0, Unit::Fahrenheit), 212.0);
+        assert_close(Unit::Fahrenheit.convert(32.0, Unit::Celsius), 0.0);
+        assert_close(Unit::Kelvin.convert(273.15, Unit::Fahrenheit), 32.0);
+        assert_close(Unit::KilometersPerHour.convert(1.852, Unit::Knots), 1.0);
+        assert_close(
+            Unit::MilesPerHour.convert(1.0, Unit::KilometersPerHour),
+            1.609344,
+        );
+        assert_close(Unit::Inches.convert(1.0, Unit::Millimeters), 25.4);
+        assert_close(Unit::Meters.convert(1000.0, Unit::Feet), 3280.839895);
+        assert_close(
+            Unit::Hectopascals.convert(1013.25, Unit::InchesOfMercury),
+            29.921255,
+        );
+        assert_eq!(Unit::Celsius.convert(1.0, Unit::Knots), None);
+    }
+
+    #[test]
+    fn test_unit_system_targets() {
+        assert_eq!(UnitSystem::Imperial.target(Unit::Celsius), Unit::Fahrenheit);
+        assert_eq!(UnitSystem::Imperial.target(Unit::Millimeters), Unit::Inches);
+        assert_eq!(UnitSystem::Imperial.target(Unit::Meters), Unit::Feet);
+        assert_eq!(UnitSystem::Metric.target(Unit::Inches), Unit::Millimeters);
+        assert_eq!(
+            UnitSystem::Metric.target(Unit::Centimeters),
+            Unit::Centimeters
+        );
+        assert_eq!(
+            UnitSystem::Aviation.target(Unit::KilometersPerHour),
+            Unit::Knots
+        );
+        assert_eq!(UnitSystem::Aviation.target(Unit::Meters), Unit::Feet);
+        assert_eq!(UnitSystem::Aviation.target(Unit::Fahrenheit), Unit::Celsius);
+    }
+
+    #[test]
+    fn test_unit_system_names() {
+        for system in UnitSystem::ALL {
+            assert_eq!(system.to_string().parse::<UnitSystem>(), Ok(system));
+        }
+        assert_eq!("Imperial".parse::<UnitSystem>(), Ok(UnitSystem::Imperial));
+        assert!("nautical".parse::<UnitSystem>().is_err());
+    }
+
+    #[test]
+    fn test_quantity() {
+        let temperature = Quantity::new(20.0, Unit::Celsius);
+        assert_eq!(temperature.to_string(), "20 °C");
+        assert_eq!(
+            format!("{:.1}", temperature.to_system(UnitSystem::Imperial)),
+            "68.0 °F"
+        );
+        assert_eq!(temperature.to(Unit::Millimeters), None);
+        assert_eq!(Unit::from(WindSpeedUnit::Kn), Unit::Knots);
+    }
+}
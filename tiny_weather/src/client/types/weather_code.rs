@@ -0,0 +1,560 @@
+//! WMO weather interpretation codes (`weather_code` variable)
+//!
+//! Open-Meteo reports the most severe weather condition of each interval as a
+//! WMO code. [`WeatherCode`] covers every code the API emits, with localized
+//! descriptions and day/night icons. Codes are ordered by this crate's severity
+//! ranking, so `max()` of a day's hourly codes is its worst weather in that order.
+//! The API's daily `weather_code` is the numerically highest code instead, so the
+//! two can differ (e.g., light freezing drizzle 56 ranks above heavy rain 65 here).
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Name of the weather code variable in API requests and responses
+pub const WEATHER_CODE: &str = "weather_code";
+
+/// WMO weather interpretation code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "u8", into = "u8")]
+#[repr(u8)]
+pub enum WeatherCode {
+    Clear = 0,
+    MainlyClear = 1,
+    PartlyCloudy = 2,
+    Overcast = 3,
+    Fog = 45,
+    DepositingRimeFog = 48,
+    LightDrizzle = 51,
+    ModerateDrizzle = 53,
+    DenseDrizzle = 55,
+    LightFreezingDrizzle = 56,
+    DenseFreezingDrizzle = 57,
+    SlightRain = 61,
+    ModerateRain = 63,
+    HeavyRain = 65,
+    LightFreezingRain = 66,
+    HeavyFreezingRain = 67,
+    SlightSnowfall = 71,
+    ModerateSnowfall = 73,
+    HeavySnowfall = 75,
+    SnowGrains = 77,
+    SlightRainShowers = 80,
+    ModerateRainShowers = 81,
+    ViolentRainShowers = 82,
+    SlightSnowShowers = 85,
+    HeavySnowShowers = 86,
+    Thunderstorm = 95,
+    ThunderstormWithSlightHail = 96,
+    ThunderstormWithHeavyHail = 99,
+}
+
+/// All codes from least to most severe
+///
+/// Freezing precipitation ranks above heavy snow because of the icing hazard,
+/// and every thunderstorm ranks above any non-convective condition.
+const BY_SEVERITY: [WeatherCode; 28] = [
+    WeatherCode::Clear,
+    WeatherCode::MainlyClear,
+    WeatherCode::PartlyCloudy,
+    WeatherCode::Overcast,
+    WeatherCode::Fog,
+    WeatherCode::DepositingRimeFog,
+    WeatherCode::LightDrizzle,
+    WeatherCode::ModerateDrizzle,
+    WeatherCode::DenseDrizzle,
+    WeatherCode::SlightRain,
+    WeatherCode::SlightRainShowers,
+    WeatherCode::SnowGrains,
+    WeatherCode::SlightSnowfall,
+    WeatherCode::SlightSnowShowers,
+    WeatherCode::ModerateRain,
+    WeatherCode::ModerateRainShowers,
+    WeatherCode::ModerateSnowfall,
+    WeatherCode::HeavyRain,
+    WeatherCode::ViolentRainShowers,
+    WeatherCode::HeavySnowfall,
+    WeatherCode::HeavySnowShowers,
+    WeatherCode::LightFreezingDrizzle,
+    WeatherCode::DenseFreezingDrizzle,
+    WeatherCode::LightFreezingRain,
+    WeatherCode::HeavyFreezingRain,
+    WeatherCode::Thunderstorm,
+    WeatherCode::ThunderstormWithSlightHail,
+    WeatherCode::ThunderstormWithHeavyHail,
+];
+
+/// Nerd Font glyphs (Weather Icons set, `nf-weather-*`)
+mod nerd {
+    pub const DAY_SUNNY: &str = "\u{e30d}";
+    pub const NIGHT_CLEAR: &str = "\u{e32b}";
+    pub const DAY_SUNNY_OVERCAST: &str = "\u{e30c}";
+    pub const NIGHT_PARTLY_CLOUDY: &str = "\u{e379}";
+    pub const DAY_CLOUDY: &str = "\u{e302}";
+    pub const NIGHT_CLOUDY: &str = "\u{e37e}";
+    pub const CLOUDY: &str = "\u{e312}";
+    pub const DAY_FOG: &str = "\u{e303}";
+    pub const NIGHT_FOG: &str = "\u{e346}";
+    pub const DAY_SPRINKLE: &str = "\u{e30b}";
+    pub const NIGHT_SPRINKLE: &str = "\u{e328}";
+    pub const DAY_RAIN: &str = "\u{e308}";
+    pub const NIGHT_RAIN: &str = "\u{e325}";
+    pub const DAY_SLEET: &str = "\u{e3aa}";
+    pub const NIGHT_SLEET: &str = "\u{e3ac}";
+    pub const DAY_SNOW: &str = "\u{e30a}";
+    pub const NIGHT_SNOW: &str = "\u{e327}";
+    pub const DAY_SHOWERS: &str = "\u{e309}";
+    pub const NIGHT_SHOWERS: &str = "\u{e326}";
+    pub const DAY_THUNDERSTORM: &str = "\u{e30f}";
+    pub const NIGHT_THUNDERSTORM: &str = "\u{e32a}";
+    pub const DAY_HAIL: &str = "\u{e304}";
+    pub const NIGHT_HAIL: &str = "\u{e321}";
+}
+
+impl WeatherCode {
+    /// Every code Open-Meteo emits, in numeric order
+    pub const ALL: [WeatherCode; 28] = [
+        Self::Clear,
+        Self::MainlyClear,
+        Self::PartlyCloudy,
+        Self::Overcast,
+        Self::Fog,
+        Self::DepositingRimeFog,
+        Self::LightDrizzle,
+        Self::ModerateDrizzle,
+        Self::DenseDrizzle,
+        Self::LightFreezingDrizzle,
+        Self::DenseFreezingDrizzle,
+        Self::SlightRain,
+        Self::ModerateRain,
+        Self::HeavyRain,
+        Self::LightFreezingRain,
+        Self::HeavyFreezingRain,
+        Self::SlightSnowfall,
+        Self::ModerateSnowfall,
+        Self::HeavySnowfall,
+        Self::SnowGrains,
+        Self::SlightRainShowers,
+        Self::ModerateRainShowers,
+        Self::ViolentRainShowers,
+        Self::SlightSnowShowers,
+        Self::HeavySnowShowers,
+        Self::Thunderstorm,
+        Self::ThunderstormWithSlightHail,
+        Self::ThunderstormWithHeavyHail,
+    ];
+
+    /// Numeric WMO code
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+
+    /// Look up a numeric WMO code
+    pub fn from_code(code: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.code() == code)
+    }
+
+    /// Convert a value from a series, which the API returns as a float
+    pub fn from_value(value: f64) -> Option<Self> {
+        if value.fract() != 0.0 || !(0.0..=f64::from(u8::MAX)).contains(&value) {
+            return None;
+        }
+        Self::from_code(value as u8)
+    }
+
+    /// Rank from 0 (clear sky) to 27 (thunderstorm with heavy hail)
+    pub fn severity(self) -> u8 {
+        BY_SEVERITY
+            .iter()
+            .position(|c| *c == self)
+            .expect("every code has a severity") as u8
+    }
+
+    /// Whether the code reports any precipitation
+    pub fn is_precipitation(self) -> bool {
+        self.code() >= 51
+    }
+
+    /// Whether the code reports freezing drizzle or rain
+    pub fn is_freezing(self) -> bool {
+        matches!(self.code(), 56 | 57 | 66 | 67)
+    }
+
+    /// Whether the code reports snow
+    pub fn is_snow(self) -> bool {
+        matches!(self.code(), 71..=77 | 85 | 86)
+    }
+
+    /// Whether the code reports a thunderstorm
+    pub fn is_thunderstorm(self) -> bool {
+        self.code() >= 95
+    }
+
+    /// English description (e.g., "Moderate rain")
+    pub fn description(self) -> &'static str {
+        self.descriptions()[0]
+    }
+
+    /// Description in the given language (ISO 639-1 code such as `de` or `fr-CH`)
+    ///
+    /// Supported languages are English, German, French and Spanish; any other
+    /// language falls back to English.
+    pub fn description_in(self, language: &str) -> &'static str {
+        let language = language
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let index = match language.as_str() {
+            "de" => 1,
+            "fr" => 2,
+            "es" => 3,
+            _ => 0,
+        };
+        self.descriptions()[index]
+    }
+
+    /// Descriptions in English, German, French and Spanish
+    fn descriptions(self) -> [&'static str; 4] {
+        match self {
+            Self::Clear => [
+                "Clear sky",
+                "Klarer Himmel",
+                "Ciel dégagé",
+                "Cielo despejado",
+            ],
+            Self::MainlyClear => [
+                "Mainly clear",
+                "Überwiegend klar",
+                "Plutôt dégagé",
+                "Mayormente despejado",
+            ],
+            Self::PartlyCloudy => [
+                "Partly cloudy",
+                "Teilweise bewölkt",
+                "Partiellement nuageux",
+                "Parcialmente nublado",
+            ],
+            Self::Overcast => ["Overcast", "Bedeckt", "Couvert", "Cubierto"],
+            Self::Fog => ["Fog", "Nebel", "Brouillard", "Niebla"],
+            Self::DepositingRimeFog => [
+                "Depositing rime fog",
+                "Nebel mit Reifansatz",
+                "Brouillard givrant",
+                "Niebla con escarcha",
+            ],
+            Self::LightDrizzle => [
+                "Light drizzle",
+                "Leichter Nieselregen",
+                "Bruine légère",
+                "Llovizna ligera",
+            ],
+            Self::ModerateDrizzle => [
+                "Moderate drizzle",
+                "Mäßiger Nieselregen",
+                "Bruine modérée",
+                "Llovizna moderada",
+            ],
+            Self::DenseDrizzle => [
+                "Dense drizzle",
+                "Starker Nieselregen",
+                "Bruine dense",
+                "Llovizna densa",
+            ],
+            Self::LightFreezingDrizzle => [
+                "Light freezing drizzle",
+                "Leichter gefrierender Nieselregen",
+                "Bruine verglaçante légère",
+                "Llovizna helada ligera",
+            ],
+            Self::DenseFreezingDrizzle => [
+                "Dense freezing drizzle",
+                "Starker gefrierender Nieselregen",
+                "Bruine verglaçante dense",
+                "Llovizna helada densa",
+            ],
+            Self::SlightRain => [
+                "Slight rain",
+                "Leichter Regen",
+                "Pluie faible",
+                "Lluvia ligera",
+            ],
+            Self::ModerateRain => [
+                "Moderate rain",
+                "Mäßiger Regen",
+                "Pluie modérée",
+                "Lluvia moderada",
+            ],
+            Self::HeavyRain => [
+                "Heavy rain",
+                "Starker Regen",
+                "Pluie forte",
+                "Lluvia intensa",
+            ],
+            Self::LightFreezingRain => [
+                "Light freezing rain",
+                "Leichter gefrierender Regen",
+                "Pluie verglaçante faible",
+                "Lluvia helada ligera",
+            ],
+            Self::HeavyFreezingRain => [
+                "Heavy freezing rain",
+                "Starker gefrierender Regen",
+                "Pluie verglaçante forte",
+                "Lluvia helada intensa",
+            ],
+            Self::SlightSnowfall => [
+                "Slight snowfall",
+                "Leichter Schneefall",
+                "Chute de neige faible",
+                "Nevada ligera",
+            ],
+            Self::ModerateSnowfall => [
+                "Moderate snowfall",
+                "Mäßiger Schneefall",
+                "Chute de neige modérée",
+                "Nevada moderada",
+            ],
+            Self::HeavySnowfall => [
+                "Heavy snowfall",
+                "Starker Schneefall",
+                "Chute de neige forte",
+                "Nevada intensa",
+            ],
+            Self::SnowGrains => ["Snow grains", "Schneegriesel", "Neige en grains", "Cinarra"],
+            Self::SlightRainShowers => [
+                "Slight rain showers",
+                "Leichte Regenschauer",
+                "Averses de pluie faibles",
+                "Chubascos ligeros",
+            ],
+            Self::ModerateRainShowers => [
+                "Moderate rain showers",
+                "Mäßige Regenschauer",
+                "Averses de pluie modérées",
+                "Chubascos moderados",
+            ],
+            Self::ViolentRainShowers => [
+                "Violent rain showers",
+                "Heftige Regenschauer",
+                "Averses de pluie violentes",
+                "Chubascos violentos",
+            ],
+            Self::SlightSnowShowers => [
+                "Slight snow showers",
+                "Leichte Schneeschauer",
+                "Averses de neige faibles",
+                "Chubascos de nieve ligeros",
+            ],
+            Self::HeavySnowShowers => [
+                "Heavy snow showers",
+                "Starke Schneeschauer",
+                "Averses de neige fortes",
+                "Chubascos de nieve intensos",
+            ],
+            Self::Thunderstorm => ["Thunderstorm", "Gewitter", "Orage", "Tormenta"],
+            Self::ThunderstormWithSlightHail => [
+                "Thunderstorm with slight hail",
+                "Gewitter mit leichtem Hagel",
+                "Orage avec grêle faible",
+                "Tormenta con granizo ligero",
+            ],
+            Self::ThunderstormWithHeavyHail => [
+                "Thunderstorm with heavy hail",
+                "Gewitter mit starkem Hagel",
+                "Orage avec forte grêle",
+                "Tormenta con granizo intenso",
+            ],
+        }
+    }
+
+    /// Unicode emoji for the code, by day or by night
+    pub fn icon(self, is_day: bool) -> &'static str {
+        match self {
+            Self::Clear if is_day => "☀️",
+            Self::Clear => "🌙",
+            Self::MainlyClear if is_day => "🌤️",
+            Self::MainlyClear => "🌙",
+            Self::PartlyCloudy if is_day => "⛅",
+            Self::PartlyCloudy => "☁️",
+            Self::Overcast => "☁️",
+            Self::Fog | Self::DepositingRimeFog => "🌫️",
+            Self::LightDrizzle
+            | Self::ModerateDrizzle
+            | Self::DenseDrizzle
+            | Self::SlightRain
+            | Self::ModerateRain
+            | Self::HeavyRain => "🌧️",
+            Self::LightFreezingDrizzle
+            | Self::DenseFreezingDrizzle
+            | Self::LightFreezingRain
+            | Self::HeavyFreezingRain => "🧊",
+            Self::SlightSnowfall
+            | Self::ModerateSnowfall
+            | Self::HeavySnowfall
+            | Self::SnowGrains
+            | Self::SlightSnowShowers
+            | Self::HeavySnowShowers => "🌨️",
+            Self::SlightRainShowers | Self::ModerateRainShowers | Self::ViolentRainShowers
+                if is_day =>
+            {
+                "🌦️"
+            }
+            Self::SlightRainShowers | Self::ModerateRainShowers | Self::ViolentRainShowers => "🌧️",
+            Self::Thunderstorm => "⛈️",
+            Self::ThunderstormWithSlightHail | Self::ThunderstormWithHeavyHail => "🌩️",
+        }
+    }
+
+    /// Nerd Font glyph for the code, by day or by night
+    pub fn nerd_icon(self, is_day: bool) -> &'static str {
+        let (day, night) = match self {
+            Self::Clear => (nerd::DAY_SUNNY, nerd::NIGHT_CLEAR),
+            Self::MainlyClear => (nerd::DAY_SUNNY_OVERCAST, nerd::NIGHT_PARTLY_CLOUDY),
+            Self::PartlyCloudy => (nerd::DAY_CLOUDY, nerd::NIGHT_CLOUDY),
+            Self::Overcast => (nerd::CLOUDY, nerd::CLOUDY),
+            Self::Fog | Self::DepositingRimeFog => (nerd::DAY_FOG, nerd::NIGHT_FOG),
+            Self::LightDrizzle | Self::ModerateDrizzle | Self::DenseDrizzle => {
+                (nerd::DAY_SPRINKLE, nerd::NIGHT_SPRINKLE)
+            }
+            Self::SlightRain | Self::ModerateRain | Self::HeavyRain => {
+                (nerd::DAY_RAIN, nerd::NIGHT_RAIN)
+            }
+            Self::LightFreezingDrizzle
+            | Self::DenseFreezingDrizzle
+            | Self::LightFreezingRain
+            | Self::HeavyFreezingRain => (nerd::DAY_SLEET, nerd::NIGHT_SLEET),
+            Self::SlightSnowfall
+            | Self::ModerateSnowfall
+            | Self::HeavySnowfall
+            | Self::SnowGrains
+            | Self::SlightSnowShowers
+            | Self::HeavySnowShowers => (nerd::DAY_SNOW, nerd::NIGHT_SNOW),
+            Self::SlightRainShowers | Self::ModerateRainShowers | Self::ViolentRainShowers => {
+                (nerd::DAY_SHOWERS, nerd::NIGHT_SHOWERS)
+            }
+            Self::Thunderstorm => (nerd::DAY_THUNDERSTORM, nerd::NIGHT_THUNDERSTORM),
+            Self::ThunderstormWithSlightHail | Self::ThunderstormWithHeavyHail => {
+                (nerd::DAY_HAIL, nerd::NIGHT_HAIL)
+            }
+        };
+        if is_day { day } else { night }
+    }
+}
+
+impl PartialOrd for WeatherCode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Orders codes by [`WeatherCode::severity`], not by their numeric value
+impl Ord for WeatherCode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.severity().cmp(&other.severity())
+    }
+}
+
+impl fmt::Display for WeatherCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+impl TryFrom<u8> for WeatherCode {
+    type Error = String;
+
+    fn try_from(code: u8) -> Result<Self, Self::Error> {
+        Self::from_code(code).ok_or_else(|| format!("unknown WMO weather code {}", code))
+    }
+}
+
+impl From<WeatherCode> for u8 {
+    fn from(code: WeatherCode) -> Self {
+        code.code()
+    }
+}
+
+/// Convert a `weather_code` series into typed codes
+///
+/// Missing or unknown values become `None`.
+pub(crate) fn from_series(values: &[Option<f64>]) -> Vec<Option<WeatherCode>> {
+    values
+        .iter()
+        .map(|v| v.and_then(WeatherCode::from_value))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weather_code_lookup() {
+        for code in WeatherCode::ALL {
+            assert_eq!(WeatherCode::from_code(code.code()), Some(code));
+        }
+        assert_eq!(
+            WeatherCode::from_value(63.0),
+            Some(WeatherCode::ModerateRain)
+        );
+        assert_eq!(WeatherCode::from_value(63.5), None);
+        assert_eq!(WeatherCode::from_value(-1.0), None);
+        assert_eq!(WeatherCode::from_code(4), None);
+    }
+
+    #[test]
+    fn test_weather_code_severity_ordering() {
+        let mut severities: Vec<u8> = WeatherCode::ALL.iter().map(|c| c.severity()).collect();
+        severities.sort();
+        assert_eq!(severities, (0..28).collect::<Vec<u8>>());
+
+        assert!(WeatherCode::HeavyFreezingRain > WeatherCode::HeavySnowfall);
+        assert!(WeatherCode::SnowGrains < WeatherCode::HeavySnowfall);
+        assert!(WeatherCode::Thunderstorm > WeatherCode::ViolentRainShowers);
+        assert_eq!(
+            [
+                WeatherCode::Fog,
+                WeatherCode::ThunderstormWithHeavyHail,
+                WeatherCode::Clear
+            ]
+            .into_iter()
+            .max(),
+            Some(WeatherCode::ThunderstormWithHeavyHail)
+        );
+    }
+
+    #[test]
+    fn test_weather_code_descriptions() {
+        assert_eq!(WeatherCode::ModerateRain.description(), "Moderate rain");
+        assert_eq!(WeatherCode::ModerateRain.to_string(), "Moderate rain");
+        assert_eq!(
+            WeatherCode::ModerateRain.description_in("de"),
+            "Mäßiger Regen"
+        );
+        assert_eq!(WeatherCode::Fog.description_in("fr-CH"), "Brouillard");
+        assert_eq!(WeatherCode::Overcast.description_in("ES"), "Cubierto");
+        assert_eq!(WeatherCode::Overcast.description_in("ja"), "Overcast");
+    }
+
+    #[test]
+    fn test_weather_code_icons() {
+        assert_eq!(WeatherCode::Clear.icon(true), "☀️");
+        assert_eq!(WeatherCode::Clear.icon(false), "🌙");
+        assert_eq!(WeatherCode::Clear.nerd_icon(true), "\u{e30d}");
+        assert_eq!(WeatherCode::Clear.nerd_icon(false), "\u{e32b}");
+        assert_eq!(
+            WeatherCode::Overcast.nerd_icon(true),
+            WeatherCode::Overcast.nerd_icon(false)
+        );
+    }
+
+    #[test]
+    fn test_weather_code_serde() {
+        let code: WeatherCode = serde_json::from_str("95").unwrap();
+        assert_eq!(code, WeatherCode::Thunderstorm);
+        assert_eq!(serde_json::to_string(&code).unwrap(), "95");
+        assert!(serde_json::from_str::<WeatherCode>("42").is_err());
+    }
+}
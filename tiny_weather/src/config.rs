@@ -0,0 +1,404 @@
+//! Persistent settings of the command-line tool
+//!
+//! Settings are read from `$XDG_CONFIG_HOME/tinyverse/tiny_weather.toml`
+//! (`~/.config/...` without `XDG_CONFIG_HOME`, or the file named by
+//! `TINY_WEATHER_CONFIG`), then overridden by environment variables named after
+//! each key (`units` → `TINY_WEATHER_UNITS`, `cache.forecast_ttl` →
+//! `TINY_WEATHER_CACHE_FORECAST_TTL`). Command-line arguments take precedence
+//! over both.
+//!
+//! ```toml
+//! location = "Berlin"
+//! units = "metric"
+//! language = "de"
+//! theme = "emoji"
+//! snap_warning_km = 25
+//!
+//! [variables]
+//! hourly = ["temperature_2m", "precipitation"]
+//!
+//! [cache]
+//! forecast_ttl = 900
+//! ```
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::client::error::{OpenMeteoError, Result};
+use crate::client::types::UnitSystem;
+use crate::output::Theme;
+
+/// Environment variable naming the config file, overriding the default path
+pub const CONFIG_PATH_VAR: &str = "TINY_WEATHER_CONFIG";
+
+/// Prefix of the environment variables overriding settings
+pub const ENV_PREFIX: &str = "TINY_WEATHER_";
+
+/// Default time a forecast is reused for the same request
+pub const DEFAULT_FORECAST_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// Default time a geocoded place name is reused
+pub const DEFAULT_GEOCODING_TTL: Duration = Duration::from_secs(30 * 24 * 3600);
+
+/// Every settable key, in the order of `config get`
+pub const KEYS: [&str; 11] = [
+    "location",
+    "units",
+    "language",
+    "api_key",
+    "theme",
+    "snap_warning_km",
+    "variables.current",
+    "variables.hourly",
+    "variables.daily",
+    "cache.forecast_ttl",
+    "cache.geocoding_ttl",
+];
+
+/// Keys whose values are never printed in full
+pub const SECRET_KEYS: [&str; 1] = ["api_key"];
+
+/// Settings for the `tiny_weather` command; every field is optional
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct TinyWeatherConfig {
+    /// Place used when a command is given none
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub units: Option<UnitSystem>,
+    /// Language of place names and weather descriptions (ISO 639-1)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Key of a commercial Open-Meteo subscription
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theme: Option<Theme>,
+    /// Distance (km) from the requested place to the model grid point above
+    /// which a warning is logged; see [`ClientConfig::snap_warning`]
+    ///
+    /// [`ClientConfig::snap_warning`]: crate::client::ClientConfig::snap_warning
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snap_warning_km: Option<f64>,
+    #[serde(skip_serializing_if = "VariableDefaults::is_empty")]
+    pub variables: VariableDefaults,
+    #[serde(skip_serializing_if = "CacheConfig::is_empty")]
+    pub cache: CacheConfig,
+}
+
+/// Variables fetched by each command when none are given
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct VariableDefaults {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hourly: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub daily: Option<Vec<String>>,
+}
+
+impl VariableDefaults {
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// How long cached responses are reused, in seconds (0 disables the cache)
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forecast_ttl: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub geocoding_ttl: Option<u64>,
+}
+
+impl CacheConfig {
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Time a forecast is reused for the same request
+    pub fn forecast_ttl(&self) -> Duration {
+        self.forecast_ttl
+            .map_or(DEFAULT_FORECAST_TTL, Duration::from_secs)
+    }
+
+    /// Time a geocoded place name is reused
+    pub fn geocoding_ttl(&self) -> Duration {
+        self.geocoding_ttl
+            .map_or(DEFAULT_GEOCODING_TTL, Duration::from_secs)
+    }
+}
+
+impl TinyWeatherConfig {
+    /// Path of the config file
+    pub fn path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os(CONFIG_PATH_VAR) {
+            return Some(PathBuf::from(path));
+        }
+        let config = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config.join("tinyverse").join("tiny_weather.toml"))
+    }
+
+    /// Settings from the config file and the environment
+    pub fn load() -> Result<Self> {
+        let mut config = match Self::path() {
+            Some(path) => Self::read(&path)?,
+            None => Self::default(),
+        };
+        config.apply_env(|name| std::env::var(name).ok())?;
+        Ok(config)
+    }
+
+    /// Settings from a config file, or the defaults if it does not exist
+    pub fn read(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => toml::from_str(&text).map_err(|e| {
+                OpenMeteoError::InvalidParameter(format!("{}: {}", path.display(), e))
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Write the settings to a config file, creating its directory
+    pub fn write(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let text = toml::to_string_pretty(self)
+            .map_err(|e| OpenMeteoError::InvalidParameter(e.to_string()))?;
+
+        // The file may hold the API key, so only the owner may read it
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+
+            options.mode(0o600);
+        }
+        let mut file = options.open(path)?;
+        // The mode only applies to new files; tighten files written before
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+        }
+        file.write_all(text.as_bytes())?;
+        Ok(())
+    }
+
+    /// Override settings from environment variables, looked up with `var`
+    pub fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<()> {
+        for key in KEYS {
+            if let Some(value) = var(&env_var(key)) {
+                self.set(key, Some(&value))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Value of a setting as text, `None` if unset
+    ///
+    /// Lists are comma-separated.
+    pub fn get(&self, key: &str) -> Result<Option<String>> {
+        let list = |values: &Option<Vec<String>>| values.as_ref().map(|v| v.join(","));
+        Ok(match key {
+            "location" => self.location.clone(),
+            "units" => self.units.map(|units| units.to_string()),
+            "language" => self.language.clone(),
+            "api_key" => self.api_key.clone(),
+            "theme" => self.theme.map(|theme| theme.to_string()),
+            "snap_warning_km" => self.snap_warning_km.map(|km| km.to_string()),
+            "variables.current" => list(&self.variables.current),
+            "variables.hourly" => list(&self.variables.hourly),
+            "variables.daily" => list(&self.variables.daily),
+            "cache.forecast_ttl" => self.cache.forecast_ttl.map(|ttl| ttl.to_string()),
+            "cache.geocoding_ttl" => self.cache.geocoding_ttl.map(|ttl| ttl.to_string()),
+            _ => return Err(unknown_key(key)),
+        })
+    }
+
+    /// Set a setting from text, or unset it with `None` or an empty value
+    pub fn set(&mut self, key: &str, value: Option<&str>) -> Result<()> {
+        let value = value.map(str::trim).filter(|value| !value.is_empty());
+        let invalid = |e: String| OpenMeteoError::InvalidParameter(format!("{}: {}", key, e));
+        let text = || value.map(str::to_string);
+        let list = || {
+            value.map(|value| {
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|v| !v.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+        };
+        let seconds = || {
+            value
+                .map(|value| value.parse::<u64>().map_err(|e| invalid(e.to_string())))
+                .transpose()
+        };
+
+        match key {
+            "location" => self.location = text(),
+            "units" => self.units = value.map(str::parse).transpose().map_err(invalid)?,
+            "language" => self.language = text(),
+            "api_key" => self.api_key = text(),
+            "theme" => self.theme = value.map(str::parse).transpose().map_err(invalid)?,
+            "snap_warning_km" => {
+                self.snap_warning_km = value
+                    .map(|value| match value.parse::<f64>() {
+                        Ok(km) if km >= 0.0 => Ok(km),
+                        _ => Err(invalid(format!(
+                            "expected a distance in km, got '{}'",
+                            value
+                        ))),
+                    })
+                    .transpose()?
+            }
+            "variables.current" => self.variables.current = list(),
+            "variables.hourly" => self.variables.hourly = list(),
+            "variables.daily" => self.variables.daily = list(),
+            "cache.forecast_ttl" => self.cache.forecast_ttl = seconds()?,
+            "cache.geocoding_ttl" => self.cache.geocoding_ttl = seconds()?,
+            _ => return Err(unknown_key(key)),
+        }
+        Ok(())
+    }
+}
+
+/// Environment variable overriding a key
+pub fn env_var(key: &str) -> String {
+    format!(
+        "{}{}",
+        ENV_PREFIX,
+        key.replace('.', "_").to_ascii_uppercase()
+    )
+}
+
+fn unknown_key(key: &str) -> OpenMeteoError {
+    OpenMeteoError::InvalidParameter(format!(
+        "unknown config key '{}' (expected one of: {})",
+        key,
+        KEYS.join(", ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_file_roundtrip() {
+        let config: TinyWeatherConfig = toml::from_str(
+            r#"
+            location = "Berlin"
+            units = "imperial"
+            theme = "nerd"
+
+            [variables]
+            hourly = ["temperature_2m", "precipitation"]
+
+            [cache]
+            forecast_ttl = 60
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.location.as_deref(), Some("Berlin"));
+        assert_eq!(config.units, Some(UnitSystem::Imperial));
+        assert_eq!(config.theme, Some(Theme::Nerd));
+        assert_eq!(config.cache.forecast_ttl(), Duration::from_secs(60));
+        assert_eq!(config.cache.geocoding_ttl(), DEFAULT_GEOCODING_TTL);
+
+        let path = std::env::temp_dir()
+            .join(format!("tiny_weather_config_{}", std::process::id()))
+            .join("tiny_weather.toml");
+        config.write(&path).unwrap();
+        assert_eq!(TinyWeatherConfig::read(&path).unwrap(), config);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+            config.write(&path).unwrap();
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        // A missing file is an empty config, unknown keys are errors
+        assert_eq!(
+            TinyWeatherConfig::read(&path).unwrap(),
+            TinyWeatherConfig::default()
+        );
+        assert!(toml::from_str::<TinyWeatherConfig>("colour = \"red\"").is_err());
+    }
+
+    #[test]
+    fn test_config_get_set() {
+        let mut config = TinyWeatherConfig::default();
+        config.set("units", Some("Aviation")).unwrap();
+        config
+            .set("variables.daily", Some("temperature_2m_max, sunrise"))
+            .unwrap();
+        config.set("cache.geocoding_ttl", Some("0")).unwrap();
+        config.set("snap_warning_km", Some("2.5")).unwrap();
+
+        assert_eq!(config.get("units").unwrap().as_deref(), Some("aviation"));
+        assert_eq!(config.snap_warning_km, Some(2.5));
+        assert_eq!(
+            config.variables.daily,
+            Some(vec![
+                "temperature_2m_max".to_string(),
+                "sunrise".to_string()
+            ])
+        );
+        assert_eq!(config.cache.geocoding_ttl(), Duration::ZERO);
+        assert_eq!(config.get("location").unwrap(), None);
+
+        config.set("units", None).unwrap();
+        assert_eq!(config.units, None);
+        assert!(config.set("units", Some("furlongs")).is_err());
+        assert!(config.set("cache.forecast_ttl", Some("-1")).is_err());
+        assert!(config.set("snap_warning_km", Some("-1")).is_err());
+        assert!(config.get("colour").is_err());
+        for key in KEYS {
+            assert!(config.get(key).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_config_env_overrides() {
+        let mut config = TinyWeatherConfig {
+            location: Some("Berlin".to_string()),
+            language: Some("de".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            env_var("cache.forecast_ttl"),
+            "TINY_WEATHER_CACHE_FORECAST_TTL"
+        );
+
+        config
+            .apply_env(|name| match name {
+                "TINY_WEATHER_LOCATION" => Some("Paris".to_string()),
+                "TINY_WEATHER_CACHE_FORECAST_TTL" => Some("5".to_string()),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(config.location.as_deref(), Some("Paris"));
+        assert_eq!(config.language.as_deref(), Some("de"));
+        assert_eq!(config.cache.forecast_ttl, Some(5));
+    }
+}
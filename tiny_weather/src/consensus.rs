@@ -0,0 +1,299 @@
+//! Consensus forecast blended from several weather models
+//!
+//! A multi-model response (see [`WeatherModel`]) gives one series per model,
+//! which rarely agree. [`Consensus`] combines them into a single hourly series
+//! per variable, with the spread between models as its uncertainty:
+//!
+//! - `<variable>`: weighted mean of the models
+//! - `<variable>_median`: median of the models
+//! - `<variable>_spread`: weighted standard deviation of the models
+//! - `<variable>_models`: number of models that contributed
+//!
+//! Models only contribute within their forecast horizon
+//! ([`WeatherModel::forecast_hours`]), so a regional model that ends after two
+//! days does not pull the blend towards its last values, and missing values
+//! never count as zero.
+//!
+//! ```no_run
+//! # async fn run() -> tiny_weather::client::Result<()> {
+//! use tiny_weather::client::ForecastClient;
+//! use tiny_weather::client::types::{ForecastRequest, WeatherModel};
+//! use tiny_weather::consensus::Consensus;
+//!
+//! let request = ForecastRequest::new(52.52, 13.41)
+//!     .hourly(&["temperature_2m"])
+//!     .models(&[WeatherModel::EcmwfIfs025, WeatherModel::GfsGlobal])
+//!     .model(WeatherModel::IconD2);
+//! let forecast = ForecastClient::new().get(request).await?;
+//!
+//! let blend = Consensus::new()
+//!     .weight(WeatherModel::EcmwfIfs025, 2.0)
+//!     .hourly(&forecast, &["temperature_2m"])?;
+//! let mean = &blend.variables["temperature_2m"];
+//! let spread = &blend.variables["temperature_2m_spread"];
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::client::error::{OpenMeteoError, Result};
+use crate::client::types::forecast::{ForecastResponse, HourlyData};
+use crate::client::types::model::WeatherModel;
+use crate::frame::parse_time;
+
+/// Suffix of the median column
+pub const MEDIAN_SUFFIX: &str = "_median";
+/// Suffix of the spread (uncertainty) column
+pub const SPREAD_SUFFIX: &str = "_spread";
+/// Suffix of the contributing model count column
+pub const MODELS_SUFFIX: &str = "_models";
+
+/// Settings for blending the models of a multi-model response
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Consensus {
+    /// Weight per model; empty for equal weights
+    pub weights: HashMap<WeatherModel, f64>,
+    /// Time horizons are measured from; the first hourly time if `None`
+    pub start: Option<DateTime<Utc>>,
+    /// Fewest models needed for a value, below which the blend is `None`
+    pub min_models: usize,
+}
+
+impl Consensus {
+    /// Create an equal-weight consensus
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Weight a model, e.g., by its verification skill
+    ///
+    /// Once any weight is set, models without a weight are left out of the blend.
+    pub fn weight(mut self, model: WeatherModel, weight: f64) -> Self {
+        self.weights.insert(model, weight);
+        self
+    }
+
+    /// Measure model horizons from this time (e.g., the model run time when
+    /// `past_days` is requested)
+    pub fn start(mut self, start: DateTime<Utc>) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    /// Require at least this many models per value
+    pub fn min_models(mut self, min_models: usize) -> Self {
+        self.min_models = min_models;
+        self
+    }
+
+    fn weight_of(&self, model: WeatherModel) -> f64 {
+        if self.weights.is_empty() {
+            1.0
+        } else {
+            self.weights.get(&model).copied().unwrap_or(0.0)
+        }
+    }
+
+    /// Blend hourly variables of a multi-model response into one series each
+    pub fn hourly(&self, forecast: &ForecastResponse, variables: &[&str]) -> Result<HourlyData> {
+        if self
+            .weights
+            .values()
+            .any(|weight| !weight.is_finite() || *weight < 0.0)
+        {
+            return Err(OpenMeteoError::InvalidParameter(
+                "model weights must be finite and non-negative".to_string(),
+            ));
+        }
+        let hourly = forecast.hourly.as_ref().ok_or_else(|| {
+            OpenMeteoError::InvalidParameter("consensus requires hourly data".to_string())
+        })?;
+
+        let times = hourly
+            .time
+            .iter()
+            .map(|time| parse_time(time, forecast.utc_offset_seconds))
+            .collect::<Result<Vec<_>>>()?;
+        let start = self.start.or_else(|| times.first().copied());
+
+        let mut blended = HashMap::new();
+        for variable in variables {
+            let series = forecast.hourly_by_model(variable);
+            if series.is_empty() {
+                return Err(OpenMeteoError::InvalidParameter(format!(
+                    "no model returned the hourly variable {}",
+                    variable
+                )));
+            }
+            let columns = self.blend(&series, &times, start);
+            let [mean, median, spread, count] = columns;
+            blended.insert(variable.to_string(), mean);
+            blended.insert(format!("{}{}", variable, MEDIAN_SUFFIX), median);
+            blended.insert(format!("{}{}", variable, SPREAD_SUFFIX), spread);
+            blended.insert(format!("{}{}", variable, MODELS_SUFFIX), count);
+        }
+
+        Ok(HourlyData {
+            time: hourly.time.clone(),
+            variables: blended,
+        })
+    }
+
+    /// Mean, median, spread and model count columns of one variable
+    fn blend(
+        &self,
+        series: &BTreeMap<WeatherModel, &[Option<f64>]>,
+        times: &[DateTime<Utc>],
+        start: Option<DateTime<Utc>>,
+    ) -> [Vec<Option<f64>>; 4] {
+        let mut columns: [Vec<Option<f64>>; 4] = Default::default();
+        for (i, time) in times.iter().enumerate() {
+            let members: Vec<(f64, f64)> = series
+                .iter()
+                .filter(|(model, _)| within_horizon(**model, *time, start))
+                .filter_map(|(model, values)| {
+                    let value = values.get(i).copied().flatten()?;
+                    let weight = self.weight_of(*model);
+                    (weight > 0.0).then_some((value, weight))
+                })
+                .collect();
+
+            let blend = (!members.is_empty() && members.len() >= self.min_models)
+                .then(|| summarize(&members));
+            let [mean, median, spread, count] = &mut columns;
+            mean.push(blend.map(|b| b.0));
+            median.push(blend.map(|b| b.1));
+            spread.push(blend.map(|b| b.2));
+            count.push(Some(members.len() as f64));
+        }
+        columns
+    }
+}
+
+fn within_horizon(model: WeatherModel, time: DateTime<Utc>, start: Option<DateTime<Utc>>) -> bool {
+    match (model.forecast_hours(), start) {
+        (Some(hours), Some(start)) => time - start < Duration::hours(hours.into()),
+        _ => true,
+    }
+}
+
+/// Weighted mean, median and weighted standard deviation of `(value, weight)` pairs
+fn summarize(members: &[(f64, f64)]) -> (f64, f64, f64) {
+    let total: f64 = members.iter().map(|(_, weight)| weight).sum();
+    let mean = members
+        .iter()
+        .map(|(value, weight)| value * weight)
+        .sum::<f64>()
+        / total;
+    let variance = members
+        .iter()
+        .map(|(value, weight)| weight * (value - mean).powi(2))
+        .sum::<f64>()
+        / total;
+
+    let mut values: Vec<f64> = members.iter().map(|(value, _)| *value).collect();
+    values.sort_by(f64::total_cmp);
+    let middle = values.len() / 2;
+    let median = if values.len().is_multiple_of(2) {
+        (values[middle - 1] + values[middle]) / 2.0
+    } else {
+        values[middle]
+    };
+
+    (mean, median, variance.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::types::forecast::fixtures;
+
+    fn forecast() -> ForecastResponse {
+        fixtures::forecast(serde_json::json!({
+            "hourly": {
+                "time": ["2024-01-03T00:00", "2024-01-03T01:00", "2024-01-03T02:00"],
+                "temperature_2m_ecmwf_ifs025": [1.0, 2.0, 3.0],
+                "temperature_2m_gfs_global": [3.0, null, 5.0],
+                "temperature_2m_icon_d2": [5.0, 6.0, 7.0]
+            }
+        }))
+    }
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.unwrap();
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_equal_weights() {
+        let blend = Consensus::new()
+            .hourly(&forecast(), &["temperature_2m"])
+            .unwrap();
+        let mean = &blend.variables["temperature_2m"];
+        assert_close(mean[0], 3.0);
+        // A missing value is left out, not counted as zero
+        assert_close(mean[1], 4.0);
+        assert_close(blend.variables["temperature_2m_median"][1], 4.0);
+        assert_close(
+            blend.variables["temperature_2m_spread"][0],
+            (8.0f64 / 3.0).sqrt(),
+        );
+        assert_eq!(
+            blend.variables["temperature_2m_models"],
+            vec![Some(3.0), Some(2.0), Some(3.0)]
+        );
+        assert_eq!(blend.time.len(), 3);
+    }
+
+    #[test]
+    fn test_skill_weights_and_min_models() {
+        let blend = Consensus::new()
+            .weight(WeatherModel::EcmwfIfs025, 3.0)
+            .weight(WeatherModel::GfsGlobal, 1.0)
+            .min_models(2)
+            .hourly(&forecast(), &["temperature_2m"])
+            .unwrap();
+
+        // ICON-D2 has no weight, so it is left out
+        let mean = &blend.variables["temperature_2m"];
+        assert_close(mean[0], 1.5);
+        assert_eq!(mean[1], None);
+        assert_close(blend.variables["temperature_2m_spread"][2], 0.75f64.sqrt());
+    }
+
+    #[test]
+    fn test_regional_model_horizon() {
+        // ICON-D2 runs for 48 hours, which ends before the last time step
+        let start = parse_time("2024-01-01T02:00", 0).unwrap();
+        let blend = Consensus::new()
+            .start(start)
+            .hourly(&forecast(), &["temperature_2m"])
+            .unwrap();
+
+        assert_eq!(
+            blend.variables["temperature_2m_models"],
+            vec![Some(3.0), Some(2.0), Some(2.0)]
+        );
+        assert_close(blend.variables["temperature_2m"][2], 4.0);
+    }
+
+    #[test]
+    fn test_invalid_consensus() {
+        let forecast = forecast();
+        assert!(
+            Consensus::new()
+                .hourly(&forecast, &["precipitation"])
+                .is_err()
+        );
+        let negative = Consensus::new().weight(WeatherModel::GfsGlobal, -1.0);
+        assert!(negative.hourly(&forecast, &["temperature_2m"]).is_err());
+    }
+}
@@ -0,0 +1,542 @@
+//! Derived meteorological metrics computed from forecast series
+//!
+//! Not every weather model returns every variable, but most of the common
+//! comfort and safety indices can be derived from temperature, relative
+//! humidity and wind speed. Each formula is only applied inside the range it
+//! was fitted for; outside of it (or when an input is missing) the result is
+//! `None` rather than a misleading number.
+//!
+//! The wet-bulb globe temperature is the simplified shade estimate from
+//! temperature and humidity only. The full Liljegren model also needs solar
+//! radiation, wind and pressure and is not implemented; in direct sun the real
+//! WBGT can be several degrees higher.
+//!
+//! The functions take metric inputs (°C, %, km/h). [`ForecastResponse::add_derived`]
+//! converts the response's inputs first and adds each metric as a new hourly column:
+//!
+//! ```no_run
+//! # async fn run() -> tiny_weather::client::Result<()> {
+//! use tiny_weather::client::ForecastClient;
+//! use tiny_weather::client::types::ForecastRequest;
+//! use tiny_weather::derived::DerivedMetric;
+//!
+//! let request = ForecastRequest::new(33.45, -112.07)
+//!     .hourly(&["temperature_2m", "relative_humidity_2m", "wind_speed_10m"]);
+//! let mut forecast = ForecastClient::new().get(request).await?;
+//!
+//! forecast.add_derived(&[DerivedMetric::HeatIndex, DerivedMetric::WetBulb])?;
+//! let heat_index = &forecast.hourly.as_ref().unwrap().variables["heat_index"];
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+
+use crate::client::error::{OpenMeteoError, Result};
+use crate::client::types::forecast::{ForecastResponse, HourlyData};
+use crate::client::types::units::Unit;
+
+/// Temperature input variable
+pub const TEMPERATURE: &str = "temperature_2m";
+/// Relative humidity input variable
+pub const RELATIVE_HUMIDITY: &str = "relative_humidity_2m";
+/// Wind speed input variable
+pub const WIND_SPEED: &str = "wind_speed_10m";
+
+/// Magnus formula coefficients (Alduchov & Eskridge 1996, over water)
+const MAGNUS_B: f64 = 17.62;
+const MAGNUS_C: f64 = 243.12;
+
+/// Upper wind speed bounds (m/s) of Beaufort forces 0 to 11
+const BEAUFORT_LIMITS: [f64; 12] = [
+    0.5, 1.6, 3.4, 5.5, 8.0, 10.8, 13.9, 17.2, 20.8, 24.5, 28.5, 32.7,
+];
+
+/// A metric that can be derived from hourly forecast variables
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DerivedMetric {
+    HeatIndex,
+    WindChill,
+    Humidex,
+    WetBulb,
+    WetBulbGlobe,
+    DewPoint,
+    AbsoluteHumidity,
+    CloudBase,
+    Beaufort,
+}
+
+impl DerivedMetric {
+    /// Every derived metric
+    pub const ALL: [DerivedMetric; 9] = [
+        Self::HeatIndex,
+        Self::WindChill,
+        Self::Humidex,
+        Self::WetBulb,
+        Self::WetBulbGlobe,
+        Self::DewPoint,
+        Self::AbsoluteHumidity,
+        Self::CloudBase,
+        Self::Beaufort,
+    ];
+
+    /// Name of the column the metric is stored in
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::HeatIndex => "heat_index",
+            Self::WindChill => "wind_chill",
+            Self::Humidex => "humidex",
+            Self::WetBulb => "wet_bulb_temperature_2m",
+            Self::WetBulbGlobe => "wet_bulb_globe_temperature",
+            Self::DewPoint => "dew_point_2m",
+            Self::AbsoluteHumidity => "absolute_humidity_2m",
+            Self::CloudBase => "cloud_base",
+            Self::Beaufort => "beaufort",
+        }
+    }
+
+    /// Hourly variables the metric is computed from
+    pub fn inputs(self) -> &'static [&'static str] {
+        match self {
+            Self::WindChill => &[TEMPERATURE, WIND_SPEED],
+            Self::Beaufort => &[WIND_SPEED],
+            _ => &[TEMPERATURE, RELATIVE_HUMIDITY],
+        }
+    }
+
+    /// Unit of the computed values, given the unit of the temperature input
+    ///
+    /// Temperature-like metrics are reported in the same unit as `temperature_2m`.
+    fn unit(self, temperature: Unit) -> &'static str {
+        match self {
+            Self::HeatIndex
+            | Self::WindChill
+            | Self::WetBulb
+            | Self::WetBulbGlobe
+            | Self::DewPoint => temperature.symbol(),
+            Self::Humidex => "",
+            Self::AbsoluteHumidity => "g/m³",
+            Self::CloudBase => "m",
+            Self::Beaufort => "Bft",
+        }
+    }
+
+    /// Compute the metric for one time step from metric inputs
+    fn apply(
+        self,
+        temperature: Option<f64>,
+        humidity: Option<f64>,
+        wind: Option<f64>,
+    ) -> Option<f64> {
+        match self {
+            Self::HeatIndex => heat_index(temperature?, humidity?),
+            Self::WindChill => wind_chill(temperature?, wind?),
+            Self::Humidex => humidex(temperature?, humidity?),
+            Self::WetBulb => wet_bulb(temperature?, humidity?),
+            Self::WetBulbGlobe => wet_bulb_globe(temperature?, humidity?),
+            Self::DewPoint => dew_point(temperature?, humidity?),
+            Self::AbsoluteHumidity => absolute_humidity(temperature?, humidity?),
+            Self::CloudBase => cloud_base(temperature?, humidity?),
+            Self::Beaufort => beaufort(wind?).map(f64::from),
+        }
+    }
+}
+
+/// Heat index (°C), using the NWS Rothfusz regression with its adjustments
+///
+/// Valid for 26.7–50 °C (80–122 °F), where the regression was fitted.
+pub fn heat_index(temperature: f64, humidity: f64) -> Option<f64> {
+    if !(26.7..=50.0).contains(&temperature) || !valid_humidity(humidity) {
+        return None;
+    }
+
+    let t = temperature * 9.0 / 5.0 + 32.0;
+    let rh = humidity;
+    let mut hi = -42.379 + 2.04901523 * t + 10.14333127 * rh
+        - 0.22475541 * t * rh
+        - 0.00683783 * t * t
+        - 0.05481717 * rh * rh
+        + 0.00122874 * t * t * rh
+        + 0.00085282 * t * rh * rh
+        - 0.00000199 * t * t * rh * rh;
+
+    if rh < 13.0 && t <= 112.0 {
+        hi -= (13.0 - rh) / 4.0 * ((17.0 - (t - 95.0).abs()) / 17.0).sqrt();
+    } else if rh > 85.0 && t <= 87.0 {
+        hi += (rh - 85.0) / 10.0 * ((87.0 - t) / 5.0);
+    }
+
+    Some((hi - 32.0) * 5.0 / 9.0)
+}
+
+/// Wind chill (°C), using the 2001 JAG/TI formula (NWS and Environment Canada)
+///
+/// Valid for temperatures at or below 10 °C and wind speeds of at least 4.8 km/h.
+pub fn wind_chill(temperature: f64, wind_speed: f64) -> Option<f64> {
+    if temperature > 10.0 || wind_speed < 4.8 {
+        return None;
+    }
+
+    let v = wind_speed.powf(0.16);
+    Some(13.12 + 0.6215 * temperature - 11.37 * v + 0.3965 * temperature * v)
+}
+
+/// Humidex (dimensionless, Environment Canada)
+///
+/// Only defined for temperatures of at least 20 °C, where humidity adds to discomfort.
+pub fn humidex(temperature: f64, humidity: f64) -> Option<f64> {
+    if temperature < 20.0 {
+        return None;
+    }
+
+    let dew_point = dew_point(temperature, humidity)?;
+    let vapour_pressure = 6.11 * (5417.7530 * (1.0 / 273.16 - 1.0 / (273.15 + dew_point))).exp();
+    Some(temperature + 0.5555 * (vapour_pressure - 10.0))
+}
+
+/// Wet-bulb temperature (°C) at sea-level pressure, using Stull (2011)
+///
+/// Valid for −20–50 °C and 5–99 % relative humidity.
+pub fn wet_bulb(temperature: f64, humidity: f64) -> Option<f64> {
+    if !(-20.0..=50.0).contains(&temperature) || !(5.0..=99.0).contains(&humidity) {
+        return None;
+    }
+
+    let (t, rh) = (temperature, humidity);
+    Some(
+        t * (0.151977 * (rh + 8.313659).sqrt()).atan() + (t + rh).atan() - (rh - 1.676331).atan()
+            + 0.00391838 * rh.powf(1.5) * (0.023101 * rh).atan()
+            - 4.686035,
+    )
+}
+
+/// Wet-bulb globe temperature in the shade (°C), using the simplified
+/// Australian Bureau of Meteorology estimate
+///
+/// `0.567 T + 0.393 e + 3.94`, where `e` is the vapour pressure in hPa. It
+/// assumes light wind and no direct sun. Only defined for temperatures of at
+/// least 20 °C, where heat stress is a concern.
+pub fn wet_bulb_globe(temperature: f64, humidity: f64) -> Option<f64> {
+    if !(20.0..=50.0).contains(&temperature) || !valid_humidity(humidity) {
+        return None;
+    }
+
+    let vapour_pressure =
+        humidity / 100.0 * 6.105 * (17.27 * temperature / (237.7 + temperature)).exp();
+    Some(0.567 * temperature + 0.393 * vapour_pressure + 3.94)
+}
+
+/// Dew point (°C), using the Magnus formula
+///
+/// Valid for −45–60 °C and relative humidity above 0 %.
+pub fn dew_point(temperature: f64, humidity: f64) -> Option<f64> {
+    if !valid_magnus(temperature) || !valid_humidity(humidity) || humidity == 0.0 {
+        return None;
+    }
+
+    let gamma = (humidity / 100.0).ln() + MAGNUS_B * temperature / (MAGNUS_C + temperature);
+    Some(MAGNUS_C * gamma / (MAGNUS_B - gamma))
+}
+
+/// Absolute humidity (g/m³)
+///
+/// Valid for −45–60 °C, the range of the Magnus saturation vapour pressure.
+pub fn absolute_humidity(temperature: f64, humidity: f64) -> Option<f64> {
+    if !valid_magnus(temperature) || !valid_humidity(humidity) {
+        return None;
+    }
+
+    let saturation = 6.112 * (17.67 * temperature / (temperature + 243.5)).exp();
+    Some(saturation * humidity * 2.1674 / (273.15 + temperature))
+}
+
+/// Estimated base of convective clouds above ground (m)
+///
+/// Uses the 125 m per °C spread between temperature and dew point; it assumes a
+/// well-mixed boundary layer and does not apply to stratiform or frontal cloud.
+pub fn cloud_base(temperature: f64, humidity: f64) -> Option<f64> {
+    let spread = temperature - dew_point(temperature, humidity)?;
+    Some(spread.max(0.0) * 125.0)
+}
+
+/// Beaufort force (0–12) for a wind speed in km/h
+pub fn beaufort(wind_speed: f64) -> Option<u8> {
+    if wind_speed.is_nan() || wind_speed < 0.0 {
+        return None;
+    }
+
+    let ms = wind_speed / 3.6;
+    Some(
+        BEAUFORT_LIMITS
+            .iter()
+            .position(|limit| ms < *limit)
+            .unwrap_or(BEAUFORT_LIMITS.len()) as u8,
+    )
+}
+
+fn valid_magnus(temperature: f64) -> bool {
+    (-45.0..=60.0).contains(&temperature)
+}
+
+fn valid_humidity(humidity: f64) -> bool {
+    (0.0..=100.0).contains(&humidity)
+}
+
+impl HourlyData {
+    /// Compute a derived metric and add it as a new column, returning its unit
+    ///
+    /// `units` is the response's `hourly_units` map, used to convert the inputs to
+    /// metric; without it inputs are assumed to be in °C, % and km/h. An existing
+    /// column of the same name (e.g., `dew_point_2m` returned by the API) is kept.
+    pub fn add_derived(
+        &mut self,
+        metric: DerivedMetric,
+        units: Option<&HashMap<String, String>>,
+    ) -> Result<&'static str> {
+        let (unit, values) = self.derive(metric, units)?;
+        if let Some(values) = values {
+            self.variables.insert(metric.name().to_string(), values);
+        }
+        Ok(unit)
+    }
+
+    /// Compute a derived metric and its unit, or only the unit if the column exists
+    fn derive(
+        &self,
+        metric: DerivedMetric,
+        units: Option<&HashMap<String, String>>,
+    ) -> Result<(&'static str, Option<Vec<Option<f64>>>)> {
+        let unit_of = |variable: &str, default: Unit| {
+            units
+                .and_then(|units| units.get(variable))
+                .and_then(|unit| Unit::parse(unit))
+                .unwrap_or(default)
+        };
+        let temperature_unit = unit_of(TEMPERATURE, Unit::Celsius);
+        let unit = metric.unit(temperature_unit);
+        if self.variables.contains_key(metric.name()) {
+            return Ok((unit, None));
+        }
+
+        let len = self.time.len();
+        // Read an input variable, converting it to metric when it has a physical unit
+        let input =
+            |variable: &str, conversion: Option<(Unit, Unit)>| -> Result<Vec<Option<f64>>> {
+                if !metric.inputs().contains(&variable) {
+                    return Ok(vec![None; len]);
+                }
+                let values = self.variables.get(variable).ok_or_else(|| {
+                    OpenMeteoError::InvalidParameter(format!(
+                        "{} requires the hourly variable {}",
+                        metric.name(),
+                        variable
+                    ))
+                })?;
+                Ok(values
+                    .iter()
+                    .map(|v| match conversion {
+                        Some((unit, target)) => v.and_then(|v| unit.convert(v, target)),
+                        None => *v,
+                    })
+                    .collect())
+            };
+        let temperature = input(TEMPERATURE, Some((temperature_unit, Unit::Celsius)))?;
+        let humidity = input(RELATIVE_HUMIDITY, None)?;
+        let wind_unit = unit_of(WIND_SPEED, Unit::KilometersPerHour);
+        let wind = input(WIND_SPEED, Some((wind_unit, Unit::KilometersPerHour)))?;
+
+        let is_temperature = unit == temperature_unit.symbol();
+        let values = (0..len)
+            .map(|i| {
+                let value = metric.apply(temperature[i], humidity[i], wind[i])?;
+                if is_temperature {
+                    Unit::Celsius.convert(value, temperature_unit)
+                } else {
+                    Some(value)
+                }
+            })
+            .collect();
+
+        Ok((unit, Some(values)))
+    }
+}
+
+impl ForecastResponse {
+    /// Add derived metrics as new hourly columns, recording their units in `hourly_units`
+    pub fn add_derived(&mut self, metrics: &[DerivedMetric]) -> Result<()> {
+        let hourly = self.hourly.as_ref().ok_or_else(|| {
+            OpenMeteoError::InvalidParameter("derived metrics require hourly data".to_string())
+        })?;
+        // Compute every column first so a missing input leaves the response unchanged
+        let columns = metrics
+            .iter()
+            .map(|metric| {
+                Ok((
+                    metric.name(),
+                    hourly.derive(*metric, self.hourly_units.as_ref())?,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let units = self.hourly_units.get_or_insert_with(HashMap::new);
+        for (name, (unit, values)) in columns {
+            if let (Some(hourly), Some(values)) = (&mut self.hourly, values) {
+                hourly.variables.entry(name.to_string()).or_insert(values);
+            }
+            units
+                .entry(name.to_string())
+                .or_insert_with(|| unit.to_string());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::types::forecast::fixtures;
+
+    fn assert_close(actual: Option<f64>, expected: f64, tolerance: f64) {
+        let actual = actual.unwrap();
+        assert!(
+            (actual - expected).abs() < tolerance,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_reference_values() {
+        // NWS heat index chart: 90 °F at 70 % is 106 °F
+        assert_close(heat_index(32.22, 70.0), 41.1, 0.1);
+        // Environment Canada wind chill chart: −10 °C at 20 km/h is −18
+        assert_close(wind_chill(-10.0, 20.0), -17.9, 0.1);
+        // Environment Canada: 30 °C with a 15 °C dew point is a humidex of 34
+        assert_close(humidex(30.0, 39.5), 34.0, 0.2);
+        // Stull (2011): 20 °C at 50 % is 13.7 °C
+        assert_close(wet_bulb(20.0, 50.0), 13.7, 0.05);
+        // Bureau of Meteorology: 30 °C at 50 % is a shade WBGT of about 29
+        assert_close(wet_bulb_globe(30.0, 50.0), 29.26, 0.01);
+        assert_close(dew_point(20.0, 50.0), 9.26, 0.01);
+        assert_close(absolute_humidity(20.0, 50.0), 8.64, 0.01);
+        assert_close(cloud_base(20.0, 50.0), 1343.0, 1.0);
+        assert_eq!(beaufort(0.0), Some(0));
+        assert_eq!(beaufort(30.0), Some(5));
+        assert_eq!(beaufort(150.0), Some(12));
+    }
+
+    #[test]
+    fn test_validity_ranges() {
+        assert_eq!(heat_index(20.0, 50.0), None);
+        assert_eq!(heat_index(30.0, 120.0), None);
+        assert_eq!(wind_chill(15.0, 20.0), None);
+        assert_eq!(wind_chill(-5.0, 3.0), None);
+        assert_eq!(humidex(15.0, 50.0), None);
+        assert_eq!(wet_bulb(20.0, 2.0), None);
+        assert_eq!(wet_bulb(55.0, 50.0), None);
+        assert_eq!(wet_bulb_globe(15.0, 50.0), None);
+        assert_eq!(dew_point(20.0, 0.0), None);
+        assert_eq!(dew_point(-50.0, 50.0), None);
+        assert_eq!(beaufort(-1.0), None);
+    }
+
+    fn forecast(temperature_unit: &str) -> ForecastResponse {
+        fixtures::forecast(serde_json::json!({
+            "latitude": 33.45,
+            "longitude": -112.07,
+            "elevation": 331.0,
+            "hourly": {
+                "time": ["2024-07-01T12:00", "2024-07-01T13:00", "2024-07-01T14:00"],
+                "temperature_2m": if temperature_unit == "°F" {
+                    [90.0, 50.0, 14.0]
+                } else {
+                    [32.22, 10.0, -10.0]
+                },
+                "relative_humidity_2m": [70.0, null, 80.0],
+                "wind_speed_10m": [5.0, 10.0, 20.0]
+            },
+            "hourly_units": {
+                "temperature_2m": temperature_unit,
+                "relative_humidity_2m": "%",
+                "wind_speed_10m": "km/h"
+            }
+        }))
+    }
+
+    #[test]
+    fn test_add_derived_columns() {
+        let mut response = forecast("°C");
+        response.add_derived(&DerivedMetric::ALL).unwrap();
+
+        let hourly = response.hourly.as_ref().unwrap();
+        let heat_index = &hourly.variables["heat_index"];
+        assert_close(heat_index[0], 41.1, 0.1);
+        assert_eq!(heat_index[1], None);
+        assert_eq!(heat_index[2], None);
+
+        let wind_chill = &hourly.variables["wind_chill"];
+        assert_eq!(wind_chill[0], None);
+        assert_close(wind_chill[2], -17.9, 0.1);
+
+        assert_eq!(
+            hourly.variables["beaufort"],
+            vec![Some(1.0), Some(2.0), Some(4.0)]
+        );
+        let units = response.hourly_units.as_ref().unwrap();
+        assert_eq!(units["heat_index"], "°C");
+        assert_eq!(units["wet_bulb_globe_temperature"], "°C");
+        assert_eq!(units["absolute_humidity_2m"], "g/m³");
+    }
+
+    #[test]
+    fn test_add_derived_converts_units() {
+        let mut response = forecast("°F");
+        response
+            .add_derived(&[DerivedMetric::HeatIndex, DerivedMetric::WindChill])
+            .unwrap();
+
+        let hourly = response.hourly.as_ref().unwrap();
+        assert_close(hourly.variables["heat_index"][0], 105.9, 0.1);
+        assert_close(hourly.variables["wind_chill"][2], -0.2, 0.1);
+        assert_eq!(response.hourly_units.as_ref().unwrap()["heat_index"], "°F");
+    }
+
+    #[test]
+    fn test_add_derived_missing_input() {
+        let mut response = forecast("°C");
+        response
+            .hourly
+            .as_mut()
+            .unwrap()
+            .variables
+            .remove(RELATIVE_HUMIDITY);
+
+        let error = response.add_derived(&[DerivedMetric::WetBulb]).unwrap_err();
+        assert!(matches!(error, OpenMeteoError::InvalidParameter(_)));
+
+        // A later metric's missing input adds no column for the earlier ones
+        assert!(
+            response
+                .add_derived(&[DerivedMetric::WindChill, DerivedMetric::WetBulb])
+                .is_err()
+        );
+        assert!(
+            !response
+                .hourly
+                .as_ref()
+                .unwrap()
+                .variables
+                .contains_key("wind_chill")
+        );
+        assert!(
+            !response
+                .hourly_units
+                .as_ref()
+                .unwrap()
+                .contains_key("wind_chill")
+        );
+        assert!(response.add_derived(&[DerivedMetric::WindChill]).is_ok());
+    }
+}
@@ -0,0 +1,660 @@
+//! Comparison of two forecasts for the same location
+//!
+//! [`diff`] aligns the hourly series of an earlier and a later forecast on
+//! their UTC time and the daily series on their local date, pairs their
+//! current conditions as a single step, converts the earlier values to the
+//! later forecast's units, and keeps the pairs side by side.
+//! [`ForecastDiff::changes`] then picks out what matters: changes above
+//! per-variable [`Thresholds`] and precipitation that appeared or cleared.
+//!
+//! ```no_run
+//! # fn run(previous: tiny_weather::client::types::ForecastResponse,
+//! #        current: tiny_weather::client::types::ForecastResponse)
+//! #        -> tiny_weather::client::Result<()> {
+//! use tiny_weather::diff::{self, Thresholds};
+//!
+//! let changes = diff::diff(&previous, &current)?
+//!     .changes(&Thresholds::default().variable("temperature_2m_max", 2.0));
+//! for change in changes {
+//!     // e.g., "daily temperature_2m_max 2024-06-22: 24.0 → 19.0 °C (-5.0)"
+//!     println!("{}", change);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+
+use crate::client::error::{OpenMeteoError, Result};
+use crate::client::types::forecast::ForecastResponse;
+use crate::client::types::units::{Dimension, Unit};
+use crate::frame::{local_date, parse_time};
+
+/// Largest distance (degrees) between grid cells considered the same location
+const MAX_LOCATION_DISTANCE: f64 = 0.01;
+
+/// Variable name prefixes of precipitation amounts
+const PRECIPITATION_PREFIXES: [&str; 4] = ["precipitation", "rain", "showers", "snowfall"];
+
+/// Hourly or daily series, or current conditions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Resolution {
+    Hourly,
+    Daily,
+    Current,
+}
+
+impl fmt::Display for Resolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Hourly => "hourly",
+            Self::Daily => "daily",
+            Self::Current => "current",
+        })
+    }
+}
+
+/// Minimum changes reported by [`ForecastDiff::changes`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Thresholds {
+    /// Minimum absolute change for variables without their own threshold
+    pub default: f64,
+    /// Minimum absolute change per variable, in the later forecast's unit
+    pub variables: HashMap<String, f64>,
+    /// Amount from which precipitation counts as present
+    pub precipitation: f64,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            default: 1.0,
+            variables: HashMap::new(),
+            precipitation: 0.1,
+        }
+    }
+}
+
+impl Thresholds {
+    /// Set the threshold for variables without their own
+    pub fn default_threshold(mut self, threshold: f64) -> Self {
+        self.default = threshold;
+        self
+    }
+
+    /// Set the threshold of one variable
+    pub fn variable(mut self, variable: impl Into<String>, threshold: f64) -> Self {
+        self.variables.insert(variable.into(), threshold);
+        self
+    }
+
+    /// Set the amount from which precipitation counts as present
+    pub fn precipitation(mut self, amount: f64) -> Self {
+        self.precipitation = amount;
+        self
+    }
+
+    fn of(&self, variable: &str) -> f64 {
+        self.variables
+            .get(variable)
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+/// One variable of both forecasts, aligned on the times they share
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableDiff {
+    pub resolution: Resolution,
+    pub variable: String,
+    /// Unit of the later forecast, which earlier values were converted to
+    pub unit: Option<String>,
+    /// Times as written in the later forecast
+    pub time: Vec<String>,
+    pub previous: Vec<Option<f64>>,
+    pub current: Vec<Option<f64>>,
+}
+
+impl VariableDiff {
+    /// Change at each time, `None` where either value is missing
+    pub fn deltas(&self) -> Vec<Option<f64>> {
+        self.previous
+            .iter()
+            .zip(&self.current)
+            .map(|(previous, current)| Some((*current)? - (*previous)?))
+            .collect()
+    }
+
+    /// Largest absolute change, if any time has both values
+    pub fn max_abs_delta(&self) -> Option<f64> {
+        self.deltas()
+            .into_iter()
+            .flatten()
+            .map(f64::abs)
+            .reduce(f64::max)
+    }
+
+    /// Whether the variable is a precipitation amount (not a probability or duration)
+    pub fn is_precipitation(&self) -> bool {
+        PRECIPITATION_PREFIXES
+            .iter()
+            .any(|prefix| self.variable.starts_with(prefix))
+            && match &self.unit {
+                Some(unit) => Unit::parse(unit).map(Unit::dimension) == Some(Dimension::Length),
+                None => {
+                    !self.variable.ends_with("_probability") && !self.variable.ends_with("_hours")
+                }
+            }
+    }
+}
+
+/// Kind of a reported change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeKind {
+    /// Value changed by at least its threshold
+    Changed,
+    /// Precipitation now expected where there was none
+    NewPrecipitation,
+    /// Precipitation no longer expected
+    PrecipitationCleared,
+}
+
+/// A significant change at one time step
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub kind: ChangeKind,
+    pub resolution: Resolution,
+    pub variable: String,
+    pub unit: Option<String>,
+    pub time: String,
+    pub previous: Option<f64>,
+    pub current: Option<f64>,
+}
+
+impl Change {
+    /// Difference between the current and previous value
+    pub fn delta(&self) -> Option<f64> {
+        Some(self.current? - self.previous?)
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = |v: Option<f64>| v.map_or("-".to_string(), |v| format!("{:.1}", v));
+        write!(
+            f,
+            "{} {} {}: {} → {}",
+            self.resolution,
+            self.variable,
+            self.time,
+            value(self.previous),
+            value(self.current)
+        )?;
+        if let Some(unit) = self.unit.as_deref().filter(|unit| !unit.is_empty()) {
+            write!(f, " {}", unit)?;
+        }
+        match self.kind {
+            ChangeKind::Changed => match self.delta() {
+                Some(delta) => write!(f, " ({:+.1})", delta),
+                None => Ok(()),
+            },
+            ChangeKind::NewPrecipitation => f.write_str(" (new precipitation)"),
+            ChangeKind::PrecipitationCleared => f.write_str(" (precipitation cleared)"),
+        }
+    }
+}
+
+/// Aligned series of two forecasts
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForecastDiff {
+    /// Variables present in both forecasts, hourly, daily then current, sorted by name
+    pub variables: Vec<VariableDiff>,
+}
+
+impl ForecastDiff {
+    /// Diff of one variable
+    pub fn variable(&self, resolution: Resolution, variable: &str) -> Option<&VariableDiff> {
+        self.variables
+            .iter()
+            .find(|diff| diff.resolution == resolution && diff.variable == variable)
+    }
+
+    /// Significant changes, in series order
+    ///
+    /// Precipitation amounts are reported when they cross `thresholds.precipitation`
+    /// (a missing earlier value counts as none); other variables when both values
+    /// are present and differ by at least their threshold.
+    pub fn changes(&self, thresholds: &Thresholds) -> Vec<Change> {
+        let mut changes = Vec::new();
+        for diff in &self.variables {
+            let precipitation = diff.is_precipitation();
+            let threshold = thresholds.of(&diff.variable);
+            for (i, time) in diff.time.iter().enumerate() {
+                let (previous, current) = (diff.previous[i], diff.current[i]);
+                let kind = if precipitation {
+                    let wet = |v: Option<f64>| v.is_some_and(|v| v >= thresholds.precipitation);
+                    match (wet(previous), wet(current)) {
+                        (false, true) => ChangeKind::NewPrecipitation,
+                        (true, false) if current.is_some() => ChangeKind::PrecipitationCleared,
+                        _ => continue,
+                    }
+                } else {
+                    match (previous, current) {
+                        (Some(previous), Some(current))
+                            if (current - previous).abs() >= threshold =>
+                        {
+                            ChangeKind::Changed
+                        }
+                        _ => continue,
+                    }
+                };
+                changes.push(Change {
+                    kind,
+                    resolution: diff.resolution,
+                    variable: diff.variable.clone(),
+                    unit: diff.unit.clone(),
+                    time: time.clone(),
+                    previous,
+                    current,
+                });
+            }
+        }
+        changes
+    }
+}
+
+/// Compare an earlier forecast with a later one for the same location
+///
+/// Fails if the forecasts are for different locations or a variable's units
+/// cannot be converted into each other.
+pub fn diff(previous: &ForecastResponse, current: &ForecastResponse) -> Result<ForecastDiff> {
+    if (previous.latitude - current.latitude).abs() > MAX_LOCATION_DISTANCE
+        || (previous.longitude - current.longitude).abs() > MAX_LOCATION_DISTANCE
+    {
+        return Err(OpenMeteoError::InvalidParameter(format!(
+            "forecasts are for different locations: ({}, {}) and ({}, {})",
+            previous.latitude, previous.longitude, current.latitude, current.longitude
+        )));
+    }
+
+    let mut variables = Vec::new();
+    if let (Some(before), Some(after)) = (&previous.hourly, &current.hourly) {
+        variables.extend(diff_series(
+            Resolution::Hourly,
+            Series::new(
+                &before.time,
+                &before.variables,
+                previous.hourly_units.as_ref(),
+                previous.utc_offset_seconds,
+            ),
+            Series::new(
+                &after.time,
+                &after.variables,
+                current.hourly_units.as_ref(),
+                current.utc_offset_seconds,
+            ),
+        )?);
+    }
+    if let (Some(before), Some(after)) = (&previous.daily, &current.daily) {
+        variables.extend(diff_series(
+            Resolution::Daily,
+            Series::new(
+                &before.time,
+                &before.variables,
+                previous.daily_units.as_ref(),
+                previous.utc_offset_seconds,
+            ),
+            Series::new(
+                &after.time,
+                &after.variables,
+                current.daily_units.as_ref(),
+                current.utc_offset_seconds,
+            ),
+        )?);
+    }
+
+    if let (Some(before), Some(after)) = (&previous.current, &current.current) {
+        // One step each, compared whatever their times; `interval` is not a reading
+        let step = |variables: &HashMap<String, Option<f64>>| -> HashMap<String, Vec<Option<f64>>> {
+            variables
+                .iter()
+                .filter(|(name, _)| name.as_str() != "interval")
+                .map(|(name, value)| (name.clone(), vec![*value]))
+                .collect()
+        };
+        let (before_time, after_time) = ([before.time.clone()], [after.time.clone()]);
+        let (before_variables, after_variables) = (step(&before.variables), step(&after.variables));
+        variables.extend(diff_series(
+            Resolution::Current,
+            Series::new(
+                &before_time,
+                &before_variables,
+                previous.current_units.as_ref(),
+                previous.utc_offset_seconds,
+            ),
+            Series::new(
+                &after_time,
+                &after_variables,
+                current.current_units.as_ref(),
+                current.utc_offset_seconds,
+            ),
+        )?);
+    }
+
+    Ok(ForecastDiff { variables })
+}
+
+/// Time and variables of one resolution of a forecast
+struct Series<'a> {
+    time: &'a [String],
+    variables: &'a HashMap<String, Vec<Option<f64>>>,
+    units: Option<&'a HashMap<String, String>>,
+    utc_offset_seconds: i32,
+}
+
+impl<'a> Series<'a> {
+    fn new(
+        time: &'a [String],
+        variables: &'a HashMap<String, Vec<Option<f64>>>,
+        units: Option<&'a HashMap<String, String>>,
+        utc_offset_seconds: i32,
+    ) -> Self {
+        Self {
+            time,
+            variables,
+            units,
+            utc_offset_seconds,
+        }
+    }
+
+    fn unit(&self, variable: &str) -> Option<&'a String> {
+        self.units?.get(variable)
+    }
+
+    /// Times to match on: UTC instants for hourly series, local dates (as
+    /// midnight UTC) for daily ones, so a day stays the same day when the
+    /// offset changes between the forecasts, and one shared key for current
+    /// conditions
+    fn keys(&self, resolution: Resolution) -> Result<Vec<DateTime<Utc>>> {
+        if resolution == Resolution::Current {
+            return Ok(vec![DateTime::UNIX_EPOCH; self.time.len()]);
+        }
+        self.time
+            .iter()
+            .map(|time| {
+                let time = parse_time(time, self.utc_offset_seconds)?;
+                Ok(match resolution {
+                    Resolution::Hourly => time,
+                    Resolution::Daily => local_date(time, self.utc_offset_seconds),
+                    Resolution::Current => unreachable!("keyed above"),
+                })
+            })
+            .collect()
+    }
+}
+
+fn diff_series(
+    resolution: Resolution,
+    previous: Series<'_>,
+    current: Series<'_>,
+) -> Result<Vec<VariableDiff>> {
+    // Index pairs of the times present in both forecasts
+    let previous_index: HashMap<DateTime<Utc>, usize> = previous
+        .keys(resolution)?
+        .into_iter()
+        .enumerate()
+        .map(|(i, time)| (time, i))
+        .collect();
+    let aligned: Vec<(usize, usize)> = current
+        .keys(resolution)?
+        .iter()
+        .enumerate()
+        .filter_map(|(j, time)| Some((*previous_index.get(time)?, j)))
+        .collect();
+
+    let mut names: Vec<&String> = current
+        .variables
+        .keys()
+        .filter(|name| previous.variables.contains_key(*name))
+        .collect();
+    names.sort();
+
+    names
+        .into_iter()
+        .map(|name| {
+            let unit = current.unit(name);
+            let conversion = match (previous.unit(name), unit) {
+                (Some(from), Some(to)) if from != to => {
+                    let convertible = Unit::parse(from)
+                        .zip(Unit::parse(to))
+                        .filter(|(from, to)| from.dimension() == to.dimension());
+                    Some(convertible.ok_or_else(|| {
+                        OpenMeteoError::InvalidParameter(format!(
+                            "cannot compare {} in {} with {}",
+                            name, from, to
+                        ))
+                    })?)
+                }
+                _ => None,
+            };
+
+            let before = &previous.variables[name];
+            let after = &current.variables[name];
+            let value = |values: &[Option<f64>], i: usize| values.get(i).copied().flatten();
+            Ok(VariableDiff {
+                resolution,
+                variable: name.clone(),
+                unit: unit.cloned(),
+                time: aligned
+                    .iter()
+                    .map(|(_, j)| current.time[*j].clone())
+                    .collect(),
+                previous: aligned
+                    .iter()
+                    .map(|(i, _)| {
+                        let v = value(before, *i)?;
+                        match conversion {
+                            Some((from, to)) => from.convert(v, to),
+                            None => Some(v),
+                        }
+                    })
+                    .collect(),
+                current: aligned.iter().map(|(_, j)| value(after, *j)).collect(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::types::forecast::fixtures;
+
+    fn forecast(
+        utc_offset_seconds: i32,
+        temperature_unit: &str,
+        hourly: serde_json::Value,
+        daily: serde_json::Value,
+    ) -> ForecastResponse {
+        fixtures::forecast(serde_json::json!({
+            "utc_offset_seconds": utc_offset_seconds,
+            "hourly": hourly,
+            "hourly_units": {"temperature_2m": temperature_unit, "precipitation": "mm"},
+            "daily": daily,
+            "daily_units": {"temperature_2m_max": temperature_unit}
+        }))
+    }
+
+    fn previous() -> ForecastResponse {
+        forecast(
+            0,
+            "°C",
+            serde_json::json!({
+                "time": ["2024-06-21T00:00", "2024-06-21T01:00", "2024-06-21T02:00"],
+                "temperature_2m": [15.0, 14.5, 14.0],
+                "precipitation": [0.0, 0.4, null]
+            }),
+            serde_json::json!({
+                "time": ["2024-06-21", "2024-06-22"],
+                "temperature_2m_max": [22.0, 24.0]
+            }),
+        )
+    }
+
+    #[test]
+    fn test_aligns_times_and_converts_units() {
+        // One hour later, in UTC+2 and °F: starts at the previous run's 01:00 UTC
+        let current = forecast(
+            7200,
+            "°F",
+            serde_json::json!({
+                "time": ["2024-06-21T03:00", "2024-06-21T04:00", "2024-06-21T05:00"],
+                "temperature_2m": [59.0, 59.0, 57.2],
+                "precipitation": [0.0, 1.2, 0.0]
+            }),
+            serde_json::json!({"time": [], "temperature_2m_max": []}),
+        );
+
+        let diff = diff(&previous(), &current).unwrap();
+        let temperature = diff.variable(Resolution::Hourly, "temperature_2m").unwrap();
+        assert_eq!(
+            temperature.time,
+            vec!["2024-06-21T03:00", "2024-06-21T04:00"]
+        );
+        assert_eq!(temperature.unit.as_deref(), Some("°F"));
+        assert!((temperature.previous[0].unwrap() - 58.1).abs() < 1e-9);
+        assert!((temperature.max_abs_delta().unwrap() - 1.8).abs() < 1e-9);
+
+        let precipitation = diff.variable(Resolution::Hourly, "precipitation").unwrap();
+        assert_eq!(precipitation.deltas(), vec![Some(-0.4), None]);
+    }
+
+    #[test]
+    fn test_aligns_days_across_offsets() {
+        // The same days in UTC+2, once as dates and once in unix time
+        let dates = serde_json::json!(["2024-06-21", "2024-06-22", "2024-06-23"]);
+        let unixtime = serde_json::json!(["1718920800", "1719007200", "1719093600"]);
+        for time in [dates, unixtime] {
+            let current = forecast(
+                7200,
+                "°C",
+                serde_json::json!({"time": [], "temperature_2m": [], "precipitation": []}),
+                serde_json::json!({"time": time, "temperature_2m_max": [21.0, 25.0, 26.0]}),
+            );
+
+            let diff = diff(&previous(), &current).unwrap();
+            let maximum = diff
+                .variable(Resolution::Daily, "temperature_2m_max")
+                .unwrap();
+            assert_eq!(maximum.time.len(), 2);
+            assert_eq!(maximum.previous, vec![Some(22.0), Some(24.0)]);
+            assert_eq!(maximum.deltas(), vec![Some(-1.0), Some(1.0)]);
+        }
+    }
+
+    #[test]
+    fn test_significant_changes() {
+        let current = forecast(
+            0,
+            "°C",
+            serde_json::json!({
+                "time": ["2024-06-21T00:00", "2024-06-21T01:00", "2024-06-21T02:00"],
+                "temperature_2m": [15.5, 14.5, 11.0],
+                "precipitation": [0.0, 0.0, 2.5]
+            }),
+            serde_json::json!({
+                "time": ["2024-06-21", "2024-06-22"],
+                "temperature_2m_max": [22.4, 19.0]
+            }),
+        );
+
+        let changes = diff(&previous(), &current)
+            .unwrap()
+            .changes(&Thresholds::default().variable("temperature_2m", 2.0));
+        let kinds: Vec<_> = changes
+            .iter()
+            .map(|change| (change.kind, change.variable.as_str(), change.time.as_str()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (
+                    ChangeKind::PrecipitationCleared,
+                    "precipitation",
+                    "2024-06-21T01:00"
+                ),
+                (
+                    ChangeKind::NewPrecipitation,
+                    "precipitation",
+                    "2024-06-21T02:00"
+                ),
+                (ChangeKind::Changed, "temperature_2m", "2024-06-21T02:00"),
+                (ChangeKind::Changed, "temperature_2m_max", "2024-06-22"),
+            ]
+        );
+        assert_eq!(
+            changes[3].to_string(),
+            "daily temperature_2m_max 2024-06-22: 24.0 → 19.0 °C (-5.0)"
+        );
+    }
+
+    #[test]
+    fn test_compares_current_conditions() {
+        let now = |time: &str, temperature: f64, precipitation: f64| {
+            let mut forecast = previous();
+            forecast.current = Some(
+                serde_json::from_value(serde_json::json!({
+                    "time": time,
+                    "interval": 900,
+                    "temperature_2m": temperature,
+                    "precipitation": precipitation
+                }))
+                .unwrap(),
+            );
+            forecast.current_units = Some(HashMap::from([
+                ("temperature_2m".to_string(), "°C".to_string()),
+                ("precipitation".to_string(), "mm".to_string()),
+            ]));
+            forecast
+        };
+
+        let diff = diff(
+            &now("2024-06-21T10:00", 18.0, 0.0),
+            &now("2024-06-21T10:15", 19.5, 0.6),
+        )
+        .unwrap();
+        assert!(diff.variable(Resolution::Current, "interval").is_none());
+        let changes: Vec<String> = diff
+            .changes(&Thresholds::default())
+            .into_iter()
+            .filter(|change| change.resolution == Resolution::Current)
+            .map(|change| change.to_string())
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                "current precipitation 2024-06-21T10:15: 0.0 → 0.6 mm (new precipitation)",
+                "current temperature_2m 2024-06-21T10:15: 18.0 → 19.5 °C (+1.5)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rejects_other_location_and_units() {
+        let mut elsewhere = previous();
+        elsewhere.latitude = 48.14;
+        assert!(diff(&previous(), &elsewhere).is_err());
+
+        let mut percent = previous();
+        percent
+            .hourly_units
+            .as_mut()
+            .unwrap()
+            .insert("temperature_2m".to_string(), "%".to_string());
+        let error = diff(&previous(), &percent).unwrap_err();
+        assert!(matches!(error, OpenMeteoError::InvalidParameter(_)));
+    }
+}
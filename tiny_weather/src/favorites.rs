@@ -0,0 +1,187 @@
+//! Saved locations under user-chosen aliases
+//!
+//! Favorites are resolved [`Location`] records keyed by an alias such as
+//! `home` or `cabin`, kept as one TOML table per alias in
+//! `tiny_weather_favorites.toml` next to the config file. An alias resolves
+//! without a geocoding call.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::client::error::{OpenMeteoError, Result};
+use crate::client::types::Location;
+use crate::config::TinyWeatherConfig;
+
+/// File name of the favorites, in the directory of the config file
+pub const FAVORITES_FILE: &str = "tiny_weather_favorites.toml";
+
+/// Locations by alias, in alphabetical order
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Favorites {
+    places: BTreeMap<String, Location>,
+}
+
+impl Favorites {
+    /// Path of the favorites file
+    pub fn path() -> Option<PathBuf> {
+        Some(TinyWeatherConfig::path()?.parent()?.join(FAVORITES_FILE))
+    }
+
+    /// Favorites from a file, or none if it does not exist
+    pub fn read(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => toml::from_str(&text).map_err(|e| {
+                OpenMeteoError::InvalidParameter(format!("{}: {}", path.display(), e))
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Write the favorites to a file, creating its directory
+    pub fn write(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let text = toml::to_string_pretty(self)
+            .map_err(|e| OpenMeteoError::InvalidParameter(e.to_string()))?;
+        std::fs::write(path, text)?;
+        Ok(())
+    }
+
+    /// Location saved under `alias`
+    pub fn get(&self, alias: &str) -> Option<&Location> {
+        self.places.get(alias)
+    }
+
+    /// Save `location` under `alias`, returning the location it replaces
+    pub fn insert(&mut self, alias: &str, location: Location) -> Result<Option<Location>> {
+        validate(alias)?;
+        Ok(self.places.insert(alias.to_string(), location))
+    }
+
+    /// Forget `alias`, returning its location
+    pub fn remove(&mut self, alias: &str) -> Result<Location> {
+        self.places.remove(alias).ok_or_else(|| unknown(alias))
+    }
+
+    /// Move the location saved under `from` to `to`, which must be free
+    pub fn rename(&mut self, from: &str, to: &str) -> Result<()> {
+        validate(to)?;
+        if self.places.contains_key(to) {
+            return Err(OpenMeteoError::InvalidParameter(format!(
+                "favorite '{}' already exists",
+                to
+            )));
+        }
+        let location = self.remove(from)?;
+        self.places.insert(to.to_string(), location);
+        Ok(())
+    }
+
+    /// Aliases and their locations, in alphabetical order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Location)> {
+        self.places
+            .iter()
+            .map(|(alias, location)| (alias.as_str(), location))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.places.is_empty()
+    }
+}
+
+/// Aliases start with a letter and contain only letters, digits, `-` and `_`,
+/// so they never look like coordinates or a postal code
+fn validate(alias: &str) -> Result<()> {
+    let valid = alias.chars().next().is_some_and(char::is_alphabetic)
+        && alias
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(OpenMeteoError::InvalidParameter(format!(
+            "invalid alias '{}': use letters, digits, '-' and '_', starting with a letter",
+            alias
+        )))
+    }
+}
+
+fn unknown(alias: &str) -> OpenMeteoError {
+    OpenMeteoError::InvalidParameter(format!("no favorite named '{}'", alias))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn location(name: &str, latitude: f64, longitude: f64) -> Location {
+        serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "name": name,
+            "latitude": latitude,
+            "longitude": longitude,
+            "timezone": "Europe/Berlin"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_favorites_add_rename_remove() {
+        let mut favorites = Favorites::default();
+        assert!(
+            favorites
+                .insert("home", location("Berlin", 52.52, 13.41))
+                .unwrap()
+                .is_none()
+        );
+        favorites
+            .insert("office", location("Potsdam", 52.4, 13.07))
+            .unwrap();
+        let replaced = favorites
+            .insert("office", location("Leipzig", 51.34, 12.37))
+            .unwrap();
+        assert_eq!(replaced.unwrap().name, "Potsdam");
+
+        assert!(favorites.rename("office", "home").is_err());
+        favorites.rename("office", "work").unwrap();
+        assert!(favorites.get("office").is_none());
+        assert_eq!(favorites.get("work").unwrap().name, "Leipzig");
+        let aliases: Vec<&str> = favorites.iter().map(|(alias, _)| alias).collect();
+        assert_eq!(aliases, ["home", "work"]);
+
+        assert_eq!(favorites.remove("home").unwrap().name, "Berlin");
+        assert!(favorites.remove("home").is_err());
+
+        for alias in ["", "52.5", "my home", "home,2"] {
+            assert!(favorites.insert(alias, location("X", 0.0, 0.0)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_favorites_file_roundtrip() {
+        let mut favorites = Favorites::default();
+        favorites
+            .insert("cabin", location("Åre", 63.4, 13.08))
+            .unwrap();
+
+        let path = std::env::temp_dir()
+            .join(format!("tiny_weather_favorites_{}", std::process::id()))
+            .join(FAVORITES_FILE);
+        favorites.write(&path).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.starts_with("[cabin]\n"));
+
+        let read = Favorites::read(&path).unwrap();
+        let cabin = read.get("cabin").unwrap();
+        assert_eq!((cabin.latitude, cabin.longitude), (63.4, 13.08));
+        assert_eq!(cabin.timezone.as_deref(), Some("Europe/Berlin"));
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert!(Favorites::read(&path).unwrap().is_empty());
+    }
+}
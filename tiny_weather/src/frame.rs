@@ -0,0 +1,561 @@
+//! Columnar forecast frames for analytics
+//!
+//! A [`ForecastFrame`] holds the hourly or daily series of one or more
+//! locations as a table: a typed UTC time column plus one nullable `f64`
+//! column per variable and location. Hourly rows of different locations are
+//! aligned on their UTC time and daily rows on their local date, with `None`
+//! where a location has no value.
+//!
+//! Conversions to other dataframe libraries are behind cargo features:
+//! - `arrow`: [`ForecastFrame::to_record_batch`]
+//! - `polars`: [`ForecastFrame::to_dataframe`]
+//! - `parquet`: [`ForecastFrame::write_parquet`] (implies `arrow`)
+//!
+//! ```no_run
+//! # async fn run() -> tiny_weather::client::Result<()> {
+//! use tiny_weather::ForecastFrame;
+//! use tiny_weather::client::ForecastClient;
+//! use tiny_weather::client::types::ForecastRequest;
+//!
+//! let request = ForecastRequest::new(52.52, 13.41)
+//!     .location(48.14, 11.58)
+//!     .hourly(&["temperature_2m", "precipitation"]);
+//! let forecasts = ForecastClient::new().get_many(request).await?;
+//!
+//! let frame = ForecastFrame::hourly(&forecasts)?;
+//! let temperature = frame.column("temperature_2m@1").unwrap();
+//! println!("{} rows, {:?} {}", frame.len(), temperature.values[0], temperature.unit);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+use crate::client::error::{OpenMeteoError, Result};
+use crate::client::types::forecast::{DailyData, ForecastResponse, HourlyData};
+
+/// Name of the time column in exported tables
+pub const TIME_COLUMN: &str = "time";
+
+/// Hourly or daily forecast series as a table with a typed time column
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForecastFrame {
+    time: Vec<DateTime<Utc>>,
+    columns: Vec<FrameColumn>,
+}
+
+/// One variable of one location
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameColumn {
+    /// Column name: the variable name, suffixed with `@<location>` in multi-location frames
+    pub name: String,
+    /// Open-Meteo variable name (e.g., `temperature_2m`)
+    pub variable: String,
+    /// Index of the location in the request
+    pub location: usize,
+    /// Unit from the response's `*_units` map, empty if unknown
+    pub unit: String,
+    pub values: Vec<Option<f64>>,
+}
+
+/// Time and variables of one location, before alignment
+struct LocationSeries<'a> {
+    time: &'a [String],
+    variables: &'a HashMap<String, Vec<Option<f64>>>,
+    units: Option<&'a HashMap<String, String>>,
+    utc_offset_seconds: i32,
+    /// Whether the time steps are local dates rather than instants
+    daily: bool,
+}
+
+impl ForecastFrame {
+    /// Build a frame from the hourly series of one or more forecasts
+    pub fn hourly(forecasts: &[ForecastResponse]) -> Result<Self> {
+        let series = forecasts
+            .iter()
+            .map(|forecast| {
+                let hourly = forecast
+                    .hourly
+                    .as_ref()
+                    .ok_or_else(|| invalid("forecast has no hourly data"))?;
+                Ok(LocationSeries {
+                    time: &hourly.time,
+                    variables: &hourly.variables,
+                    units: forecast.hourly_units.as_ref(),
+                    utc_offset_seconds: forecast.utc_offset_seconds,
+                    daily: false,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Self::from_series(&series)
+    }
+
+    /// Build a frame from the daily series of one or more forecasts
+    ///
+    /// Each day is stamped with its local date at midnight UTC, so the same
+    /// date is one row for locations in different timezones.
+    pub fn daily(forecasts: &[ForecastResponse]) -> Result<Self> {
+        let series = forecasts
+            .iter()
+            .map(|forecast| {
+                let daily = forecast
+                    .daily
+                    .as_ref()
+                    .ok_or_else(|| invalid("forecast has no daily data"))?;
+                Ok(LocationSeries {
+                    time: &daily.time,
+                    variables: &daily.variables,
+                    units: forecast.daily_units.as_ref(),
+                    utc_offset_seconds: forecast.utc_offset_seconds,
+                    daily: true,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Self::from_series(&series)
+    }
+
+    /// Build a single-location frame from hourly data
+    ///
+    /// `utc_offset_seconds` is the response's offset of local times from UTC.
+    pub fn from_hourly(hourly: &HourlyData, utc_offset_seconds: i32) -> Result<Self> {
+        Self::from_series(&[LocationSeries {
+            time: &hourly.time,
+            variables: &hourly.variables,
+            units: None,
+            utc_offset_seconds,
+            daily: false,
+        }])
+    }
+
+    /// Build a single-location frame from daily data
+    ///
+    /// `utc_offset_seconds` is the response's offset of local times from UTC,
+    /// needed to find the local date of unix timestamps.
+    pub fn from_daily(daily: &DailyData, utc_offset_seconds: i32) -> Result<Self> {
+        Self::from_series(&[LocationSeries {
+            time: &daily.time,
+            variables: &daily.variables,
+            units: None,
+            utc_offset_seconds,
+            daily: true,
+        }])
+    }
+
+    fn from_series(series: &[LocationSeries<'_>]) -> Result<Self> {
+        let parsed = series
+            .iter()
+            .map(|s| {
+                s.time
+                    .iter()
+                    .map(|t| {
+                        let time = parse_time(t, s.utc_offset_seconds)?;
+                        Ok(if s.daily {
+                            local_date(time, s.utc_offset_seconds)
+                        } else {
+                            time
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // Union of all time steps, so locations in different timezones line up
+        let rows: BTreeMap<DateTime<Utc>, usize> = parsed
+            .iter()
+            .flatten()
+            .copied()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .enumerate()
+            .map(|(row, t)| (t, row))
+            .collect();
+
+        let multi = series.len() > 1;
+        let mut columns = Vec::new();
+        for (location, (s, times)) in series.iter().zip(&parsed).enumerate() {
+            // Sort by name so column order does not depend on `HashMap` iteration
+            let mut variables: Vec<_> = s.variables.iter().collect();
+            variables.sort_by_key(|(name, _)| name.as_str());
+
+            for (variable, values) in variables {
+                if values.len() != times.len() {
+                    return Err(invalid(&format!(
+                        "`{}` has {} values for {} time steps",
+                        variable,
+                        values.len(),
+                        times.len()
+                    )));
+                }
+
+                let mut aligned = vec![None; rows.len()];
+                for (t, value) in times.iter().zip(values) {
+                    aligned[rows[t]] = *value;
+                }
+
+                columns.push(FrameColumn {
+                    name: if multi {
+                        format!("{}@{}", variable, location)
+                    } else {
+                        variable.clone()
+                    },
+                    variable: variable.clone(),
+                    location,
+                    unit: s
+                        .units
+                        .and_then(|units| units.get(variable))
+                        .cloned()
+                        .unwrap_or_default(),
+                    values: aligned,
+                });
+            }
+        }
+
+        Ok(Self {
+            time: rows.into_keys().collect(),
+            columns,
+        })
+    }
+
+    /// Number of rows (time steps)
+    pub fn len(&self) -> usize {
+        self.time.len()
+    }
+
+    /// Whether the frame has no rows
+    pub fn is_empty(&self) -> bool {
+        self.time.is_empty()
+    }
+
+    /// Time column, in UTC and ascending order
+    pub fn time(&self) -> &[DateTime<Utc>] {
+        &self.time
+    }
+
+    /// All value columns, grouped by location and sorted by variable name
+    pub fn columns(&self) -> &[FrameColumn] {
+        &self.columns
+    }
+
+    /// Look up a column by its name
+    pub fn column(&self, name: &str) -> Option<&FrameColumn> {
+        self.columns.iter().find(|c| c.name == name)
+    }
+
+    /// All columns of one variable, one per location
+    pub fn variable<'a>(&'a self, variable: &'a str) -> impl Iterator<Item = &'a FrameColumn> {
+        self.columns.iter().filter(move |c| c.variable == variable)
+    }
+
+    /// Convert to an Arrow `RecordBatch`
+    ///
+    /// The time column is a UTC second-precision timestamp; each value column is a
+    /// nullable `Float64` whose field metadata carries `variable`, `location` and `unit`.
+    #[cfg(feature = "arrow")]
+    pub fn to_record_batch(&self) -> Result<arrow_array::RecordBatch> {
+        use std::sync::Arc;
+
+        use arrow_array::{ArrayRef, Float64Array, RecordBatch, TimestampSecondArray};
+        use arrow_schema::{DataType, Field, Schema, TimeUnit};
+
+        let mut fields = vec![Field::new(
+            TIME_COLUMN,
+            DataType::Timestamp(TimeUnit::Second, Some("UTC".into())),
+            false,
+        )];
+        let mut arrays: Vec<ArrayRef> = vec![Arc::new(
+            TimestampSecondArray::from(self.time.iter().map(|t| t.timestamp()).collect::<Vec<_>>())
+                .with_timezone("UTC"),
+        )];
+
+        for column in &self.columns {
+            let metadata = HashMap::from([
+                ("variable".to_string(), column.variable.clone()),
+                ("location".to_string(), column.location.to_string()),
+                ("unit".to_string(), column.unit.clone()),
+            ]);
+            fields.push(Field::new(&column.name, DataType::Float64, true).with_metadata(metadata));
+            arrays.push(Arc::new(Float64Array::from(column.values.clone())));
+        }
+
+        RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)
+            .map_err(|e| OpenMeteoError::Export(e.to_string()))
+    }
+
+    /// Convert to a Polars `DataFrame`
+    ///
+    /// The time column is a UTC millisecond-precision `Datetime`.
+    #[cfg(feature = "polars")]
+    pub fn to_dataframe(&self) -> Result<polars::prelude::DataFrame> {
+        use polars::prelude::{Column, DataFrame, DataType, NamedFrom, Series, TimeUnit};
+
+        let time = Series::new(
+            TIME_COLUMN.into(),
+            self.time
+                .iter()
+                .map(|t| t.timestamp_millis())
+                .collect::<Vec<_>>(),
+        )
+        .cast(&DataType::Datetime(
+            TimeUnit::Milliseconds,
+            Some("UTC".into()),
+        ))
+        .map_err(|e| OpenMeteoError::Export(e.to_string()))?;
+
+        let columns = std::iter::once(Column::from(time))
+            .chain(
+                self.columns
+                    .iter()
+                    .map(|c| Column::new(c.name.as_str().into(), &c.values)),
+            )
+            .collect();
+
+        DataFrame::new(columns).map_err(|e| OpenMeteoError::Export(e.to_string()))
+    }
+
+    /// Write the frame as a Parquet file, returning the number of rows written
+    #[cfg(feature = "parquet")]
+    pub fn write_parquet(&self, path: impl AsRef<std::path::Path>) -> Result<usize> {
+        let file = std::fs::File::create(path)?;
+        self.write_parquet_to(file)
+    }
+
+    /// Write the frame in Parquet format to any writer
+    #[cfg(feature = "parquet")]
+    pub fn write_parquet_to<W: std::io::Write + Send>(&self, writer: W) -> Result<usize> {
+        let batch = self.to_record_batch()?;
+        let mut writer = parquet::arrow::ArrowWriter::try_new(writer, batch.schema(), None)
+            .map_err(|e| OpenMeteoError::Export(e.to_string()))?;
+        writer
+            .write(&batch)
+            .map_err(|e| OpenMeteoError::Export(e.to_string()))?;
+        writer
+            .close()
+            .map_err(|e| OpenMeteoError::Export(e.to_string()))?;
+        Ok(batch.num_rows())
+    }
+}
+
+/// Parse a response time in local time (ISO 8601 date-time or date) or unix seconds
+pub(crate) fn parse_time(time: &str, utc_offset_seconds: i32) -> Result<DateTime<Utc>> {
+    if let Ok(unix) = time.parse::<i64>() {
+        return DateTime::from_timestamp(unix, 0)
+            .ok_or_else(|| invalid(&format!("timestamp out of range: {}", time)));
+    }
+
+    let local = NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M")
+        .or_else(|_| {
+            NaiveDate::parse_from_str(time, "%Y-%m-%d").map(|d| d.and_time(Default::default()))
+        })
+        .map_err(|_| invalid(&format!("unrecognized time: {}", time)))?;
+    Ok((local - chrono::Duration::seconds(utc_offset_seconds.into())).and_utc())
+}
+
+/// Local date of `time` at `utc_offset_seconds`, as midnight UTC
+pub(crate) fn local_date(time: DateTime<Utc>, utc_offset_seconds: i32) -> DateTime<Utc> {
+    (time + chrono::Duration::seconds(utc_offset_seconds.into()))
+        .date_naive()
+        .and_time(Default::default())
+        .and_utc()
+}
+
+fn invalid(reason: &str) -> OpenMeteoError {
+    OpenMeteoError::InvalidResponse(format!("forecast frame: {}", reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::types::forecast::fixtures;
+
+    fn forecast(
+        utc_offset_seconds: i32,
+        time: &[&str],
+        temperature: &[Option<f64>],
+    ) -> ForecastResponse {
+        fixtures::forecast(serde_json::json!({
+            "utc_offset_seconds": utc_offset_seconds,
+            "hourly": {
+                "time": time,
+                "temperature_2m": temperature,
+                "precipitation": vec![0.0; time.len()]
+            },
+            "hourly_units": {"temperature_2m": "°C", "precipitation": "mm"}
+        }))
+    }
+
+    #[test]
+    fn test_hourly_single_location() {
+        let forecast = forecast(
+            3600,
+            &["2024-01-01T01:00", "2024-01-01T02:00"],
+            &[Some(1.5), None],
+        );
+        let frame = ForecastFrame::hourly(&[forecast]).unwrap();
+
+        assert_eq!(frame.len(), 2);
+        assert_eq!(frame.time()[0].to_rfc3339(), "2024-01-01T00:00:00+00:00");
+        assert_eq!(
+            frame
+                .columns()
+                .iter()
+                .map(|c| c.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["precipitation", "temperature_2m"]
+        );
+
+        let temperature = frame.column("temperature_2m").unwrap();
+        assert_eq!(temperature.values, vec![Some(1.5), None]);
+        assert_eq!(temperature.unit, "°C");
+        assert_eq!(temperature.location, 0);
+    }
+
+    #[test]
+    fn test_hourly_aligns_locations_on_utc() {
+        let berlin = forecast(
+            3600,
+            &["2024-01-01T01:00", "2024-01-01T02:00"],
+            &[Some(1.0), Some(2.0)],
+        );
+        let london = forecast(
+            0,
+            &["2024-01-01T01:00", "2024-01-01T02:00"],
+            &[Some(5.0), Some(6.0)],
+        );
+        let frame = ForecastFrame::hourly(&[berlin, london]).unwrap();
+
+        assert_eq!(frame.len(), 3);
+        assert_eq!(
+            frame.column("temperature_2m@0").unwrap().values,
+            vec![Some(1.0), Some(2.0), None]
+        );
+        assert_eq!(
+            frame.column("temperature_2m@1").unwrap().values,
+            vec![None, Some(5.0), Some(6.0)]
+        );
+        assert_eq!(frame.variable("temperature_2m").count(), 2);
+    }
+
+    #[test]
+    fn test_from_daily_and_unixtime() {
+        let daily = DailyData {
+            time: vec!["2024-01-01".to_string(), "2024-01-02".to_string()],
+            variables: HashMap::from([(
+                "temperature_2m_max".to_string(),
+                vec![Some(3.0), Some(4.0)],
+            )]),
+            time_variables: HashMap::new(),
+        };
+        let frame = ForecastFrame::from_daily(&daily, -18000).unwrap();
+        assert_eq!(frame.time()[0].to_rfc3339(), "2024-01-01T00:00:00+00:00");
+        assert_eq!(frame.column("temperature_2m_max").unwrap().unit, "");
+
+        let hourly = HourlyData {
+            time: vec!["1704067200".to_string()],
+            variables: HashMap::new(),
+        };
+        let frame = ForecastFrame::from_hourly(&hourly, 3600).unwrap();
+        assert_eq!(frame.time()[0].timestamp(), 1704067200);
+    }
+
+    #[test]
+    fn test_daily_aligns_locations_on_local_date() {
+        let daily = |utc_offset_seconds: i32, time: &[&str], max: &[f64]| {
+            let mut forecast = forecast(utc_offset_seconds, &[], &[]);
+            forecast.daily = Some(DailyData {
+                time: time.iter().map(|t| t.to_string()).collect(),
+                variables: HashMap::from([(
+                    "temperature_2m_max".to_string(),
+                    max.iter().copied().map(Some).collect(),
+                )]),
+                time_variables: HashMap::new(),
+            });
+            forecast
+        };
+        let berlin = daily(3600, &["2024-01-01", "2024-01-02"], &[3.0, 4.0]);
+        let new_york = daily(-18000, &["2024-01-01", "2024-01-02"], &[-1.0, 0.0]);
+        // Tokyo local midnights of the same dates, as unix timestamps
+        let tokyo = daily(32400, &["1704034800", "1704121200"], &[9.0, 10.0]);
+        let frame = ForecastFrame::daily(&[berlin, new_york, tokyo]).unwrap();
+
+        assert_eq!(frame.len(), 2);
+        assert_eq!(frame.time()[0].to_rfc3339(), "2024-01-01T00:00:00+00:00");
+        assert_eq!(
+            frame.column("temperature_2m_max@1").unwrap().values,
+            vec![Some(-1.0), Some(0.0)]
+        );
+        assert_eq!(
+            frame.column("temperature_2m_max@2").unwrap().values,
+            vec![Some(9.0), Some(10.0)]
+        );
+    }
+
+    #[test]
+    fn test_invalid_series() {
+        let ragged = forecast(0, &["2024-01-01T00:00"], &[Some(1.0), Some(2.0)]);
+        assert!(ForecastFrame::hourly(&[ragged]).is_err());
+
+        let bad_time = forecast(0, &["yesterday"], &[Some(1.0)]);
+        assert!(ForecastFrame::hourly(&[bad_time]).is_err());
+
+        let mut no_hourly = forecast(0, &[], &[]);
+        no_hourly.hourly = None;
+        assert!(ForecastFrame::hourly(&[no_hourly]).is_err());
+    }
+
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn test_to_record_batch() {
+        let forecast = forecast(
+            0,
+            &["2024-01-01T00:00", "2024-01-01T01:00"],
+            &[Some(1.5), None],
+        );
+        let batch = ForecastFrame::hourly(&[forecast])
+            .unwrap()
+            .to_record_batch()
+            .unwrap();
+
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.num_columns(), 3);
+        let schema = batch.schema();
+        let field = schema.field_with_name("temperature_2m").unwrap();
+        assert_eq!(field.metadata()["unit"], "°C");
+        assert_eq!(batch.column(2).null_count(), 1);
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn test_to_dataframe() {
+        let forecast = forecast(
+            0,
+            &["2024-01-01T00:00", "2024-01-01T01:00"],
+            &[Some(1.5), None],
+        );
+        let df = ForecastFrame::hourly(&[forecast])
+            .unwrap()
+            .to_dataframe()
+            .unwrap();
+
+        assert_eq!(df.shape(), (2, 3));
+        let temperature = df.column("temperature_2m").unwrap().f64().unwrap();
+        assert_eq!(temperature.get(0), Some(1.5));
+        assert_eq!(temperature.get(1), None);
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_write_parquet() {
+        let forecast = forecast(
+            0,
+            &["2024-01-01T00:00", "2024-01-01T01:00"],
+            &[Some(1.5), None],
+        );
+        let frame = ForecastFrame::hourly(&[forecast]).unwrap();
+
+        let mut buffer = Vec::new();
+        assert_eq!(frame.write_parquet_to(&mut buffer).unwrap(), 2);
+        assert!(buffer.starts_with(b"PAR1"));
+    }
+}
@@ -3,7 +3,42 @@
 //! This crate provides clients for:
 //! - Weather Forecast API (hourly, daily, current conditions)
 //! - Geocoding API (location search)
+//!
+//! Derived metrics such as heat index, wind chill and wet-bulb temperature
+//! are computed locally by the [`derived`] module, and sunrise, sunset,
+//! twilight and moon phase offline by the [`astro`] module. The [`aggregate`]
+//! module rolls hourly series up into days, weeks or custom windows, and
+//! [`diff`] reports how a forecast changed between two fetches. Multi-model
+//! responses blend into one forecast with an uncertainty band via [`consensus`],
+//! and [`solar`] estimates PV yield from tilted irradiance.
+//!
+//! Forecasts convert to a columnar [`ForecastFrame`] for analytics, with
+//! Arrow, Polars and Parquet interop behind the `arrow`, `polars` and
+//! `parquet` features, and to a versioned [`output::Report`] for the JSON,
+//! NDJSON, CSV and YAML output of the command-line tool. The tool's settings
+//! live in [`config`], saved places in [`favorites`], and [`cache`] keeps
+//! responses on disk between runs. Location arguments (coordinates, geohashes,
+//! plus codes, postal codes, aliases) are parsed by [`query`], and ambiguous
+//! place names can be settled with the terminal [`picker`].
+//!
+//! Enable the `testing` feature for an in-process mock Open-Meteo server.
 
+pub mod aggregate;
 pub mod client;
 pub mod args;
+pub mod astro;
+pub mod cache;
+pub mod config;
+pub mod consensus;
+pub mod derived;
+pub mod diff;
+pub mod favorites;
+pub mod frame;
+pub mod output;
+pub mod picker;
+pub mod query;
+pub mod solar;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub use client::{ForecastClient, GeocodingClient, OpenMeteoClient};
+pub use frame::ForecastFrame;
@@ -1,93 +1,534 @@
-//! Example usage of the tiny_weather Open-Meteo API client
+//! Command-line client for Open-Meteo forecasts, history and geocoding
 
-use tiny_weather::client::types::{ForecastRequest, GeocodingRequest};
-use tiny_weather::client::{ForecastClient, GeocodingClient};
-use tiny_weather::args::TinyWeatherArgs;
-use tracing::info;
-use anyhow::Result;
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
 use clap::Parser;
+use tiny_weather::args::{
+    AstroArgs, Command, ConfigCommand, DiffOptions, FavoriteCommand, ForecastArgs, HistoryArgs,
+    NowArgs, RequestOptions, SearchArgs, TinyWeatherArgs,
+};
+use tiny_weather::cache::DiskCache;
+use tiny_weather::client::types::{
+    Coordinates, ForecastRequest, ForecastResponse, GeocodingRequest, Location, UnitSystem,
+};
+use tiny_weather::client::{ClientConfig, OpenMeteoClient};
+use tiny_weather::config::{self, TinyWeatherConfig};
+use tiny_weather::diff::{self, Thresholds};
+use tiny_weather::favorites::Favorites;
+use tiny_weather::output::{OutputFormat, Report};
+use tiny_weather::picker;
+use tiny_weather::query::LocationQuery;
+use tracing::debug;
+
+/// Current variables shown by `now` unless `--variables` is given
+const NOW_VARIABLES: &[&str] = &[
+    "weather_code",
+    "temperature_2m",
+    "apparent_temperature",
+    "relative_humidity_2m",
+    "precipitation",
+    "wind_speed_10m",
+    "wind_direction_10m",
+];
+
+/// Hourly variables shown by `hourly` unless `--variables` is given
+const HOURLY_VARIABLES: &[&str] = &[
+    "weather_code",
+    "temperature_2m",
+    "precipitation_probability",
+    "precipitation",
+    "wind_speed_10m",
+];
+
+/// Daily variables shown by `daily` unless `--variables` is given
+const DAILY_VARIABLES: &[&str] = &[
+    "weather_code",
+    "temperature_2m_max",
+    "temperature_2m_min",
+    "precipitation_sum",
+    "precipitation_probability_max",
+    "sunrise",
+    "sunset",
+];
+
+/// Daily variables shown by `history` unless `--variables` is given
+const HISTORY_VARIABLES: &[&str] = &[
+    "weather_code",
+    "temperature_2m_max",
+    "temperature_2m_min",
+    "precipitation_sum",
+];
+
+/// What every command needs: the client, the merged settings, the saved
+/// locations and the output format
+struct App {
+    client: OpenMeteoClient,
+    config: TinyWeatherConfig,
+    favorites: Favorites,
+    output: OutputFormat,
+    /// Ignore remembered choices among geocoding matches
+    choose: bool,
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
-
     let args = TinyWeatherArgs::parse();
-    info!(target: "tiny_weather", "args: {:?}", args);
 
-    // Initialize tracing subscriber for logging
+    // Only warnings (e.g., a distant grid point) are logged, to stderr
     tracing_subscriber::fmt()
-        .with_max_level(tracing::Level::DEBUG)
+        .with_max_level(tracing::Level::WARN)
+        .with_writer(std::io::stderr)
         .init();
+    debug!("args: {:?}", args);
+
+    if let Command::Config(command) = &args.command {
+        return configure(command);
+    }
+
+    // File, then environment, then command line
+    let mut config = TinyWeatherConfig::load()?;
+    if args.theme.is_some() {
+        config.theme = args.theme;
+    }
+    let language = match &args.command {
+        Command::Search(search) => search.language.as_ref(),
+        Command::Now(now) => now.options.language.as_ref(),
+        Command::Hourly(forecast) | Command::Daily(forecast) => forecast.options.language.as_ref(),
+        Command::History(history) => history.options.language.as_ref(),
+        _ => None,
+    };
+    if language.is_some() {
+        config.language = language.cloned();
+    }
+    let mut client_config = ClientConfig::new();
+    if let Some(api_key) = &config.api_key {
+        client_config = client_config.api_key(api_key);
+    }
+    if let Some(km) = config.snap_warning_km {
+        client_config = client_config.snap_warning(km);
+    }
+    let client = OpenMeteoClient::with_config(client_config)?;
+    let favorites = match Favorites::path() {
+        Some(path) => Favorites::read(&path)?,
+        None => Favorites::default(),
+    };
+    let mut app = App {
+        client,
+        config,
+        favorites,
+        output: args.output,
+        choose: args.choose,
+    };
+
+    let report = match &args.command {
+        Command::Search(search) => search_locations(&app, search).await?,
+        Command::Now(forecast) => now(&app, forecast).await?,
+        Command::Hourly(forecast) => hourly(&app, forecast).await?,
+        Command::Daily(forecast) => daily(&app, forecast).await?,
+        Command::History(history) => history_range(&app, history).await?,
+        Command::Astro(astro) => astro_report(&app, astro).await?,
+        Command::Favorite(command) => favorite(&mut app, command).await?,
+        Command::Config(_) => unreachable!("handled above"),
+    };
+    let mut report = report.theme(app.config.theme.unwrap_or_default());
+    if let Some(language) = &app.config.language {
+        report = report.language(language);
+    }
 
-    // Example 1: Geocoding - Search for a location
-    println!("=== Geocoding Example ===");
-    let geocoding_client = GeocodingClient::new();
+    let mut stdout = std::io::stdout().lock();
+    report.write(app.output, &mut stdout)?;
+    stdout.flush()?;
+    Ok(())
+}
 
-    match geocoding_client
-        .search(
-            GeocodingRequest::new("San Francisco")
-                .count(1)
-                .language("en"),
-        )
+async fn search_locations(app: &App, args: &SearchArgs) -> Result<Report> {
+    let mut request = GeocodingRequest::new(&args.name).count(args.count);
+    if let Some(language) = &app.config.language {
+        request = request.language(language);
+    }
+    let response = app
+        .client
+        .geocoding()
+        .search(request)
         .await
-    {
-        Ok(response) => {
-            if let Some(location) = response.results.first() {
-                println!(
-                    "Found: {} ({}, {})",
-                    location.name, location.latitude, location.longitude
+        .with_context(|| format!("no locations found for '{}'", args.name))?;
+    Ok(Report::locations(&response.results))
+}
+
+async fn now(app: &App, args: &NowArgs) -> Result<Report> {
+    let location = resolve(app, args.location.as_deref()).await?;
+    let configured = app.config.variables.current.as_deref();
+    let variables = variables(&args.options, configured, NOW_VARIABLES);
+    let request = forecast_request(&location, &args.options).current(&variables);
+    let forecast = fetch_forecast(app, "now", request, &args.options, &args.diff).await?;
+    Ok(Report::current(&forecast, &variables)?.place(&location))
+}
+
+async fn hourly(app: &App, args: &ForecastArgs) -> Result<Report> {
+    let location = resolve(app, args.location.as_deref()).await?;
+    let configured = app.config.variables.hourly.as_deref();
+    let variables = variables(&args.options, configured, HOURLY_VARIABLES);
+    let request = forecast_request(&location, &args.options)
+        .hourly(&variables)
+        .forecast_days(args.days.unwrap_or(2));
+    let forecast = fetch_forecast(app, "hourly", request, &args.options, &args.diff).await?;
+    Ok(Report::hourly(&forecast, &variables)?.place(&location))
+}
+
+async fn daily(app: &App, args: &ForecastArgs) -> Result<Report> {
+    let location = resolve(app, args.location.as_deref()).await?;
+    let configured = app.config.variables.daily.as_deref();
+    let variables = variables(&args.options, configured, DAILY_VARIABLES);
+    let mut request = forecast_request(&location, &args.options).daily(&variables);
+    if let Some(days) = args.days {
+        request = request.forecast_days(days);
+    }
+    let forecast = fetch_forecast(app, "daily", request, &args.options, &args.diff).await?;
+    Ok(Report::daily(&forecast, &variables)?.place(&location))
+}
+
+async fn history_range(app: &App, args: &HistoryArgs) -> Result<Report> {
+    if args.from > args.to {
+        bail!("--from {} is after --to {}", args.from, args.to);
+    }
+    let location = resolve(app, args.location.as_deref()).await?;
+    let configured = app.config.variables.daily.as_deref();
+    let variables = variables(&args.options, configured, HISTORY_VARIABLES);
+    let request = forecast_request(&location, &args.options)
+        .daily(&variables)
+        .date_range(args.from, args.to);
+    let mut history = app.client.archive().get(request).await?;
+    history.convert_to(units(app, &args.options));
+    Ok(Report::daily(&history, &variables)?.place(&location))
+}
+
+/// Sun and moon for the next days, computed offline; only place names need
+/// the geocoding API
+async fn astro_report(app: &App, args: &AstroArgs) -> Result<Report> {
+    let location = resolve(app, args.location.as_deref()).await?;
+    let coordinates = Coordinates::new(location.latitude, location.longitude);
+    let date = args
+        .date
+        .unwrap_or_else(|| chrono::Local::now().date_naive());
+    Ok(Report::astro(coordinates, date, args.days).place(&location))
+}
+
+/// `favorite add|list|remove|rename`, each showing the resulting favorites
+async fn favorite(app: &mut App, command: &FavoriteCommand) -> Result<Report> {
+    let path = Favorites::path().context("cannot locate the favorites file: HOME is not set")?;
+    match command {
+        FavoriteCommand::Add {
+            alias,
+            location,
+            force,
+        } => {
+            if !force && app.favorites.get(alias).is_some() {
+                bail!(
+                    "favorite '{}' already exists; use --force to replace it",
+                    alias
                 );
-                println!("Country: {:?}", location.country);
-                println!("Timezone: {:?}\n", location.timezone);
-
-                // Example 2: Get weather forecast for the found location
-                println!("=== Forecast Example ===");
-                let forecast_client = ForecastClient::new();
-
-                match forecast_client
-                    .get(
-                        ForecastRequest::new(location.latitude, location.longitude)
-                            .hourly(&["temperature_2m", "precipitation"])
-                            .daily(&["temperature_2m_max", "temperature_2m_min"])
-                            .timezone("auto")
-                            .temperature_unit(
-                                tiny_weather::client::types::TemperatureUnit::Fahrenheit,
-                            )
-                            .forecast_days(3),
-                    )
-                    .await
-                {
-                    Ok(forecast) => {
-                        println!("Weather forecast for {}", location.name);
-                        println!("Timezone: {}", forecast.timezone);
-
-                        if let Some(daily) = forecast.daily {
-                            println!("\nDaily forecast:");
-                            for (i, date) in daily.time.iter().enumerate() {
-                                let temp_max = daily
-                                    .variables
-                                    .get("temperature_2m_max")
-                                    .and_then(|v| v.get(i))
-                                    .and_then(|v| *v);
-                                let temp_min = daily
-                                    .variables
-                                    .get("temperature_2m_min")
-                                    .and_then(|v| v.get(i))
-                                    .and_then(|v| *v);
-
-                                println!(
-                                    "  {}: {:.1}°C - {:.1}°C",
-                                    date,
-                                    temp_min.unwrap_or(0.0),
-                                    temp_max.unwrap_or(0.0)
-                                );
-                            }
-                        }
-                    }
-                    Err(e) => eprintln!("Forecast error: {}", e),
-                }
             }
+            let location = resolve(app, Some(location)).await?;
+            app.favorites.insert(alias, location)?;
+        }
+        FavoriteCommand::List => {}
+        FavoriteCommand::Remove { alias } => {
+            app.favorites.remove(alias)?;
         }
-        Err(e) => eprintln!("Geocoding error: {}", e),
+        FavoriteCommand::Rename { from, to } => app.favorites.rename(from, to)?,
+    }
+    if !matches!(command, FavoriteCommand::List) {
+        app.favorites.write(&path)?;
     }
+    Ok(Report::favorites(&app.favorites))
+}
 
+/// Resolve a location argument, or the configured location
+///
+/// Aliases and coordinates in any supported notation resolve locally; postal
+/// codes and place names are geocoded, with the matches cached for
+/// `cache.geocoding_ttl`. Among several matches the user picks one in a
+/// terminal, and that choice is remembered for the query; otherwise the best
+/// ranked match is used and named on stderr.
+async fn resolve(app: &App, input: Option<&str>) -> Result<Location> {
+    let Some(input) = input.or(app.config.location.as_deref()) else {
+        bail!("no location given; pass one or run `tiny_weather config set location <name>`");
+    };
+    let query = LocationQuery::parse(input, &app.favorites)?;
+    if let Some(location) = query.location() {
+        return Ok(location);
+    }
+    let language = app.config.language.as_deref();
+    let key = format!("{}|{}", language.unwrap_or_default(), query);
+    let choices = cache_dir().map(|dir| DiskCache::new(dir.join("choices")));
+    let remembered = choices
+        .as_ref()
+        .filter(|_| !app.choose)
+        .and_then(|choices| choices.get(&key, Duration::MAX));
+    if let Some(location) = remembered {
+        return Ok(location);
+    }
+
+    let cache = cache_dir().map(|dir| DiskCache::new(dir.join("geocoding")));
+    let ttl = app.config.cache.geocoding_ttl();
+    let cached = cache.as_ref().and_then(|cache| cache.get(&key, ttl));
+    let mut candidates: Vec<Location> = match cached {
+        Some(candidates) => candidates,
+        None => {
+            let candidates = query
+                .candidates(app.client.geocoding(), language)
+                .await
+                .with_context(|| format!("no location found for '{}'", query))?;
+            if let Some(cache) = cache.filter(|_| !ttl.is_zero())
+                && let Err(e) = cache.put(&key, &candidates)
+            {
+                eprintln!("Could not cache locations: {}", e);
+            }
+            candidates
+        }
+    };
+    if candidates.is_empty() {
+        bail!("no location found for '{}'", query);
+    }
+    if candidates.len() == 1 {
+        return Ok(candidates.remove(0));
+    }
+
+    // The picker reads keys from stdin and draws on stderr
+    if std::io::stdin().is_terminal()
+        && std::io::stdout().is_terminal()
+        && std::io::stderr().is_terminal()
+    {
+        let labels: Vec<String> = candidates.iter().map(label).collect();
+        let prompt = format!("Several places match '{}':", query);
+        let Some(index) = picker::pick(&prompt, &labels)? else {
+            bail!("no location chosen for '{}'", query);
+        };
+        let location = candidates.swap_remove(index);
+        if let Some(choices) = choices
+            && let Err(e) = choices.put(&key, &location)
+        {
+            eprintln!("Could not remember the choice: {}", e);
+        }
+        return Ok(location);
+    }
+
+    eprintln!(
+        "Using {} for '{}', the best of {} matches; run in a terminal to choose another",
+        label(&candidates[0]),
+        query,
+        candidates.len()
+    );
+    Ok(candidates.remove(0))
+}
+
+/// One line describing a geocoding match
+fn label(location: &Location) -> String {
+    let mut label = [
+        Some(&location.name),
+        location.admin1.as_ref(),
+        location.country.as_ref(),
+    ]
+    .into_iter()
+    .flatten()
+    .map(String::as_str)
+    .collect::<Vec<_>>()
+    .join(", ");
+    if let Some(population) = location.population {
+        label.push_str(&format!(" · pop. {}", population));
+    }
+    label.push_str(&format!(
+        " · {:.4}, {:.4}",
+        location.latitude, location.longitude
+    ));
+    label
+}
+
+/// Requested variables, or the configured ones, or the command's defaults
+fn variables<'a>(
+    options: &'a RequestOptions,
+    configured: Option<&'a [String]>,
+    defaults: &[&'a str],
+) -> Vec<&'a str> {
+    if !options.variables.is_empty() {
+        options.variables.iter().map(String::as_str).collect()
+    } else if let Some(configured) = configured {
+        configured.iter().map(String::as_str).collect()
+    } else {
+        defaults.to_vec()
+    }
+}
+
+/// Requested unit system, or the configured one
+fn units(app: &App, options: &RequestOptions) -> UnitSystem {
+    options.units.or(app.config.units).unwrap_or_default()
+}
+
+fn forecast_request(location: &Location, options: &RequestOptions) -> ForecastRequest {
+    ForecastRequest::new(location.latitude, location.longitude)
+        .timezone("auto")
+        .models(&options.model)
+}
+
+/// Fetch a forecast in the requested unit system, comparing with the saved one for `--diff`
+///
+/// Responses are reused for `cache.forecast_ttl`, except with `--diff`. The changes
+/// go to stdout above a table, and to stderr for machine-readable output. Each
+/// command and location keeps its own previous forecast.
+async fn fetch_forecast(
+    app: &App,
+    command: &str,
+    request: ForecastRequest,
+    options: &RequestOptions,
+    diff: &DiffOptions,
+) -> Result<ForecastResponse> {
+    let previous = cache_dir().map(|dir| DiskCache::new(dir.join("previous")));
+    let previous_key = format!(
+        "{} {:.4},{:.4}",
+        command, request.latitude, request.longitude
+    );
+    let cache = cache_dir().map(|dir| DiskCache::new(dir.join("forecasts")));
+    let key = format!("{:?}", request);
+    let ttl = app.config.cache.forecast_ttl();
+    let cached = cache
+        .as_ref()
+        .filter(|_| !diff.enabled)
+        .and_then(|cache| cache.get::<ForecastResponse>(&key, ttl));
+    let mut forecast = match cached {
+        Some(forecast) => forecast,
+        None => {
+            let forecast = app.client.forecast().get(request).await?;
+            if let Some(cache) = cache.filter(|_| !ttl.is_zero())
+                && let Err(e) = cache.put(&key, &forecast)
+            {
+                eprintln!("Could not cache forecast: {}", e);
+            }
+            forecast
+        }
+    };
+    forecast.convert_to(units(app, options));
+
+    if diff.enabled {
+        let saved = previous
+            .as_ref()
+            .and_then(|previous| previous.get(&previous_key, Duration::MAX));
+        let thresholds = diff
+            .threshold
+            .iter()
+            .fold(Thresholds::default(), |thresholds, (variable, change)| {
+                thresholds.variable(variable, *change)
+            });
+        let result = if app.output == OutputFormat::Table {
+            print_diff(saved, &forecast, &thresholds, &mut std::io::stdout())
+        } else {
+            print_diff(saved, &forecast, &thresholds, &mut std::io::stderr())
+        };
+        result?;
+    }
+    if let Some(previous) = previous
+        && let Err(e) = previous.put(&previous_key, &forecast)
+    {
+        eprintln!("Could not cache forecast: {}", e);
+    }
+    Ok(forecast)
+}
+
+/// `config path|get|set|edit`
+fn configure(command: &ConfigCommand) -> Result<()> {
+    let path =
+        TinyWeatherConfig::path().context("cannot locate the config file: HOME is not set")?;
+    match command {
+        ConfigCommand::Path => println!("{}", path.display()),
+        ConfigCommand::Get { key: Some(key) } => {
+            if let Some(value) = TinyWeatherConfig::load()?.get(key)? {
+                println!("{}", value);
+            }
+        }
+        ConfigCommand::Get { key: None } => {
+            let loaded = TinyWeatherConfig::load()?;
+            for key in config::KEYS {
+                let Some(mut value) = loaded.get(key)? else {
+                    continue;
+                };
+                // Secrets only show with `config get <key>`
+                if config::SECRET_KEYS.contains(&key) {
+                    value = "***".to_string();
+                }
+                let var = config::env_var(key);
+                if std::env::var_os(&var).is_some() {
+                    println!("{} = {}  (from {})", key, value, var);
+                } else {
+                    println!("{} = {}", key, value);
+                }
+            }
+        }
+        ConfigCommand::Set { key, value } => {
+            // Only the file is rewritten; environment overrides stay out of it
+            let mut saved = TinyWeatherConfig::read(&path)?;
+            saved.set(key, value.as_deref())?;
+            saved.write(&path)?;
+        }
+        ConfigCommand::Edit => {
+            if !path.exists() {
+                TinyWeatherConfig::default().write(&path)?;
+            }
+            let editor = std::env::var("VISUAL")
+                .or_else(|_| std::env::var("EDITOR"))
+                .unwrap_or_else(|_| "vi".to_string());
+            let mut words = editor.split_whitespace();
+            let program = words.next().context("$VISUAL or $EDITOR is empty")?;
+            let status = std::process::Command::new(program)
+                .args(words)
+                .arg(&path)
+                .status()
+                .with_context(|| format!("could not run '{}'", editor))?;
+            if !status.success() {
+                bail!("'{}' exited with {}", editor, status);
+            }
+            TinyWeatherConfig::read(&path)
+                .with_context(|| format!("{} is not a valid config", path.display()))?;
+        }
+    }
     Ok(())
 }
+
+/// Directory of cached locations and forecasts
+fn cache_dir() -> Option<PathBuf> {
+    let cache = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(cache.join("tiny_weather"))
+}
+
+/// Print changes since the `previous` forecast above the given thresholds
+fn print_diff(
+    previous: Option<ForecastResponse>,
+    forecast: &ForecastResponse,
+    thresholds: &Thresholds,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    let Some(previous) = previous else {
+        return writeln!(out, "No cached forecast to compare with\n");
+    };
+
+    match diff::diff(&previous, forecast) {
+        Ok(diff) => {
+            let changes = diff.changes(thresholds);
+            writeln!(out, "Changes since the previous forecast:")?;
+            if changes.is_empty() {
+                writeln!(out, "  none")?;
+            }
+            for change in changes {
+                writeln!(out, "  {}", change)?;
+            }
+            writeln!(out)
+        }
+        Err(e) => {
+            eprintln!("Could not compare with the previous forecast: {}", e);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,918 @@
+//! Machine-readable and tabular output of forecasts and locations
+//!
+//! Every CLI result becomes a [`Report`]: what it is, the place it is for
+//! and one record per time step (or location), keyed by column name. A report
+//! renders as a human-readable table or as JSON, NDJSON, CSV or YAML.
+//!
+//! The serialized layout is a schema of its own rather than the API response,
+//! so scripts keep working when the API or the table layout changes. Within a
+//! [`SCHEMA_VERSION`] fields and columns are only ever added; renaming or
+//! removing anything bumps the version. Values are never formatted for
+//! humans: weather codes stay WMO numbers and times stay ISO 8601 local times
+//! (with the UTC offset in `grid`).
+//!
+//! ```no_run
+//! # async fn run() -> tiny_weather::client::Result<()> {
+//! use tiny_weather::client::ForecastClient;
+//! use tiny_weather::client::types::ForecastRequest;
+//! use tiny_weather::output::{OutputFormat, Report};
+//!
+//! let request = ForecastRequest::new(52.52, 13.41).hourly(&["temperature_2m"]);
+//! let forecast = ForecastClient::new().get(request).await?;
+//!
+//! let report = Report::hourly(&forecast, &["temperature_2m"])?;
+//! report.write(OutputFormat::Ndjson, &mut std::io::stdout())?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::astro;
+use crate::client::error::{OpenMeteoError, Result};
+use crate::client::types::common::Coordinates;
+use crate::client::types::forecast::ForecastResponse;
+use crate::client::types::geocoding::Location;
+use crate::client::types::model::WeatherModel;
+use crate::client::types::weather_code::{WEATHER_CODE, WeatherCode};
+use crate::favorites::Favorites;
+use crate::frame::TIME_COLUMN;
+
+/// Version of the serialized report layout
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// How a report is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Aligned columns for reading in a terminal (not a stable format)
+    #[default]
+    Table,
+    /// One pretty-printed JSON document
+    Json,
+    /// One JSON object per record and line
+    Ndjson,
+    /// Header row of column names, then one row per record
+    Csv,
+    /// The JSON document as YAML
+    Yaml,
+}
+
+impl OutputFormat {
+    /// Every output format
+    pub const ALL: [OutputFormat; 5] =
+        [Self::Table, Self::Json, Self::Ndjson, Self::Csv, Self::Yaml];
+
+    /// Lowercase name, as used on the command line
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Table => "table",
+            Self::Json => "json",
+            Self::Ndjson => "ndjson",
+            Self::Csv => "csv",
+            Self::Yaml => "yaml",
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(name: &str) -> std::result::Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|format| format.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("unknown output format: {}", name))
+    }
+}
+
+/// How weather codes are shown in tables
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    /// Descriptions only
+    #[default]
+    Plain,
+    /// Descriptions after a Unicode emoji
+    Emoji,
+    /// Descriptions after a Nerd Font glyph
+    Nerd,
+}
+
+impl Theme {
+    /// Every theme
+    pub const ALL: [Theme; 3] = [Self::Plain, Self::Emoji, Self::Nerd];
+
+    /// Lowercase name, as used in configuration
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Plain => "plain",
+            Self::Emoji => "emoji",
+            Self::Nerd => "nerd",
+        }
+    }
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(name: &str) -> std::result::Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|theme| theme.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("unknown theme: {}", name))
+    }
+}
+
+/// What the records of a report are
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportKind {
+    /// Geocoding results, one record per location
+    Locations,
+    /// Current conditions, a single record
+    Current,
+    /// One record per hour
+    Hourly,
+    /// One record per day
+    Daily,
+    /// Sun and moon, one record per day, computed offline with times in UTC
+    Astro,
+}
+
+/// The place a report was requested for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Place {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin1: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country_code: Option<String>,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl From<&Location> for Place {
+    fn from(location: &Location) -> Self {
+        Self {
+            name: Some(location.name.clone()),
+            admin1: location.admin1.clone(),
+            country: location.country.clone(),
+            country_code: location.country_code.clone(),
+            latitude: location.latitude,
+            longitude: location.longitude,
+        }
+    }
+}
+
+/// The model grid cell a forecast was computed for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GridPoint {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub elevation: f64,
+    pub timezone: String,
+    pub utc_offset_seconds: i32,
+    /// Distance from the requested place (km), if the place is known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distance_km: Option<f64>,
+}
+
+/// Command result in the versioned output schema
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Report {
+    pub schema_version: u32,
+    pub kind: ReportKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub place: Option<Place>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grid: Option<GridPoint>,
+    /// Column names in display order
+    pub columns: Vec<String>,
+    /// Unit of each column that has one
+    #[serde(default)]
+    pub units: BTreeMap<String, String>,
+    /// Values by column name; a missing value is `null`
+    pub records: Vec<BTreeMap<String, Value>>,
+    #[serde(skip)]
+    theme: Theme,
+    #[serde(skip)]
+    language: Option<String>,
+}
+
+impl Report {
+    fn new(kind: ReportKind, columns: Vec<String>) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            kind,
+            place: None,
+            grid: None,
+            columns,
+            units: BTreeMap::new(),
+            records: Vec::new(),
+            theme: Theme::default(),
+            language: None,
+        }
+    }
+
+    /// Geocoding results
+    pub fn locations(locations: &[Location]) -> Self {
+        let columns = [
+            "id",
+            "name",
+            "admin1",
+            "country",
+            "country_code",
+            "latitude",
+            "longitude",
+            "elevation",
+            "population",
+            "timezone",
+        ];
+        let mut report = Self::new(
+            ReportKind::Locations,
+            columns.iter().map(|c| c.to_string()).collect(),
+        );
+        report.records = locations
+            .iter()
+            .map(|location| {
+                let Value::Object(fields) = serde_json::json!({
+                    "id": location.id,
+                    "name": location.name,
+                    "admin1": location.admin1,
+                    "country": location.country,
+                    "country_code": location.country_code,
+                    "latitude": location.latitude,
+                    "longitude": location.longitude,
+                    "elevation": location.elevation,
+                    "population": location.population,
+                    "timezone": location.timezone,
+                }) else {
+                    unreachable!("a JSON object literal is an object")
+                };
+                fields.into_iter().collect()
+            })
+            .collect();
+        report
+    }
+
+    /// Saved locations with their aliases
+    pub fn favorites(favorites: &Favorites) -> Self {
+        let (aliases, locations): (Vec<&str>, Vec<Location>) = favorites
+            .iter()
+            .map(|(alias, location)| (alias, location.clone()))
+            .unzip();
+        let mut report = Self::locations(&locations);
+        report.columns.insert(0, "alias".to_string());
+        for (record, alias) in report.records.iter_mut().zip(aliases) {
+            record.insert("alias".to_string(), Value::from(alias));
+        }
+        report
+    }
+
+    /// Current conditions of the requested variables
+    pub fn current(forecast: &ForecastResponse, variables: &[&str]) -> Result<Self> {
+        let current = forecast
+            .current
+            .as_ref()
+            .ok_or_else(|| missing("current"))?;
+        let names = matching(&current.variables, variables);
+
+        let mut record =
+            BTreeMap::from([(TIME_COLUMN.to_string(), Value::from(current.time.as_str()))]);
+        for name in &names {
+            record.insert(name.to_string(), number(name, current.variables[*name]));
+        }
+
+        let mut report = Self::forecast(ReportKind::Current, forecast, &names);
+        report.units = units(forecast.current_units.as_ref(), &names);
+        report.records.push(record);
+        Ok(report)
+    }
+
+    /// Hourly series of the requested variables
+    pub fn hourly(forecast: &ForecastResponse, variables: &[&str]) -> Result<Self> {
+        let hourly = forecast.hourly.as_ref().ok_or_else(|| missing("hourly"))?;
+        let names = matching(&hourly.variables, variables);
+
+        let mut report = Self::forecast(ReportKind::Hourly, forecast, &names);
+        report.units = units(forecast.hourly_units.as_ref(), &names);
+        report.records = hourly
+            .time
+            .iter()
+            .enumerate()
+            .map(|(i, time)| {
+                let mut record =
+                    BTreeMap::from([(TIME_COLUMN.to_string(), Value::from(time.as_str()))]);
+                for name in &names {
+                    let value = hourly.variables[*name].get(i).copied().flatten();
+                    record.insert(name.to_string(), number(name, value));
+                }
+                record
+            })
+            .collect();
+        Ok(report)
+    }
+
+    /// Daily series of the requested variables, including times such as sunrise
+    pub fn daily(forecast: &ForecastResponse, variables: &[&str]) -> Result<Self> {
+        let daily = forecast.daily.as_ref().ok_or_else(|| missing("daily"))?;
+        let numbers = matching(&daily.variables, variables);
+        let times = matching(&daily.time_variables, variables);
+        // Keep the requested order across both kinds of series
+        let mut names: Vec<&str> = numbers.iter().chain(&times).copied().collect();
+        names.sort_by_key(|name| {
+            variables
+                .iter()
+                .position(|variable| matches(name, variable))
+                .unwrap_or(usize::MAX)
+        });
+
+        let mut report = Self::forecast(ReportKind::Daily, forecast, &names);
+        report.units = units(forecast.daily_units.as_ref(), &numbers);
+        report.records = daily
+            .time
+            .iter()
+            .enumerate()
+            .map(|(i, date)| {
+                let mut record =
+                    BTreeMap::from([(TIME_COLUMN.to_string(), Value::from(date.as_str()))]);
+                for name in &numbers {
+                    let value = daily.variables[*name].get(i).copied().flatten();
+                    record.insert(name.to_string(), number(name, value));
+                }
+                for name in &times {
+                    let value = daily.time_variables[*name].get(i).cloned().flatten();
+                    record.insert(name.to_string(), value.map_or(Value::Null, Value::from));
+                }
+                record
+            })
+            .collect();
+        Ok(report)
+    }
+
+    /// Sun events, the sun's highest elevation and the moon phase for `days`
+    /// days from `date`
+    ///
+    /// Nothing is requested from the API, so there is no grid point and no
+    /// local timezone: times are UTC, written with a `Z` suffix.
+    pub fn astro(coordinates: Coordinates, date: NaiveDate, days: u16) -> Self {
+        let columns = [
+            TIME_COLUMN,
+            "sunrise",
+            "sunset",
+            "solar_noon",
+            "civil_dawn",
+            "civil_dusk",
+            "daylight_duration",
+            "max_solar_elevation",
+            "moon_phase",
+            "moon_illumination",
+        ];
+        let mut report = Self::new(
+            ReportKind::Astro,
+            columns.iter().map(|c| c.to_string()).collect(),
+        );
+        report.units = BTreeMap::from([
+            ("daylight_duration".to_string(), "s".to_string()),
+            ("max_solar_elevation".to_string(), "°".to_string()),
+            ("moon_illumination".to_string(), "%".to_string()),
+        ]);
+        let time = |time: Option<DateTime<Utc>>| {
+            time.map_or(Value::Null, |time| {
+                Value::from(time.format("%Y-%m-%dT%H:%MZ").to_string())
+            })
+        };
+        report.records = date
+            .iter_days()
+            .take(days.into())
+            .map(|date| {
+                let sun = astro::sun_times(coordinates, date);
+                let noon = astro::solar_position(coordinates, sun.solar_noon);
+                let moon = astro::moon_phase(sun.solar_noon);
+                BTreeMap::from([
+                    (TIME_COLUMN.to_string(), Value::from(date.to_string())),
+                    ("sunrise".to_string(), time(sun.sunrise)),
+                    ("sunset".to_string(), time(sun.sunset)),
+                    ("solar_noon".to_string(), time(Some(sun.solar_noon))),
+                    ("civil_dawn".to_string(), time(sun.civil_dawn)),
+                    ("civil_dusk".to_string(), time(sun.civil_dusk)),
+                    (
+                        "daylight_duration".to_string(),
+                        Value::from(sun.day_length.num_seconds()),
+                    ),
+                    (
+                        "max_solar_elevation".to_string(),
+                        Value::from(noon.elevation),
+                    ),
+                    (
+                        "moon_phase".to_string(),
+                        Value::from(moon.name().description()),
+                    ),
+                    (
+                        "moon_illumination".to_string(),
+                        Value::from(moon.illumination * 100.0),
+                    ),
+                ])
+            })
+            .collect();
+        report
+    }
+
+    fn forecast(kind: ReportKind, forecast: &ForecastResponse, names: &[&str]) -> Self {
+        let columns = std::iter::once(TIME_COLUMN)
+            .chain(names.iter().copied())
+            .map(str::to_string)
+            .collect();
+        let mut report = Self::new(kind, columns);
+        report.grid = Some(GridPoint {
+            latitude: forecast.latitude,
+            longitude: forecast.longitude,
+            elevation: forecast.elevation,
+            timezone: forecast.timezone.clone(),
+            utc_offset_seconds: forecast.utc_offset_seconds,
+            distance_km: None,
+        });
+        report
+    }
+
+    /// Set the place the report was requested for
+    pub fn place(mut self, place: impl Into<Place>) -> Self {
+        let place = place.into();
+        if let Some(grid) = &mut self.grid {
+            let requested = Coordinates::new(place.latitude, place.longitude);
+            let distance = requested.distance_km(&Coordinates::new(grid.latitude, grid.longitude));
+            // Kilometers with meter precision are plenty, and stable across platforms
+            grid.distance_km = Some((distance * 1000.0).round() / 1000.0);
+        }
+        self.place = Some(place);
+        self
+    }
+
+    /// Show weather codes in tables with this theme
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Describe weather codes in tables in this language (ISO 639-1, default English)
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Write the report in `format`
+    pub fn write(&self, format: OutputFormat, writer: &mut impl Write) -> Result<()> {
+        match format {
+            OutputFormat::Table => self.write_table(writer)?,
+            OutputFormat::Json => {
+                serde_json::to_writer_pretty(&mut *writer, self).map_err(export)?;
+                writeln!(writer)?;
+            }
+            OutputFormat::Ndjson => {
+                for record in &self.records {
+                    let mut line = record.clone();
+                    line.insert(
+                        "schema_version".to_string(),
+                        Value::from(self.schema_version),
+                    );
+                    line.insert(
+                        "kind".to_string(),
+                        serde_json::to_value(self.kind).map_err(export)?,
+                    );
+                    serde_json::to_writer(&mut *writer, &line).map_err(export)?;
+                    writeln!(writer)?;
+                }
+            }
+            OutputFormat::Csv => {
+                let mut csv = csv::Writer::from_writer(writer);
+                csv.write_record(&self.columns).map_err(export)?;
+                for record in &self.records {
+                    csv.write_record(self.columns.iter().map(|column| match record.get(column) {
+                        None | Some(Value::Null) => String::new(),
+                        Some(Value::String(text)) => text.clone(),
+                        Some(value) => value.to_string(),
+                    }))
+                    .map_err(export)?;
+                }
+                csv.flush()?;
+            }
+            OutputFormat::Yaml => serde_norway::to_writer(writer, self).map_err(export)?,
+        }
+        Ok(())
+    }
+
+    fn write_table(&self, writer: &mut impl Write) -> Result<()> {
+        if let Some(heading) = self.heading() {
+            writeln!(writer, "{}\n", heading)?;
+        }
+
+        if self.kind == ReportKind::Current {
+            let record = self.records.first();
+            writeln!(
+                writer,
+                "Current conditions at {}",
+                self.cell(TIME_COLUMN, record)
+            )?;
+            for column in self.columns.iter().filter(|c| *c != TIME_COLUMN) {
+                let value = self.cell(column, record);
+                let unit = match self.units.get(column) {
+                    Some(unit) if !is_weather_code(column) => unit.as_str(),
+                    _ => "",
+                };
+                let value = format!("{} {}", value, unit);
+                writeln!(writer, "  {:<32} {}", column, value.trim_end())?;
+            }
+            return Ok(());
+        }
+
+        let headers: Vec<String> = self
+            .columns
+            .iter()
+            .map(|column| match self.units.get(column) {
+                Some(unit) if !is_weather_code(column) => format!("{} ({})", column, unit),
+                _ => column.clone(),
+            })
+            .collect();
+        let rows: Vec<Vec<String>> = self
+            .records
+            .iter()
+            .map(|record| {
+                self.columns
+                    .iter()
+                    .map(|column| self.cell(column, Some(record)))
+                    .collect()
+            })
+            .collect();
+
+        let widths: Vec<usize> = (0..headers.len())
+            .map(|i| {
+                rows.iter()
+                    .map(|row| &row[i])
+                    .chain(std::iter::once(&headers[i]))
+                    .map(|cell| cell.chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        for row in std::iter::once(&headers).chain(&rows) {
+            let line: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect();
+            writeln!(writer, "{}", line.join("  ").trim_end())?;
+        }
+        Ok(())
+    }
+
+    /// First line of a table: the place, the grid point and its timezone
+    ///
+    /// Reports without a grid point (astro) show the place's coordinates in UTC.
+    fn heading(&self) -> Option<String> {
+        let (latitude, longitude, timezone) = match (&self.grid, &self.place) {
+            (Some(grid), _) => (grid.latitude, grid.longitude, grid.timezone.as_str()),
+            (None, Some(place)) => (place.latitude, place.longitude, "UTC"),
+            (None, None) => return None,
+        };
+        let place = self.place.as_ref().map(|place| {
+            [&place.name, &place.admin1, &place.country]
+                .into_iter()
+                .flatten()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(", ")
+        });
+        let place = place.filter(|place| !place.is_empty());
+        Some(format!(
+            "{}({:.2}, {:.2}) · {}",
+            place.map(|place| format!("{} ", place)).unwrap_or_default(),
+            latitude,
+            longitude,
+            timezone
+        ))
+    }
+
+    /// A value of a record formatted for the table
+    fn cell(&self, column: &str, record: Option<&BTreeMap<String, Value>>) -> String {
+        match record.and_then(|record| record.get(column)) {
+            None | Some(Value::Null) => "-".to_string(),
+            Some(Value::String(text)) if column == TIME_COLUMN => text.replace('T', " "),
+            // Times of day such as sunrise; the date is already in the time column
+            Some(Value::String(text))
+                if matches!(self.kind, ReportKind::Daily | ReportKind::Astro) =>
+            {
+                text.split_once('T')
+                    .map_or(text.as_str(), |(_, time)| time)
+                    .to_string()
+            }
+            Some(Value::String(text)) => text.clone(),
+            Some(Value::Number(number)) if self.kind != ReportKind::Locations => {
+                match number.as_f64() {
+                    Some(code) if is_weather_code(column) => match WeatherCode::from_value(code) {
+                        Some(code) => self.weather(code, record),
+                        None => number.to_string(),
+                    },
+                    Some(value) => format!("{:.1}", value),
+                    None => number.to_string(),
+                }
+            }
+            Some(value) => value.to_string(),
+        }
+    }
+}
+
+impl Report {
+    /// Weather code description, with the icon of the theme
+    fn weather(&self, code: WeatherCode, record: Option<&BTreeMap<String, Value>>) -> String {
+        let description = match &self.language {
+            Some(language) => code.description_in(language),
+            None => code.description(),
+        };
+        // Night icons need `is_day`; days and hours without it get day icons
+        let is_day = record
+            .and_then(|record| record.get("is_day"))
+            .and_then(Value::as_f64)
+            .is_none_or(|is_day| is_day != 0.0);
+        match self.theme {
+            Theme::Plain => description.to_string(),
+            Theme::Emoji => format!("{} {}", code.icon(is_day), description),
+            Theme::Nerd => format!("{} {}", code.nerd_icon(is_day), description),
+        }
+    }
+}
+
+/// Names of the requested variables in a response, in request order
+///
+/// A variable matches itself, or one column per model in multi-model responses.
+fn matching<'a, V>(series: &'a HashMap<String, V>, variables: &[&str]) -> Vec<&'a str> {
+    variables
+        .iter()
+        .flat_map(|variable| {
+            let mut names: Vec<&str> = series
+                .keys()
+                .map(String::as_str)
+                .filter(|name| matches(name, variable))
+                .collect();
+            names.sort_unstable();
+            names
+        })
+        .collect()
+}
+
+fn matches(name: &str, variable: &str) -> bool {
+    name == variable || WeatherModel::split_variable(name).is_some_and(|(base, _)| base == variable)
+}
+
+fn is_weather_code(column: &str) -> bool {
+    matches(column, WEATHER_CODE)
+}
+
+fn units(units: Option<&HashMap<String, String>>, names: &[&str]) -> BTreeMap<String, String> {
+    names
+        .iter()
+        .filter_map(|name| {
+            let unit = units?.get(*name).filter(|unit| !unit.is_empty())?;
+            Some((name.to_string(), unit.clone()))
+        })
+        .collect()
+}
+
+/// A value of a column; weather codes are WMO numbers, so integers
+fn number(column: &str, value: Option<f64>) -> Value {
+    match value {
+        Some(code) if is_weather_code(column) && code.fract() == 0.0 => Value::from(code as i64),
+        Some(value) => Value::from(value),
+        None => Value::Null,
+    }
+}
+
+fn missing(section: &str) -> OpenMeteoError {
+    OpenMeteoError::InvalidParameter(format!("the response has no {} data", section))
+}
+
+fn export(error: impl fmt::Display) -> OpenMeteoError {
+    OpenMeteoError::Export(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::types::forecast::fixtures;
+
+    fn forecast() -> ForecastResponse {
+        fixtures::forecast(serde_json::json!({
+            "longitude": 13.419998,
+            "utc_offset_seconds": 3600,
+            "timezone": "Europe/Berlin",
+            "timezone_abbreviation": "CET",
+            "hourly": {
+                "time": ["2024-01-01T00:00", "2024-01-01T01:00"],
+                "temperature_2m": [1.25, null],
+                "weather_code": [3.0, 61.0]
+            },
+            "hourly_units": {"temperature_2m": "°C", "weather_code": "wmo code"},
+            "daily": {
+                "time": ["2024-01-01"],
+                "temperature_2m_max_icon_d2": [4.0],
+                "temperature_2m_max_gfs_global": [5.0],
+                "sunrise": ["2024-01-01T08:17"]
+            },
+            "daily_units": {
+                "temperature_2m_max_icon_d2": "°C",
+                "temperature_2m_max_gfs_global": "°C",
+                "sunrise": "iso8601"
+            }
+        }))
+    }
+
+    fn render(report: &Report, format: OutputFormat) -> String {
+        let mut out = Vec::new();
+        report.write(format, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_output_format_names() {
+        for format in OutputFormat::ALL {
+            assert_eq!(format.to_string().parse::<OutputFormat>(), Ok(format));
+        }
+        assert!("xml".parse::<OutputFormat>().is_err());
+    }
+
+    #[test]
+    fn test_hourly_json_schema() {
+        let report = Report::hourly(&forecast(), &["temperature_2m", "weather_code"])
+            .unwrap()
+            .place(Place {
+                name: Some("Berlin".to_string()),
+                admin1: None,
+                country: Some("Germany".to_string()),
+                country_code: Some("DE".to_string()),
+                latitude: 52.52,
+                longitude: 13.41,
+            });
+
+        let json: Value = serde_json::from_str(&render(&report, OutputFormat::Json)).unwrap();
+        assert_eq!(json["schema_version"], 1);
+        assert_eq!(json["kind"], "hourly");
+        assert_eq!(json["place"]["name"], "Berlin");
+        assert_eq!(json["grid"]["utc_offset_seconds"], 3600);
+        assert_eq!(json["grid"]["distance_km"], 0.676);
+        assert_eq!(
+            json["columns"],
+            serde_json::json!(["time", "temperature_2m", "weather_code"])
+        );
+        assert_eq!(json["units"]["temperature_2m"], "°C");
+        assert_eq!(
+            json["records"][1],
+            serde_json::json!({"time": "2024-01-01T01:00", "temperature_2m": null, "weather_code": 61})
+        );
+        // WMO codes are integers, not 61.0
+        assert!(json["records"][1]["weather_code"].is_u64());
+        assert!(render(&report, OutputFormat::Csv).ends_with("2024-01-01T01:00,,61\n"));
+
+        // The document round-trips, e.g., for tools written against this crate
+        let parsed: Report = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, report);
+        let yaml = render(&report, OutputFormat::Yaml);
+        assert_eq!(serde_norway::from_str::<Report>(&yaml).unwrap(), report);
+    }
+
+    #[test]
+    fn test_ndjson_and_csv() {
+        let report = Report::hourly(&forecast(), &["temperature_2m"]).unwrap();
+
+        let ndjson = render(&report, OutputFormat::Ndjson);
+        let lines: Vec<Value> = ndjson
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["schema_version"], 1);
+        assert_eq!(lines[0]["kind"], "hourly");
+        assert_eq!(lines[0]["temperature_2m"], 1.25);
+
+        assert_eq!(
+            render(&report, OutputFormat::Csv),
+            "time,temperature_2m\n2024-01-01T00:00,1.25\n2024-01-01T01:00,\n"
+        );
+    }
+
+    #[test]
+    fn test_daily_models_and_times() {
+        let report = Report::daily(&forecast(), &["sunrise", "temperature_2m_max"]).unwrap();
+        assert_eq!(
+            report.columns,
+            vec![
+                "time",
+                "sunrise",
+                "temperature_2m_max_gfs_global",
+                "temperature_2m_max_icon_d2"
+            ]
+        );
+        assert!(!report.units.contains_key("sunrise"));
+
+        let table = render(&report, OutputFormat::Table);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], "(52.52, 13.42) · Europe/Berlin");
+        assert!(lines[2].starts_with("time        sunrise  temperature_2m_max_gfs_global (°C)"));
+        assert!(lines[3].starts_with("2024-01-01  08:17    5.0"));
+    }
+
+    #[test]
+    fn test_table_and_missing_section() {
+        let report = Report::hourly(&forecast(), &["weather_code", "temperature_2m"]).unwrap();
+        let table = render(&report, OutputFormat::Table);
+        assert!(table.contains("weather_code  temperature_2m (°C)"));
+        assert!(table.contains("2024-01-01 01:00  Slight rain   -"));
+
+        assert!(Report::current(&forecast(), &["temperature_2m"]).is_err());
+
+        let report = report.theme(Theme::Emoji).language("de");
+        assert!(
+            render(&report, OutputFormat::Table).contains("2024-01-01 01:00  🌧️ Leichter Regen")
+        );
+        // Table styling is not part of the serialized schema
+        assert!(!render(&report, OutputFormat::Json).contains("theme"));
+    }
+
+    #[test]
+    fn test_astro() {
+        let london = Coordinates::new(51.5074, -0.1278);
+        let date = NaiveDate::from_ymd_opt(2024, 6, 21).unwrap();
+        let report = Report::astro(london, date, 2).place(Place {
+            name: Some("London".to_string()),
+            admin1: None,
+            country: None,
+            country_code: None,
+            latitude: london.latitude,
+            longitude: london.longitude,
+        });
+
+        assert_eq!(report.kind, ReportKind::Astro);
+        assert_eq!(report.grid, None);
+        assert_eq!(report.records.len(), 2);
+        let record = &report.records[0];
+        assert_eq!(record[TIME_COLUMN], "2024-06-21");
+        let sunrise = record["sunrise"].as_str().unwrap();
+        assert!(sunrise.starts_with("2024-06-21T03:4") && sunrise.ends_with('Z'));
+        let elevation = record["max_solar_elevation"].as_f64().unwrap();
+        assert!((elevation - 61.9).abs() < 0.2, "{}", elevation);
+        assert_eq!(report.units["daylight_duration"], "s");
+
+        let table = render(&report, OutputFormat::Table);
+        assert!(table.starts_with("London (51.51, -0.13) · UTC\n"));
+        assert!(table.contains("2024-06-22  03:4"));
+    }
+
+    #[test]
+    fn test_locations() {
+        let location: Location = serde_json::from_value(serde_json::json!({
+            "id": 2950159,
+            "name": "Berlin",
+            "latitude": 52.52437,
+            "longitude": 13.41053,
+            "country": "Germany",
+            "population": 3426354
+        }))
+        .unwrap();
+        let report = Report::locations(std::slice::from_ref(&location));
+
+        assert_eq!(report.kind, ReportKind::Locations);
+        assert_eq!(report.records[0]["population"], 3426354);
+        assert_eq!(report.records[0]["admin1"], Value::Null);
+        let csv = render(&report, OutputFormat::Csv);
+        assert!(csv.ends_with("2950159,Berlin,,Germany,,52.52437,13.41053,,3426354,\n"));
+        let table = render(&report, OutputFormat::Table);
+        assert!(table.contains("52.52437"));
+
+        let mut favorites = Favorites::default();
+        favorites.insert("home", location).unwrap();
+        let report = Report::favorites(&favorites);
+        assert_eq!(report.columns[..2], ["alias", "id"]);
+        let csv = render(&report, OutputFormat::Csv);
+        assert!(csv.ends_with("home,2950159,Berlin,,Germany,,52.52437,13.41053,,3426354,\n"));
+    }
+}
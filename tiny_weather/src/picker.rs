@@ -0,0 +1,161 @@
+//! Interactive list picker for the terminal
+//!
+//! Draws on stderr so that stdout stays clean for reports. Arrow keys (or
+//! `j`/`k`) move, Enter picks, a digit picks that entry directly, and Esc, `q`
+//! or Ctrl-C cancel. Lines are cut to the terminal width, so that each entry
+//! takes one row and the list can be redrawn in place.
+
+use std::io::{self, Write};
+
+use crossterm::cursor::{Hide, MoveToColumn, MoveUp, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{self, Clear, ClearType};
+use crossterm::{execute, queue};
+
+/// Outcome of a key press
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Move(usize),
+    Pick(usize),
+    Cancel,
+    Ignore,
+}
+
+/// Let the user pick one of `items`, `None` if they cancel
+pub fn pick(prompt: &str, items: &[String]) -> io::Result<Option<usize>> {
+    if items.is_empty() {
+        return Ok(None);
+    }
+    let mut out = io::stderr();
+    let _raw = RawMode::enable()?;
+    execute!(out, Hide)?;
+
+    let mut selected = 0;
+    let result = loop {
+        draw(&mut out, prompt, items, selected)?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        match action(key, selected, items.len()) {
+            Action::Move(index) => selected = index,
+            Action::Pick(index) => break Some(index),
+            Action::Cancel => break None,
+            Action::Ignore => {}
+        }
+        // Back to the prompt line to redraw in place
+        queue!(out, MoveUp(items.len() as u16 + 1))?;
+    };
+
+    queue!(
+        out,
+        MoveUp(items.len() as u16 + 1),
+        MoveToColumn(0),
+        Clear(ClearType::FromCursorDown),
+        Show
+    )?;
+    out.flush()?;
+    Ok(result)
+}
+
+fn draw(out: &mut impl Write, prompt: &str, items: &[String], selected: usize) -> io::Result<()> {
+    // Stay clear of the last column, where some terminals wrap early
+    let width = terminal::size().map_or(80, |(columns, _)| columns as usize);
+    let width = width.saturating_sub(1).max(1);
+
+    queue!(out, MoveToColumn(0), Clear(ClearType::FromCursorDown))?;
+    // Raw mode needs explicit carriage returns
+    let prompt = format!("{} (↑/↓, Enter, Esc)", prompt);
+    write!(out, "{}\r\n", truncate(&prompt, width))?;
+    for (i, item) in items.iter().enumerate() {
+        let marker = if i == selected { '>' } else { ' ' };
+        let line = format!("{} {}. {}", marker, i + 1, item);
+        write!(out, "{}\r\n", truncate(&line, width))?;
+    }
+    out.flush()
+}
+
+/// `line` cut to `width` characters, ending in `…` when shortened
+fn truncate(line: &str, width: usize) -> String {
+    if line.chars().count() <= width {
+        return line.to_string();
+    }
+    let mut cut: String = line.chars().take(width.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+fn action(key: KeyEvent, selected: usize, len: usize) -> Action {
+    if key.kind != KeyEventKind::Press {
+        return Action::Ignore;
+    }
+    match key.code {
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Action::Cancel,
+        KeyCode::Up | KeyCode::Char('k') => {
+            Action::Move(selected.checked_sub(1).unwrap_or(len - 1))
+        }
+        KeyCode::Down | KeyCode::Char('j') => Action::Move((selected + 1) % len),
+        KeyCode::Home => Action::Move(0),
+        KeyCode::End => Action::Move(len - 1),
+        KeyCode::Enter => Action::Pick(selected),
+        KeyCode::Esc | KeyCode::Char('q') => Action::Cancel,
+        KeyCode::Char(c) => match c.to_digit(10) {
+            Some(digit @ 1..) if (digit as usize) <= len => Action::Pick(digit as usize - 1),
+            _ => Action::Ignore,
+        },
+        _ => Action::Ignore,
+    }
+}
+
+/// Raw terminal mode, restored when dropped (also on errors)
+struct RawMode;
+
+impl RawMode {
+    fn enable() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        Ok(Self)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+        let _ = execute!(io::stderr(), Show);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_picker_keys() {
+        assert_eq!(action(press(KeyCode::Down), 0, 3), Action::Move(1));
+        assert_eq!(action(press(KeyCode::Down), 2, 3), Action::Move(0));
+        assert_eq!(action(press(KeyCode::Char('k')), 0, 3), Action::Move(2));
+        assert_eq!(action(press(KeyCode::Enter), 1, 3), Action::Pick(1));
+        assert_eq!(action(press(KeyCode::Char('3')), 0, 3), Action::Pick(2));
+        assert_eq!(action(press(KeyCode::Char('4')), 0, 3), Action::Ignore);
+        assert_eq!(action(press(KeyCode::Char('0')), 0, 3), Action::Ignore);
+        assert_eq!(action(press(KeyCode::Esc), 0, 3), Action::Cancel);
+        assert_eq!(
+            action(
+                KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL),
+                0,
+                3
+            ),
+            Action::Cancel
+        );
+    }
+
+    #[test]
+    fn test_picker_truncate() {
+        assert_eq!(truncate("> 1. Berlin", 20), "> 1. Berlin");
+        assert_eq!(truncate("> 1. Berlin", 11), "> 1. Berlin");
+        assert_eq!(truncate("> 1. Frankfurt am Main", 10), "> 1. Fran…");
+        assert_eq!(truncate("> 1. Zürich", 8).chars().count(), 8);
+    }
+}
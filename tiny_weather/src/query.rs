@@ -0,0 +1,607 @@
+//! Location arguments of the command-line tool
+//!
+//! A [`LocationQuery`] is what the user typed for a place: a favorite alias,
+//! decimal degrees (`52.52,13.41`), degrees/minutes/seconds
+//! (`52°31'N 13°24'E`), a geohash (`u33dc0`, or `geohash:gcpvj` for hashes
+//! without a digit), a full plus code (`9F4MGCH7+2X`), a postal code with its
+//! country (`10115 DE`) or free text. Everything but postal codes and free text
+//! is decoded locally, without a geocoding call.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::client::GeocodingClient;
+use crate::client::error::{OpenMeteoError, Result};
+use crate::client::types::{Coordinates, GeocodingRequest, Location};
+use crate::favorites::Favorites;
+
+/// Alphabet of geohash digits
+const GEOHASH_ALPHABET: &str = "0123456789bcdefghjkmnpqrstuvwxyz";
+
+/// Alphabet of Open Location Code digits
+const PLUS_CODE_ALPHABET: &str = "23456789CFGHJMPQRVWX";
+
+/// Geocoding matches fetched to choose from
+pub const MAX_CANDIDATES: u8 = 10;
+
+/// Degrees per plus code digit pair, from the first pair to the fifth
+const PLUS_CODE_PAIR_RESOLUTIONS: [f64; 5] = [20.0, 1.0, 0.05, 0.0025, 0.000125];
+
+/// A parsed location argument
+#[derive(Debug, Clone)]
+pub enum LocationQuery {
+    /// A saved favorite
+    Favorite {
+        alias: String,
+        location: Box<Location>,
+    },
+    /// Decimal degrees, latitude first
+    Decimal(Coordinates),
+    /// Degrees, minutes and seconds with hemisphere letters
+    Dms(Coordinates),
+    /// Center of a geohash cell
+    Geohash {
+        hash: String,
+        coordinates: Coordinates,
+    },
+    /// Center of the area of a full Open Location Code
+    PlusCode {
+        code: String,
+        coordinates: Coordinates,
+    },
+    /// Postal code within a country (ISO 3166-1 alpha-2), looked up by geocoding
+    Postal { code: String, country_code: String },
+    /// Place name, looked up by geocoding
+    Name(String),
+}
+
+impl LocationQuery {
+    /// Parse a location argument, resolving aliases from `favorites`
+    ///
+    /// Aliases take precedence over every other form.
+    pub fn parse(input: &str, favorites: &Favorites) -> Result<Self> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Err(invalid(input, "the location is empty"));
+        }
+        if let Some(location) = favorites.get(input) {
+            return Ok(Self::Favorite {
+                alias: input.to_string(),
+                location: Box::new(location.clone()),
+            });
+        }
+
+        if let Some(hash) = input
+            .strip_prefix("geohash:")
+            .or_else(|| input.strip_prefix("gh:"))
+        {
+            let coordinates =
+                decode_geohash(hash).ok_or_else(|| invalid(input, "not a valid geohash"))?;
+            return Ok(Self::Geohash {
+                hash: hash.to_ascii_lowercase(),
+                coordinates,
+            });
+        }
+        if let Some(coordinates) = decode_plus_code(input)? {
+            return Ok(Self::PlusCode {
+                code: input.to_ascii_uppercase(),
+                coordinates,
+            });
+        }
+        if let Some(coordinates) = parse_decimal(input)? {
+            return Ok(Self::Decimal(coordinates));
+        }
+        if let Some(coordinates) = parse_dms(input)? {
+            return Ok(Self::Dms(coordinates));
+        }
+        if looks_like_geohash(input)
+            && let Some(coordinates) = decode_geohash(input)
+        {
+            return Ok(Self::Geohash {
+                hash: input.to_string(),
+                coordinates,
+            });
+        }
+        if let Some((code, country_code)) = parse_postal(input) {
+            return Ok(Self::Postal { code, country_code });
+        }
+        Ok(Self::Name(input.to_string()))
+    }
+
+    /// Coordinates known without a geocoding call
+    pub fn coordinates(&self) -> Option<Coordinates> {
+        match self {
+            Self::Favorite { location, .. } => {
+                Some(Coordinates::new(location.latitude, location.longitude))
+            }
+            Self::Decimal(coordinates)
+            | Self::Dms(coordinates)
+            | Self::Geohash { coordinates, .. }
+            | Self::PlusCode { coordinates, .. } => Some(*coordinates),
+            Self::Postal { .. } | Self::Name(_) => None,
+        }
+    }
+
+    /// The location, if known without a geocoding call
+    ///
+    /// Decoded coordinates become a location named after the query.
+    pub fn location(&self) -> Option<Location> {
+        match self {
+            Self::Favorite { location, .. } => Some(location.as_ref().clone()),
+            _ => self
+                .coordinates()
+                .map(|coordinates| place(self.to_string(), coordinates)),
+        }
+    }
+
+    /// Geocoding request for postal codes and place names
+    pub fn geocoding_request(&self) -> Option<GeocodingRequest> {
+        match self {
+            Self::Postal { code, country_code } => {
+                Some(GeocodingRequest::new(code).country_code(country_code))
+            }
+            Self::Name(name) => Some(GeocodingRequest::new(name)),
+            _ => None,
+        }
+    }
+
+    /// Every location the query may mean, best match first (see [`rank`])
+    ///
+    /// Locally resolved queries have exactly one.
+    pub async fn candidates(
+        &self,
+        geocoding: &GeocodingClient,
+        language: Option<&str>,
+    ) -> Result<Vec<Location>> {
+        if let Some(location) = self.location() {
+            return Ok(vec![location]);
+        }
+        let mut request = self
+            .geocoding_request()
+            .expect("only geocoded queries have no location")
+            .count(MAX_CANDIDATES);
+        if let Some(language) = language {
+            request = request.language(language);
+        }
+        let mut candidates = geocoding.search(request).await?.results;
+        rank(&mut candidates);
+        Ok(candidates)
+    }
+
+    /// Resolve to a location, using the best geocoding match if needed
+    pub async fn resolve(
+        &self,
+        geocoding: &GeocodingClient,
+        language: Option<&str>,
+    ) -> Result<Location> {
+        self.candidates(geocoding, language)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or(OpenMeteoError::NoResults)
+    }
+}
+
+/// Order geocoding matches from most to least likely meant
+///
+/// Larger populations come first, so "Paris" is the one in France rather than
+/// Texas; ties (including unknown populations) keep the API's relevance order.
+pub fn rank(locations: &mut [Location]) {
+    locations.sort_by_key(|location| std::cmp::Reverse(location.population.unwrap_or(0)));
+}
+
+impl fmt::Display for LocationQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Favorite { alias, .. } => f.write_str(alias),
+            Self::Decimal(coordinates) | Self::Dms(coordinates) => {
+                write!(
+                    f,
+                    "{:.5}, {:.5}",
+                    coordinates.latitude, coordinates.longitude
+                )
+            }
+            Self::Geohash { hash, .. } => f.write_str(hash),
+            Self::PlusCode { code, .. } => f.write_str(code),
+            Self::Postal { code, country_code } => write!(f, "{} {}", code, country_code),
+            Self::Name(name) => f.write_str(name),
+        }
+    }
+}
+
+/// Parse without favorites
+impl FromStr for LocationQuery {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Self::parse(s, &Favorites::default()).map_err(|e| e.to_string())
+    }
+}
+
+/// A location with only a name and coordinates
+fn place(name: String, coordinates: Coordinates) -> Location {
+    Location {
+        id: 0,
+        name,
+        latitude: coordinates.latitude,
+        longitude: coordinates.longitude,
+        elevation: None,
+        timezone: None,
+        country_code: None,
+        country: None,
+        admin1: None,
+        admin2: None,
+        admin3: None,
+        admin4: None,
+        population: None,
+        postcodes: None,
+    }
+}
+
+fn invalid(input: &str, reason: &str) -> OpenMeteoError {
+    OpenMeteoError::InvalidParameter(format!("location '{}': {}", input, reason))
+}
+
+fn checked(input: &str, latitude: f64, longitude: f64) -> Result<Coordinates> {
+    if !(-90.0..=90.0).contains(&latitude) {
+        return Err(invalid(input, "latitude must be between -90 and 90"));
+    }
+    if !(-180.0..=180.0).contains(&longitude) {
+        return Err(invalid(input, "longitude must be between -180 and 180"));
+    }
+    Ok(Coordinates::new(latitude, longitude))
+}
+
+/// `52.52,13.41`, `52.52, 13.41` or `52.52 13.41`
+fn parse_decimal(input: &str) -> Result<Option<Coordinates>> {
+    let parts: Vec<&str> = if input.contains(',') {
+        input.split(',').map(str::trim).collect()
+    } else {
+        input.split_whitespace().collect()
+    };
+    let [latitude, longitude] = parts[..] else {
+        return Ok(None);
+    };
+    let number = |part: &str| part.parse::<f64>().ok().filter(|n| n.is_finite());
+    match (number(latitude), number(longitude)) {
+        (Some(latitude), Some(longitude)) => checked(input, latitude, longitude).map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// `52°31'N 13°24'E`, `52°31'12.5"N, 13°24'E`, `N52 31 E13 24` and the like
+fn parse_dms(input: &str) -> Result<Option<Coordinates>> {
+    enum Token {
+        Number(f64),
+        Hemisphere(char),
+    }
+
+    let mut tokens = Vec::new();
+    let mut number = String::new();
+    for c in input.chars().chain([' ']) {
+        if c.is_ascii_digit() || c == '.' {
+            number.push(c);
+            continue;
+        }
+        if !number.is_empty() {
+            let Ok(value) = number.parse() else {
+                return Ok(None);
+            };
+            tokens.push(Token::Number(value));
+            number.clear();
+        }
+        match c.to_ascii_uppercase() {
+            hemisphere @ ('N' | 'S' | 'E' | 'W') => tokens.push(Token::Hemisphere(hemisphere)),
+            '°' | '\'' | '"' | '′' | '″' | '’' | '”' | ',' => {}
+            c if c.is_whitespace() => {}
+            _ => return Ok(None),
+        }
+    }
+
+    let hemispheres: Vec<usize> = tokens
+        .iter()
+        .enumerate()
+        .filter(|(_, token)| matches!(token, Token::Hemisphere(_)))
+        .map(|(i, _)| i)
+        .collect();
+    let [first, second] = hemispheres[..] else {
+        return Ok(None);
+    };
+    // Hemisphere letters either lead or trail both values
+    let split = if first == 0 {
+        second
+    } else if second == tokens.len() - 1 {
+        first + 1
+    } else {
+        return Ok(None);
+    };
+
+    let mut latitude = None;
+    let mut longitude = None;
+    for group in [&tokens[..split], &tokens[split..]] {
+        let mut hemisphere = None;
+        let mut parts = Vec::new();
+        for token in group {
+            match token {
+                Token::Hemisphere(h) => hemisphere = Some(*h),
+                Token::Number(n) => parts.push(*n),
+            }
+        }
+        let (Some(hemisphere), 1..=3) = (hemisphere, parts.len()) else {
+            return Ok(None);
+        };
+        if parts[1..].iter().any(|part| *part >= 60.0) {
+            return Err(invalid(input, "minutes and seconds must be below 60"));
+        }
+        let degrees = parts
+            .iter()
+            .zip([1.0, 60.0, 3600.0])
+            .map(|(part, divisor)| part / divisor)
+            .sum::<f64>();
+        let slot = match hemisphere {
+            'N' | 'S' => &mut latitude,
+            _ => &mut longitude,
+        };
+        if slot.is_some() {
+            return Err(invalid(input, "expected one of N/S and one of E/W"));
+        }
+        *slot = Some(if matches!(hemisphere, 'S' | 'W') {
+            -degrees
+        } else {
+            degrees
+        });
+    }
+    match (latitude, longitude) {
+        (Some(latitude), Some(longitude)) => checked(input, latitude, longitude).map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// Bare geohashes need a digit and a letter, so place names such as "dresden"
+/// (all valid geohash letters) stay names
+fn looks_like_geohash(input: &str) -> bool {
+    (5..=12).contains(&input.len())
+        && input.chars().all(|c| GEOHASH_ALPHABET.contains(c))
+        && input.chars().any(|c| c.is_ascii_digit())
+        && input.chars().any(|c| c.is_ascii_lowercase())
+}
+
+/// Center of a geohash cell
+fn decode_geohash(hash: &str) -> Option<Coordinates> {
+    if hash.is_empty() || hash.len() > 12 {
+        return None;
+    }
+    let mut latitude = (-90.0, 90.0);
+    let mut longitude = (-180.0, 180.0);
+    let mut even = true;
+    for c in hash.chars() {
+        let value = GEOHASH_ALPHABET.find(c.to_ascii_lowercase())?;
+        for bit in (0..5).rev() {
+            // Bits alternate between longitude and latitude, longitude first
+            let range: &mut (f64, f64) = if even { &mut longitude } else { &mut latitude };
+            let middle = (range.0 + range.1) / 2.0;
+            if value >> bit & 1 == 1 {
+                range.0 = middle;
+            } else {
+                range.1 = middle;
+            }
+            even = !even;
+        }
+    }
+    Some(Coordinates::new(
+        (latitude.0 + latitude.1) / 2.0,
+        (longitude.0 + longitude.1) / 2.0,
+    ))
+}
+
+/// Center of the area of a full plus code, `None` if `input` is not one
+///
+/// Short codes (fewer than 8 digits before the `+`) are errors, since they
+/// need a reference location.
+fn decode_plus_code(input: &str) -> Result<Option<Coordinates>> {
+    let code = input.to_ascii_uppercase();
+    let is_plus_code = code.matches('+').count() == 1
+        && code
+            .chars()
+            .all(|c| c == '+' || c == '0' || PLUS_CODE_ALPHABET.contains(c));
+    if !is_plus_code {
+        return Ok(None);
+    }
+    let (head, tail) = code.split_once('+').expect("one '+'");
+    if head.len() < 8 {
+        return Err(invalid(
+            input,
+            "short plus codes need a reference location; use the full code",
+        ));
+    }
+
+    // Padding zeros complete an even number of digits, with nothing after the '+'
+    let digits = head.trim_end_matches('0');
+    let padded = digits.len() < head.len();
+    if head.len() != 8
+        || digits.contains('0')
+        || digits.len() % 2 == 1
+        || digits.is_empty()
+        || (padded && !tail.is_empty())
+        || tail.len() == 1
+    {
+        return Err(invalid(input, "not a valid plus code"));
+    }
+
+    let digits: Vec<usize> = digits
+        .chars()
+        .chain(tail.chars())
+        .take(15)
+        .map(|c| PLUS_CODE_ALPHABET.find(c).expect("checked alphabet"))
+        .collect();
+    if digits[0] > 8 || digits[1] > 17 {
+        return Err(invalid(input, "not a valid plus code"));
+    }
+
+    let mut latitude = -90.0;
+    let mut longitude = -180.0;
+    let mut latitude_step = 0.0;
+    let mut longitude_step = 0.0;
+    for (pair, resolution) in digits.chunks(2).take(5).zip(PLUS_CODE_PAIR_RESOLUTIONS) {
+        latitude += pair[0] as f64 * resolution;
+        longitude += pair[1] as f64 * resolution;
+        latitude_step = resolution;
+        longitude_step = resolution;
+    }
+    // Digits after the tenth refine a 5-row, 4-column grid
+    for digit in digits.iter().skip(10) {
+        latitude_step /= 5.0;
+        longitude_step /= 4.0;
+        latitude += (digit / 4) as f64 * latitude_step;
+        longitude += (digit % 4) as f64 * longitude_step;
+    }
+    Ok(Some(Coordinates::new(
+        latitude + latitude_step / 2.0,
+        longitude + longitude_step / 2.0,
+    )))
+}
+
+/// `10115 DE`, `10115, DE` or `DE-10115`
+fn parse_postal(input: &str) -> Option<(String, String)> {
+    let is_country = |s: &str| s.len() == 2 && s.chars().all(|c| c.is_ascii_uppercase());
+    let is_code = |s: &str| {
+        (2..=10).contains(&s.len())
+            && s.chars().any(|c| c.is_ascii_digit())
+            && s.chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == ' ' || c == '-')
+    };
+
+    if let Some((country, code)) = input.split_once('-')
+        && is_country(country)
+        && is_code(code)
+    {
+        return Some((code.to_string(), country.to_string()));
+    }
+    let (code, country) = input.rsplit_once([' ', ','])?;
+    let code = code.trim_end_matches([' ', ',']);
+    (is_country(country) && is_code(code)).then(|| (code.to_string(), country.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> LocationQuery {
+        input.parse().unwrap()
+    }
+
+    fn assert_near(coordinates: Option<Coordinates>, latitude: f64, longitude: f64) {
+        let coordinates = coordinates.unwrap();
+        assert!(
+            (coordinates.latitude - latitude).abs() < 1e-4
+                && (coordinates.longitude - longitude).abs() < 1e-4,
+            "{:?} is not near ({}, {})",
+            coordinates,
+            latitude,
+            longitude
+        );
+    }
+
+    #[test]
+    fn test_decimal_and_dms() {
+        for input in ["52.52,13.41", "52.52, 13.41", " 52.52 13.41 "] {
+            let query = parse(input);
+            assert!(matches!(query, LocationQuery::Decimal(_)), "{}", input);
+            assert_near(query.coordinates(), 52.52, 13.41);
+        }
+        assert_near(parse("-33.87,151.21").coordinates(), -33.87, 151.21);
+        assert!("91,0".parse::<LocationQuery>().is_err());
+
+        for input in [
+            "52°31'N 13°24'E",
+            "52°31′N, 13°24′E",
+            "N52 31 E13 24",
+            "52 31 n 13 24 e",
+        ] {
+            let query = parse(input);
+            assert!(matches!(query, LocationQuery::Dms(_)), "{}", input);
+            assert_near(query.coordinates(), 52.516_667, 13.4);
+        }
+        assert_near(
+            parse("33°52'10\"S 151°12'30\"E").coordinates(),
+            -33.869_444,
+            151.208_333,
+        );
+        assert_near(parse("13°24'E 52°31'N").coordinates(), 52.516_667, 13.4);
+        assert!("52°61'N 13°24'E".parse::<LocationQuery>().is_err());
+        assert!("52°31'N 13°24'S".parse::<LocationQuery>().is_err());
+    }
+
+    #[test]
+    fn test_geohash_and_plus_code() {
+        let query = parse("ezs42");
+        assert!(matches!(query, LocationQuery::Geohash { .. }));
+        assert_near(query.coordinates(), 42.604_980, -5.603_027);
+        assert_near(parse("geohash:gcpvj").coordinates(), 51.525_879, -0.109_863);
+        assert!("geohash:abc".parse::<LocationQuery>().is_err());
+
+        let query = parse("8FVC9G8F+6X");
+        assert!(matches!(query, LocationQuery::PlusCode { .. }));
+        assert_near(query.coordinates(), 47.365_562, 8.524_937);
+        assert_near(parse("8fvc9g8f+6xq").coordinates(), 47.365_588, 8.524_922);
+        assert_near(parse("8FVC0000+").coordinates(), 47.5, 8.5);
+        assert!("9G8F+6X".parse::<LocationQuery>().is_err());
+        assert!("8FVC9G8F+6".parse::<LocationQuery>().is_err());
+    }
+
+    #[test]
+    fn test_postal_names_and_aliases() {
+        for input in ["10115 DE", "10115, DE", "DE-10115"] {
+            let query = parse(input);
+            assert!(
+                matches!(&query, LocationQuery::Postal { code, country_code }
+                    if code == "10115" && country_code == "DE"),
+                "{}",
+                input
+            );
+            assert_eq!(query.to_string(), "10115 DE");
+            assert_eq!(
+                query.geocoding_request().unwrap().country_code.as_deref(),
+                Some("DE")
+            );
+        }
+        assert!(matches!(parse("SW1A 1AA GB"), LocationQuery::Postal { .. }));
+
+        // Words made of geohash letters, and names with a country, stay names
+        for input in [
+            "dresden",
+            "Berlin",
+            "Frankfurt am Main",
+            "Paris FR",
+            "New York",
+        ] {
+            let query = parse(input);
+            assert!(matches!(query, LocationQuery::Name(_)), "{}", input);
+            assert!(query.location().is_none());
+        }
+        assert!("  ".parse::<LocationQuery>().is_err());
+
+        let paris = |admin1: &str, population: Option<u64>| Location {
+            admin1: Some(admin1.to_string()),
+            population,
+            ..parse("0,0").location().unwrap()
+        };
+        let mut matches = [
+            paris("Texas", Some(25_171)),
+            paris("Kentucky", None),
+            paris("Île-de-France", Some(2_138_551)),
+            paris("Ontario", None),
+        ];
+        rank(&mut matches);
+        let order: Vec<&str> = matches.iter().filter_map(|l| l.admin1.as_deref()).collect();
+        assert_eq!(order, ["Île-de-France", "Texas", "Kentucky", "Ontario"]);
+
+        let mut favorites = Favorites::default();
+        let cabin = parse("63.4,13.08").location().unwrap();
+        assert_eq!(cabin.name, "63.40000, 13.08000");
+        favorites.insert("ezs42", cabin).unwrap();
+        let query = LocationQuery::parse("ezs42", &favorites).unwrap();
+        assert!(matches!(query, LocationQuery::Favorite { .. }));
+        assert_near(query.coordinates(), 63.4, 13.08);
+    }
+}
@@ -0,0 +1,317 @@
+//! Photovoltaic yield estimated from tilted irradiance forecasts
+//!
+//! Open-Meteo computes `global_tilted_irradiance` (GTI) for a panel orientation
+//! given by [`ForecastRequest::tilt`] and [`ForecastRequest::azimuth`]. A
+//! [`PvSystem`] turns GTI and air temperature into AC energy with a simple
+//! PVWatts-style model: output scales with irradiance, drops with cell
+//! temperature (estimated from NOCT) and is reduced by fixed system losses.
+//! That is accurate enough to decide when to charge a battery, not to bill a
+//! customer.
+//!
+//! ```no_run
+//! # async fn run() -> tiny_weather::client::Result<()> {
+//! use tiny_weather::client::ForecastClient;
+//! use tiny_weather::client::types::ForecastRequest;
+//! use tiny_weather::solar::{self, PvSystem};
+//!
+//! let request = ForecastRequest::new(52.52, 13.41)
+//!     .hourly(&[solar::GLOBAL_TILTED_IRRADIANCE, "temperature_2m"])
+//!     .tilt(35.0)
+//!     .azimuth(0.0);
+//! let mut forecast = ForecastClient::new().get(request).await?;
+//!
+//! forecast.add_pv_yield(&PvSystem::new(6.5).losses(0.12))?;
+//! let today = forecast.daily.as_ref().unwrap().variables[solar::PV_DAILY_ENERGY][0];
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`ForecastRequest::tilt`]: crate::client::types::ForecastRequest::tilt
+//! [`ForecastRequest::azimuth`]: crate::client::types::ForecastRequest::azimuth
+
+use std::collections::HashMap;
+
+use chrono::Duration;
+
+use crate::aggregate::Aggregation;
+use crate::client::error::{OpenMeteoError, Result};
+use crate::client::types::forecast::{ForecastResponse, HourlyData};
+use crate::client::types::units::Unit;
+use crate::derived::TEMPERATURE;
+use crate::frame::parse_time;
+
+/// Tilted irradiance input variable (W/m²)
+pub const GLOBAL_TILTED_IRRADIANCE: &str = "global_tilted_irradiance";
+/// Hourly column of estimated energy per time step (kWh)
+pub const PV_ENERGY: &str = "pv_energy";
+/// Daily column of estimated energy (kWh), named like other daily sums
+pub const PV_DAILY_ENERGY: &str = "pv_energy_sum";
+
+/// Irradiance of standard test conditions (W/m²)
+const STC_IRRADIANCE: f64 = 1000.0;
+/// Cell temperature of standard test conditions (°C)
+const STC_TEMPERATURE: f64 = 25.0;
+
+/// Panel array and inverter, as rated at standard test conditions
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PvSystem {
+    /// Rated DC power (kWp)
+    pub peak_power: f64,
+    /// Fraction of energy lost to wiring, inverter, soiling and mismatch (0–1)
+    pub losses: f64,
+    /// Relative power change per °C of cell temperature above 25 °C
+    pub temperature_coefficient: f64,
+    /// Nominal operating cell temperature (°C at 800 W/m² and 20 °C air)
+    pub noct: f64,
+}
+
+impl Default for PvSystem {
+    fn default() -> Self {
+        Self {
+            peak_power: 1.0,
+            losses: 0.14,
+            temperature_coefficient: -0.004,
+            noct: 45.0,
+        }
+    }
+}
+
+impl PvSystem {
+    /// Create a system of the given peak power with typical crystalline silicon defaults
+    pub fn new(peak_power: f64) -> Self {
+        Self {
+            peak_power,
+            ..Self::default()
+        }
+    }
+
+    /// Set the fraction of energy lost (default 0.14)
+    pub fn losses(mut self, losses: f64) -> Self {
+        self.losses = losses;
+        self
+    }
+
+    /// Set the power temperature coefficient per °C (default −0.004)
+    pub fn temperature_coefficient(mut self, coefficient: f64) -> Self {
+        self.temperature_coefficient = coefficient;
+        self
+    }
+
+    /// Set the nominal operating cell temperature (default 45 °C)
+    pub fn noct(mut self, noct: f64) -> Self {
+        self.noct = noct;
+        self
+    }
+
+    /// Cell temperature (°C) for an air temperature (°C) and irradiance (W/m²)
+    pub fn cell_temperature(&self, temperature: f64, irradiance: f64) -> f64 {
+        temperature + (self.noct - 20.0) / 800.0 * irradiance
+    }
+
+    /// AC power (kW) for an irradiance on the panel (W/m²) and air temperature (°C)
+    pub fn power(&self, irradiance: f64, temperature: f64) -> f64 {
+        let irradiance = irradiance.max(0.0);
+        let cell = self.cell_temperature(temperature, irradiance);
+        let derate = 1.0 + self.temperature_coefficient * (cell - STC_TEMPERATURE);
+        (self.peak_power * irradiance / STC_IRRADIANCE * derate * (1.0 - self.losses)).max(0.0)
+    }
+
+    /// Reject non-finite fields, which would turn every estimate into NaN
+    fn validate(&self) -> Result<()> {
+        let finite = [
+            self.peak_power,
+            self.losses,
+            self.temperature_coefficient,
+            self.noct,
+        ]
+        .iter()
+        .all(|v| v.is_finite());
+        if !finite || self.peak_power <= 0.0 || !(0.0..1.0).contains(&self.losses) {
+            return Err(OpenMeteoError::InvalidParameter(format!(
+                "invalid PV system: {:?}",
+                self
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl ForecastResponse {
+    /// Add estimated PV energy as an hourly `pv_energy` and a daily `pv_energy_sum` column
+    ///
+    /// Requires hourly `global_tilted_irradiance` and `temperature_2m`. Irradiance
+    /// is the mean over the preceding time step, so each value is the energy
+    /// produced in the step ending at its time; days are local calendar days.
+    /// The response is left unchanged on error.
+    pub fn add_pv_yield(&mut self, system: &PvSystem) -> Result<()> {
+        system.validate()?;
+        let hourly = self.hourly.as_ref().ok_or_else(|| {
+            OpenMeteoError::InvalidParameter("PV yield requires hourly data".to_string())
+        })?;
+        let input = |variable: &str| {
+            hourly.variables.get(variable).ok_or_else(|| {
+                OpenMeteoError::InvalidParameter(format!(
+                    "PV yield requires the hourly variable {}",
+                    variable
+                ))
+            })
+        };
+        let irradiance = input(GLOBAL_TILTED_IRRADIANCE)?;
+        let temperature = input(TEMPERATURE)?;
+        let temperature_unit = self
+            .hourly_units
+            .as_ref()
+            .and_then(|units| units.get(TEMPERATURE))
+            .and_then(|unit| Unit::parse(unit))
+            .unwrap_or(Unit::Celsius);
+
+        let step = match hourly.time.get(..2) {
+            Some([first, second]) => {
+                parse_time(second, self.utc_offset_seconds)?
+                    - parse_time(first, self.utc_offset_seconds)?
+            }
+            _ => Duration::hours(1),
+        };
+        let hours = step.num_seconds() as f64 / 3600.0;
+
+        let energy = irradiance
+            .iter()
+            .zip(temperature)
+            .map(|(irradiance, temperature)| {
+                let temperature = temperature_unit.convert((*temperature)?, Unit::Celsius)?;
+                Some(system.power((*irradiance)?, temperature) * hours)
+            })
+            .collect();
+        let energy = HourlyData {
+            time: hourly.time.clone(),
+            variables: HashMap::from([(PV_ENERGY.to_string(), energy)]),
+        };
+        let units = HashMap::from([(PV_ENERGY.to_string(), "kWh".to_string())]);
+        let daily =
+            self.daily_aggregates(&energy, Some(&units), &[(PV_ENERGY, Aggregation::Sum)])?;
+
+        if let Some(hourly) = &mut self.hourly {
+            hourly.variables.extend(energy.variables);
+        }
+        self.hourly_units
+            .get_or_insert_with(HashMap::new)
+            .extend(units);
+        // Replace rather than keep the daily total of an earlier system
+        if let Some(daily) = &mut self.daily {
+            daily.variables.remove(PV_DAILY_ENERGY);
+        }
+        self.insert_daily_aggregates(daily);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::types::forecast::fixtures;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_pv_power() {
+        let system = PvSystem::new(5.0).losses(0.1);
+
+        // Standard test conditions, with the cell at 25 °C
+        let ambient = 25.0 - 25.0 / 800.0 * 1000.0;
+        assert_close(system.power(1000.0, ambient), 4.5);
+
+        // A hot cell loses 0.4 %/°C: 25 °C air puts the cell at 56.25 °C
+        assert_close(system.cell_temperature(25.0, 1000.0), 56.25);
+        assert_close(system.power(1000.0, 25.0), 4.5 * (1.0 - 0.004 * 31.25));
+
+        assert_eq!(system.power(0.0, 10.0), 0.0);
+        assert_eq!(system.power(-5.0, 10.0), 0.0);
+    }
+
+    #[test]
+    fn test_add_pv_yield() {
+        let mut response = fixtures::forecast(serde_json::json!({
+            "hourly": {
+                "time": ["2024-06-21T11:00", "2024-06-21T12:00", "2024-06-22T12:00"],
+                "global_tilted_irradiance": [500.0, 800.0, null],
+                "temperature_2m": [68.0, 68.0, 68.0]
+            },
+            "hourly_units": {"global_tilted_irradiance": "W/m²", "temperature_2m": "°F"}
+        }));
+
+        let system = PvSystem::new(2.0).temperature_coefficient(0.0).losses(0.0);
+        response.add_pv_yield(&system).unwrap();
+
+        let energy = &response.hourly.as_ref().unwrap().variables[PV_ENERGY];
+        assert_eq!(energy, &vec![Some(1.0), Some(1.6), None]);
+        let daily = response.daily.as_ref().unwrap();
+        assert_eq!(daily.time, vec!["2024-06-21", "2024-06-22"]);
+        assert_close(daily.variables[PV_DAILY_ENERGY][0].unwrap(), 2.6);
+        assert_eq!(daily.variables[PV_DAILY_ENERGY][1], None);
+        assert_eq!(
+            response.daily_units.as_ref().unwrap()[PV_DAILY_ENERGY],
+            "kWh"
+        );
+
+        // The same hours as unix timestamps in UTC+2, with the API's daily rows
+        let mut response = fixtures::forecast(serde_json::json!({
+            "utc_offset_seconds": 7200,
+            "hourly": {
+                "time": ["1718960400", "1718964000", "1719050400"],
+                "global_tilted_irradiance": [500.0, 800.0, null],
+                "temperature_2m": [20.0, 20.0, 20.0]
+            },
+            "daily": {"time": ["1718920800", "1719007200"]}
+        }));
+        response.add_pv_yield(&system).unwrap();
+
+        let daily = response.daily.as_ref().unwrap();
+        assert_close(daily.variables[PV_DAILY_ENERGY][0].unwrap(), 2.6);
+        assert_eq!(daily.variables[PV_DAILY_ENERGY][1], None);
+    }
+
+    #[test]
+    fn test_add_pv_yield_requires_inputs() {
+        let mut response = fixtures::forecast(serde_json::json!({
+            "hourly": {"time": ["2024-06-21T12:00"], "temperature_2m": [20.0]}
+        }));
+
+        let error = response.add_pv_yield(&PvSystem::default()).unwrap_err();
+        assert!(matches!(error, OpenMeteoError::InvalidParameter(_)));
+        assert!(response.add_pv_yield(&PvSystem::new(0.0)).is_err());
+
+        // A failing daily rollup adds no hourly column either
+        let mut response = fixtures::forecast(serde_json::json!({
+            "hourly": {
+                "time": ["2024-06-21T12:00"],
+                "global_tilted_irradiance": [800.0],
+                "temperature_2m": [20.0]
+            },
+            "daily": {"time": ["not a date"]}
+        }));
+        assert!(response.add_pv_yield(&PvSystem::default()).is_err());
+        assert!(
+            !response
+                .hourly
+                .as_ref()
+                .unwrap()
+                .variables
+                .contains_key(PV_ENERGY)
+        );
+        assert!(
+            PvSystem::default()
+                .temperature_coefficient(f64::NAN)
+                .validate()
+                .is_err()
+        );
+        assert!(PvSystem::default().noct(f64::INFINITY).validate().is_err());
+        assert!(PvSystem::default().validate().is_ok());
+    }
+}
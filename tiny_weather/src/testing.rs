@@ -0,0 +1,716 @@
+//! In-process mock Open-Meteo server for integration tests
+//!
+//! Enabled with the `testing` cargo feature. The server listens on a random
+//! local port and emulates the forecast (`/v1/forecast`), archive
+//! (`/v1/archive`) and geocoding (`/v1/search`) endpoints:
+//! - Forecast and archive requests echo the requested coordinates, timezone and variables
+//!   back with deterministic values
+//! - Geocoding requests echo the searched name back as location results
+//! - Queued [`MockResponse`]s override the next request(s), e.g. to return
+//!   `{"error":true,"reason":...}` bodies, 429s or slow responses
+//!
+//! ```no_run
+//! # async fn example() -> std::io::Result<()> {
+//! use tiny_weather::client::types::ForecastRequest;
+//! use tiny_weather::testing::{MockResponse, MockServer};
+//!
+//! let server = MockServer::start().await?;
+//! server.enqueue(MockResponse::error(400, "Latitude must be in range of -90 to 90°."));
+//!
+//! let client = server.client();
+//! assert!(client.forecast().get(ForecastRequest::new(91.0, 0.0)).await.is_err());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use chrono::{Days, NaiveDate};
+use serde_json::{Value, json};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use crate::client::OpenMeteoClient;
+
+/// Path of the emulated forecast endpoint
+pub const FORECAST_PATH: &str = "/v1/forecast";
+
+/// Path of the emulated archive (historical weather) endpoint
+pub const ARCHIVE_PATH: &str = "/v1/archive";
+
+/// Path of the emulated geocoding endpoint
+pub const GEOCODING_PATH: &str = "/v1/search";
+
+/// Number of days returned when a request does not set `forecast_days`
+const DEFAULT_FORECAST_DAYS: u32 = 7;
+
+/// Number of results returned when a geocoding request does not set `count`
+const DEFAULT_GEOCODING_COUNT: u32 = 10;
+
+/// Upper bound on the size of a request head we are willing to buffer
+const MAX_REQUEST_BYTES: usize = 64 * 1024;
+
+/// A canned response returned by the [`MockServer`] instead of the echo handler
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: MockBody,
+    delay: Option<Duration>,
+    body_delay: Option<Duration>,
+}
+
+#[derive(Debug, Clone)]
+enum MockBody {
+    /// Use the built-in echo handler for the requested endpoint
+    Echo,
+    /// Return a fixed body
+    Fixed(String),
+}
+
+impl MockResponse {
+    /// Respond with the normal echo handler (useful combined with [`MockResponse::with_delay`])
+    pub fn echo() -> Self {
+        Self {
+            status: 200,
+            headers: Vec::new(),
+            body: MockBody::Echo,
+            delay: None,
+            body_delay: None,
+        }
+    }
+
+    /// Respond with a fixed JSON body and status 200
+    pub fn json(body: impl Into<String>) -> Self {
+        Self {
+            status: 200,
+            headers: Vec::new(),
+            body: MockBody::Fixed(body.into()),
+            delay: None,
+            body_delay: None,
+        }
+    }
+
+    /// Respond with an Open-Meteo style `{"error":true,"reason":...}` body
+    pub fn error(status: u16, reason: impl Into<String>) -> Self {
+        let body = json!({ "error": true, "reason": reason.into() }).to_string();
+        Self {
+            status,
+            headers: Vec::new(),
+            body: MockBody::Fixed(body),
+            delay: None,
+            body_delay: None,
+        }
+    }
+
+    /// Respond with `429 Too Many Requests`, optionally with a `Retry-After` header (seconds)
+    pub fn rate_limited(retry_after: Option<u64>) -> Self {
+        let mut response = Self::error(429, "Too many concurrent requests");
+        if let Some(seconds) = retry_after {
+            response = response.with_header("Retry-After", seconds.to_string());
+        }
+        response
+    }
+
+    /// Override the HTTP status code
+    pub fn with_status(mut self, status: u16) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Add a response header
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Wait before sending the response
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Send the headers, then wait before sending the body (a stalled download)
+    pub fn with_body_delay(mut self, delay: Duration) -> Self {
+        self.body_delay = Some(delay);
+        self
+    }
+}
+
+/// A request received by the [`MockServer`]
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    /// Request path without the query string
+    pub path: String,
+    /// Decoded query parameters in the order they were sent
+    pub query: Vec<(String, String)>,
+    /// Request headers (names lowercased)
+    pub headers: HashMap<String, String>,
+}
+
+impl RecordedRequest {
+    /// Get the first value of a query parameter
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    queue: VecDeque<MockResponse>,
+    requests: Vec<RecordedRequest>,
+}
+
+/// In-process HTTP server emulating the Open-Meteo APIs
+#[derive(Debug)]
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    handle: JoinHandle<()>,
+}
+
+impl MockServer {
+    /// Start a server on a random local port
+    pub async fn start() -> std::io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(State::default()));
+
+        let accept_state = Arc::clone(&state);
+        let handle = tokio::spawn(async move {
+            loop {
+                let (stream, _) = match listener.accept().await {
+                    Ok(conn) => conn,
+                    Err(e) => {
+                        warn!("Mock server accept failed: {}", e);
+                        continue;
+                    }
+                };
+                let state = Arc::clone(&accept_state);
+                tokio::spawn(async move {
+                    if let Err(e) = handle_connection(stream, state).await {
+                        debug!("Mock server connection error: {}", e);
+                    }
+                });
+            }
+        });
+
+        debug!("Mock Open-Meteo server listening on {}", addr);
+
+        Ok(Self {
+            addr,
+            state,
+            handle,
+        })
+    }
+
+    /// Base URL of the server (e.g., `http://127.0.0.1:12345`)
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Full URL of the emulated forecast endpoint
+    pub fn forecast_url(&self) -> String {
+        format!("{}{}", self.url(), FORECAST_PATH)
+    }
+
+    /// Full URL of the emulated geocoding endpoint
+    pub fn geocoding_url(&self) -> String {
+        format!("{}{}", self.url(), GEOCODING_PATH)
+    }
+
+    /// Create an [`OpenMeteoClient`] pointed at this server
+    pub fn client(&self) -> OpenMeteoClient {
+        OpenMeteoClient::with_base_host(self.url())
+    }
+
+    /// Queue a response for the next request (responses are used in FIFO order)
+    pub fn enqueue(&self, response: MockResponse) {
+        self.lock().queue.push_back(response);
+    }
+
+    /// All requests received so far
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.lock().requests.clone()
+    }
+
+    /// The most recent request received
+    pub fn last_request(&self) -> Option<RecordedRequest> {
+        self.lock().requests.last().cloned()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, state: Arc<Mutex<State>>) -> std::io::Result<()> {
+    let mut buf = Vec::with_capacity(1024);
+    let mut chunk = [0u8; 1024];
+    while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 || buf.len() > MAX_REQUEST_BYTES {
+            return Ok(());
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+
+    let head = String::from_utf8_lossy(&buf);
+    let Some(request) = parse_request(&head) else {
+        return write_response(&mut stream, 400, &[], "Bad Request", None).await;
+    };
+
+    let response = {
+        let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+        state.requests.push(request.clone());
+        state.queue.pop_front().unwrap_or_else(MockResponse::echo)
+    };
+
+    if let Some(delay) = response.delay {
+        tokio::time::sleep(delay).await;
+    }
+
+    let (status, body) = match response.body {
+        MockBody::Fixed(body) => (response.status, body),
+        MockBody::Echo => match echo(&request) {
+            Ok(body) if request.param("format") == Some("csv") => {
+                (response.status, render_csv(&body))
+            }
+            Ok(body) => (response.status, body.to_string()),
+            Err(reason) => (400, json!({ "error": true, "reason": reason }).to_string()),
+        },
+    };
+
+    write_response(
+        &mut stream,
+        status,
+        &response.headers,
+        &body,
+        response.body_delay,
+    )
+    .await
+}
+
+fn parse_request(head: &str) -> Option<RecordedRequest> {
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split_whitespace();
+    let _method = request_line.next()?;
+    let target = request_line.next()?;
+
+    let url = reqwest::Url::parse(&format!("http://localhost{}", target)).ok()?;
+    let query = url
+        .query_pairs()
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    let headers = lines
+        .take_while(|line| !line.is_empty())
+        .filter_map(|line| line.split_once(':'))
+        .map(|(k, v)| (k.trim().to_lowercase(), v.trim().to_string()))
+        .collect();
+
+    Some(RecordedRequest {
+        path: url.path().to_string(),
+        query,
+        headers,
+    })
+}
+
+async fn write_response(
+    stream: &mut TcpStream,
+    status: u16,
+    headers: &[(String, String)],
+    body: &str,
+    body_delay: Option<Duration>,
+) -> std::io::Result<()> {
+    let content_type = if body.starts_with('{') || body.starts_with('[') {
+        "application/json"
+    } else {
+        "text/csv"
+    };
+    let mut response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        reason_phrase(status),
+        content_type,
+        body.len()
+    );
+    for (name, value) in headers {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str("\r\n");
+
+    stream.write_all(response.as_bytes()).await?;
+    if let Some(delay) = body_delay {
+        stream.flush().await?;
+        tokio::time::sleep(delay).await;
+    }
+    stream.write_all(body.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => "Unknown",
+    }
+}
+
+fn echo(request: &RecordedRequest) -> Result<Value, String> {
+    match request.path.as_str() {
+        FORECAST_PATH | ARCHIVE_PATH => echo_forecast(request),
+        GEOCODING_PATH => Ok(echo_geocoding(request)),
+        path => Err(format!("Unknown endpoint {}", path)),
+    }
+}
+
+fn echo_forecast(request: &RecordedRequest) -> Result<Value, String> {
+    let latitudes = coordinate_list(request, "latitude")?;
+    let longitudes = coordinate_list(request, "longitude")?;
+    if latitudes.len() != longitudes.len() {
+        return Err(
+            "Parameter 'latitude' and 'longitude' must have the same number of elements"
+                .to_string(),
+        );
+    }
+
+    let mut forecasts = latitudes
+        .into_iter()
+        .zip(longitudes)
+        .map(|(latitude, longitude)| echo_location(request, latitude, longitude))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Multi-location requests return a JSON array
+    if forecasts.len() == 1 {
+        Ok(forecasts.remove(0))
+    } else {
+        Ok(Value::Array(forecasts))
+    }
+}
+
+fn coordinate_list(request: &RecordedRequest, name: &str) -> Result<Vec<f64>, String> {
+    request
+        .param(name)
+        .ok_or(format!("Parameter '{}' is missing", name))?
+        .split(',')
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| format!("Parameter '{}' is invalid: {}", name, value))
+        })
+        .collect()
+}
+
+fn echo_location(
+    request: &RecordedRequest,
+    latitude: f64,
+    longitude: f64,
+) -> Result<Value, String> {
+    if !(-90.0..=90.0).contains(&latitude) {
+        return Err("Latitude must be in range of -90 to 90°.".to_string());
+    }
+    if !(-180.0..=180.0).contains(&longitude) {
+        return Err("Longitude must be in range of -180 to 180°.".to_string());
+    }
+
+    let start_date = parse_param::<NaiveDate>(request, "start_date")?;
+    let end_date = parse_param::<NaiveDate>(request, "end_date")?;
+    let (days, date): (u32, Box<dyn Fn(u32) -> String>) = match (start_date, end_date) {
+        (Some(start), Some(end)) if start <= end => (
+            (end - start).num_days() as u32 + 1,
+            Box::new(move |day| (start + Days::new(day.into())).to_string()),
+        ),
+        (None, None) => {
+            let past_days = parse_param::<u32>(request, "past_days")?.unwrap_or(0);
+            let forecast_days =
+                parse_param::<u32>(request, "forecast_days")?.unwrap_or(DEFAULT_FORECAST_DAYS);
+            (past_days + forecast_days, Box::new(mock_date))
+        }
+        _ => return Err("Parameter 'start_date' and 'end_date' must be set together".to_string()),
+    };
+    let timezone = match request.param("timezone") {
+        None | Some("auto") => "GMT",
+        Some(tz) => tz,
+    };
+    let temperature_unit = match request.param("temperature_unit") {
+        Some("fahrenheit") => "°F",
+        _ => "°C",
+    };
+
+    let mut response = json!({
+        "latitude": latitude,
+        "longitude": longitude,
+        "elevation": request.param("elevation").and_then(|e| e.parse::<f64>().ok()).unwrap_or(38.0),
+        "generationtime_ms": 0.1,
+        "utc_offset_seconds": 0,
+        "timezone": timezone,
+        "timezone_abbreviation": timezone,
+    });
+
+    if let Some(hourly) = variables(request, "hourly") {
+        let time: Vec<String> = (0..days * 24)
+            .map(|hour| format!("{}T{:02}:00", date(hour / 24), hour % 24))
+            .collect();
+        let (series, units) = echo_series(&hourly, time.len(), temperature_unit);
+        response["hourly"] = series_with_time(time, series);
+        response["hourly_units"] = units;
+    }
+
+    if let Some(daily) = variables(request, "daily") {
+        let time: Vec<String> = (0..days).map(&date).collect();
+        let (series, units) = echo_series(&daily, time.len(), temperature_unit);
+        response["daily"] = series_with_time(time, series);
+        response["daily_units"] = units;
+    }
+
+    if let Some(current) = variables(request, "current") {
+        let mut data = serde_json::Map::new();
+        data.insert("time".to_string(), json!(format!("{}T00:00", date(0))));
+        data.insert("interval".to_string(), json!(900));
+        let mut units = serde_json::Map::new();
+        for (i, name) in current.iter().enumerate() {
+            data.insert(name.clone(), json!(mock_value(i, 0)));
+            units.insert(name.clone(), json!(mock_unit(name, temperature_unit)));
+        }
+        response["current"] = Value::Object(data);
+        response["current_units"] = Value::Object(units);
+    }
+
+    Ok(response)
+}
+
+/// Render echoed forecast JSON in the `format=csv` layout
+fn render_csv(body: &Value) -> String {
+    let forecasts = match body {
+        Value::Array(forecasts) => forecasts.clone(),
+        forecast => vec![forecast.clone()],
+    };
+    let multi = forecasts.len() > 1;
+    let prefix = |id: usize| {
+        if multi {
+            format!("{},", id)
+        } else {
+            String::new()
+        }
+    };
+    let id_header = if multi { "location_id," } else { "" };
+
+    let mut csv = format!(
+        "{}latitude,longitude,elevation,utc_offset_seconds,timezone,timezone_abbreviation\n",
+        id_header
+    );
+    for (id, forecast) in forecasts.iter().enumerate() {
+        csv.push_str(&format!(
+            "{}{},{},{},{},{},{}\n",
+            prefix(id),
+            forecast["latitude"],
+            forecast["longitude"],
+            forecast["elevation"],
+            forecast["utc_offset_seconds"],
+            forecast["timezone"].as_str().unwrap_or_default(),
+            forecast["timezone_abbreviation"]
+                .as_str()
+                .unwrap_or_default(),
+        ));
+    }
+
+    for section in ["hourly", "daily"] {
+        let Some(first) = forecasts.first().and_then(|f| f[section].as_object()) else {
+            continue;
+        };
+        let names: Vec<&String> = first.keys().filter(|k| *k != "time").collect();
+        let units = &forecasts[0][format!("{}_units", section)];
+        csv.push_str(&format!("\n{}time", id_header));
+        for name in &names {
+            csv.push_str(&format!(
+                ",{} ({})",
+                name,
+                units[name.as_str()].as_str().unwrap_or_default()
+            ));
+        }
+        csv.push('\n');
+
+        for (id, forecast) in forecasts.iter().enumerate() {
+            let data = &forecast[section];
+            let times = data["time"].as_array().cloned().unwrap_or_default();
+            for (step, time) in times.iter().enumerate() {
+                csv.push_str(&format!(
+                    "{}{}",
+                    prefix(id),
+                    time.as_str().unwrap_or_default()
+                ));
+                for name in &names {
+                    csv.push_str(&format!(",{}", data[name.as_str()][step]));
+                }
+                csv.push('\n');
+            }
+        }
+    }
+
+    csv
+}
+
+fn echo_geocoding(request: &RecordedRequest) -> Value {
+    let name = request.param("name").unwrap_or_default();
+    // The real API returns no `results` key for queries shorter than 2 characters
+    if name.chars().count() < 2 {
+        return json!({ "generationtime_ms": 0.1 });
+    }
+
+    let count = request
+        .param("count")
+        .and_then(|c| c.parse::<u32>().ok())
+        .unwrap_or(DEFAULT_GEOCODING_COUNT);
+    let country_code = request.param("countryCode").unwrap_or("XX");
+    let results: Vec<Value> = (0..count)
+        .map(|i| {
+            json!({
+                "id": 1000 + u64::from(i),
+                "name": name,
+                "latitude": 10.0 + f64::from(i),
+                "longitude": 20.0 + f64::from(i),
+                "elevation": 100.0,
+                "timezone": "GMT",
+                "country_code": country_code,
+                "country": "Mockland",
+                "population": 1_000_000 / (u64::from(i) + 1),
+            })
+        })
+        .collect();
+
+    json!({ "results": results, "generationtime_ms": 0.1 })
+}
+
+fn parse_param<T: std::str::FromStr>(
+    request: &RecordedRequest,
+    name: &str,
+) -> Result<Option<T>, String> {
+    request
+        .param(name)
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| format!("Parameter '{}' is invalid: {}", name, value))
+        })
+        .transpose()
+}
+
+fn variables(request: &RecordedRequest, name: &str) -> Option<Vec<String>> {
+    request.param(name).map(|value| {
+        value
+            .split(',')
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+            .collect()
+    })
+}
+
+fn echo_series(
+    names: &[String],
+    len: usize,
+    temperature_unit: &str,
+) -> (Vec<(String, Value)>, Value) {
+    let mut units = serde_json::Map::new();
+    units.insert("time".to_string(), json!("iso8601"));
+    let series = names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            units.insert(name.clone(), json!(mock_unit(name, temperature_unit)));
+            let values: Vec<f64> = (0..len).map(|step| mock_value(i, step)).collect();
+            (name.clone(), json!(values))
+        })
+        .collect();
+    (series, Value::Object(units))
+}
+
+fn series_with_time(time: Vec<String>, series: Vec<(String, Value)>) -> Value {
+    let mut data = serde_json::Map::new();
+    data.insert("time".to_string(), json!(time));
+    data.extend(series);
+    Value::Object(data)
+}
+
+/// Deterministic value for the given variable index and time step
+fn mock_value(variable: usize, step: usize) -> f64 {
+    (variable * 10 + step % 24) as f64 / 2.0
+}
+
+fn mock_unit(name: &str, temperature_unit: &str) -> &'static str {
+    let temperature = if temperature_unit == "°F" {
+        "°F"
+    } else {
+        "°C"
+    };
+    if name.starts_with("temperature")
+        || name.starts_with("apparent_temperature")
+        || name.starts_with("dew_point")
+    {
+        temperature
+    } else if name.starts_with("precipitation_probability")
+        || name.starts_with("relative_humidity")
+        || name.starts_with("cloud_cover")
+    {
+        "%"
+    } else if name.starts_with("precipitation")
+        || name.starts_with("rain")
+        || name.starts_with("showers")
+    {
+        "mm"
+    } else if name.starts_with("wind_speed") || name.starts_with("wind_gusts") {
+        "km/h"
+    } else if name.starts_with("weather_code") {
+        "wmo code"
+    } else {
+        ""
+    }
+}
+
+/// Date string for the given number of days after 2024-01-01
+fn mock_date(days: u32) -> String {
+    (NaiveDate::from_ymd_opt(2024, 1, 1).unwrap() + Days::new(days.into())).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_date() {
+        assert_eq!(mock_date(0), "2024-01-01");
+        assert_eq!(mock_date(59), "2024-02-29");
+        assert_eq!(mock_date(366), "2025-01-01");
+    }
+
+    #[test]
+    fn test_parse_request() {
+        let head = "GET /v1/forecast?latitude=52.52&hourly=temperature_2m%2Crain HTTP/1.1\r\nHost: localhost\r\nUser-Agent: test\r\n\r\n";
+        let request = parse_request(head).unwrap();
+        assert_eq!(request.path, FORECAST_PATH);
+        assert_eq!(request.param("latitude"), Some("52.52"));
+        assert_eq!(request.param("hourly"), Some("temperature_2m,rain"));
+        assert_eq!(request.headers.get("user-agent").unwrap(), "test");
+    }
+}
@@ -0,0 +1,535 @@
+//! End-to-end tests of the API clients against the bundled mock server
+
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::NaiveDate;
+
+use tiny_weather::client::types::{
+    ForecastRequest, GeocodingRequest, ResponseFormat, TemperatureUnit,
+};
+use tiny_weather::client::{ClientConfig, OpenMeteoClient, OpenMeteoError};
+use tiny_weather::query::{LocationQuery, MAX_CANDIDATES};
+use tiny_weather::testing::{
+    ARCHIVE_PATH, FORECAST_PATH, GEOCODING_PATH, MockResponse, MockServer,
+};
+
+#[tokio::test]
+async fn test_forecast_echoes_request() {
+    let server = MockServer::start().await.unwrap();
+    let client = server.client();
+
+    let forecast = client
+        .forecast()
+        .get(
+            ForecastRequest::new(52.52, 13.41)
+                .hourly(&["temperature_2m", "precipitation"])
+                .daily(&["temperature_2m_max"])
+                .current(&["temperature_2m"])
+                .timezone("Europe/Berlin")
+                .temperature_unit(TemperatureUnit::Fahrenheit)
+                .forecast_days(2),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(forecast.latitude, 52.52);
+    assert_eq!(forecast.longitude, 13.41);
+    assert_eq!(forecast.timezone, "Europe/Berlin");
+
+    let hourly = forecast.hourly.unwrap();
+    assert_eq!(hourly.time.len(), 48);
+    assert_eq!(hourly.variables["temperature_2m"].len(), 48);
+    assert_eq!(hourly.variables["precipitation"].len(), 48);
+    assert_eq!(forecast.hourly_units.unwrap()["temperature_2m"], "°F");
+
+    let daily = forecast.daily.unwrap();
+    assert_eq!(daily.time, vec!["2024-01-01", "2024-01-02"]);
+    assert!(
+        forecast
+            .current
+            .unwrap()
+            .variables
+            .contains_key("temperature_2m")
+    );
+
+    let request = server.last_request().unwrap();
+    assert_eq!(request.path, FORECAST_PATH);
+    assert_eq!(
+        request.param("hourly"),
+        Some("temperature_2m,precipitation")
+    );
+    assert_eq!(request.param("temperature_unit"), Some("fahrenheit"));
+    assert_eq!(request.param("forecast_days"), Some("2"));
+}
+
+#[tokio::test]
+async fn test_forecast_multiple_locations() {
+    let server = MockServer::start().await.unwrap();
+
+    let forecasts = server
+        .client()
+        .forecast()
+        .get_many(
+            ForecastRequest::new(52.52, 13.41)
+                .location(48.14, 11.58)
+                .hourly(&["temperature_2m"]),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(forecasts.len(), 2);
+    assert_eq!(forecasts[0].latitude, 52.52);
+    assert_eq!(forecasts[1].latitude, 48.14);
+
+    let request = server.last_request().unwrap();
+    assert_eq!(request.param("latitude"), Some("52.52,48.14"));
+    assert_eq!(request.param("longitude"), Some("13.41,11.58"));
+}
+
+#[tokio::test]
+async fn test_forecast_csv_multiple_locations() {
+    let server = MockServer::start().await.unwrap();
+
+    let forecasts = server
+        .client()
+        .forecast()
+        .get_many(
+            ForecastRequest::new(52.52, 13.41)
+                .location(48.14, 11.58)
+                .hourly(&["temperature_2m", "precipitation"])
+                .daily(&["temperature_2m_max"])
+                .forecast_days(1)
+                .format(ResponseFormat::Csv),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(server.last_request().unwrap().param("format"), Some("csv"));
+    assert_eq!(forecasts.len(), 2);
+    assert_eq!(forecasts[1].latitude, 48.14);
+
+    let hourly = forecasts[1].hourly.as_ref().unwrap();
+    assert_eq!(hourly.time.len(), 24);
+    assert_eq!(hourly.variables["precipitation"].len(), 24);
+    assert_eq!(
+        forecasts[1].hourly_units.as_ref().unwrap()["temperature_2m"],
+        "°C"
+    );
+    assert_eq!(
+        forecasts[0].daily.as_ref().unwrap().time,
+        vec!["2024-01-01"]
+    );
+}
+
+#[tokio::test]
+async fn test_forecast_download_raw_body() {
+    let server = MockServer::start().await.unwrap();
+    let path =
+        std::env::temp_dir().join(format!("tiny_weather_download_{}.csv", std::process::id()));
+    let request = ForecastRequest::new(52.52, 13.41)
+        .hourly(&["temperature_2m"])
+        .format(ResponseFormat::Csv);
+
+    let written = server
+        .client()
+        .forecast()
+        .download(&request, &path)
+        .await
+        .unwrap();
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(written, contents.len());
+    assert!(contents.starts_with("latitude,longitude"));
+    assert!(contents.contains("time,temperature_2m (°C)"));
+}
+
+#[tokio::test]
+async fn test_forecast_get_columnar() {
+    let server = MockServer::start().await.unwrap();
+    let request = ForecastRequest::new(52.52, 13.41)
+        .location(48.14, 11.58)
+        .hourly(&["temperature_2m", "precipitation"])
+        .past_days(2)
+        .forecast_days(1);
+
+    let body = server
+        .client()
+        .forecast()
+        .get_columnar(&request)
+        .await
+        .unwrap();
+    let forecasts = body.parse().unwrap();
+    assert!(body.as_bytes().starts_with(b"["));
+    let expected = server.client().forecast().get_many(request).await.unwrap();
+
+    assert_eq!(forecasts.len(), 2);
+    let hourly = forecasts[1].hourly.as_ref().unwrap();
+    assert_eq!(hourly.len(), 72);
+    assert_eq!(
+        hourly.column("temperature_2m").unwrap().len(),
+        expected[1].hourly.as_ref().unwrap().variables["temperature_2m"].len()
+    );
+    assert_eq!(
+        forecasts[1].to_response().hourly.unwrap().variables,
+        expected[1].hourly.as_ref().unwrap().variables
+    );
+
+    let csv = ForecastRequest::new(52.52, 13.41).format(ResponseFormat::Csv);
+    let error = server
+        .client()
+        .forecast()
+        .get_columnar(&csv)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, OpenMeteoError::InvalidParameter(_)));
+}
+
+#[tokio::test]
+async fn test_archive_date_range() {
+    let server = MockServer::start().await.unwrap();
+    let start = NaiveDate::from_ymd_opt(2023, 12, 30).unwrap();
+    let end = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+
+    let history = server
+        .client()
+        .archive()
+        .get(
+            ForecastRequest::new(52.52, 13.41)
+                .daily(&["temperature_2m_max"])
+                .date_range(start, end),
+        )
+        .await
+        .unwrap();
+
+    let daily = history.daily.unwrap();
+    assert_eq!(
+        daily.time,
+        vec!["2023-12-30", "2023-12-31", "2024-01-01", "2024-01-02"]
+    );
+
+    let request = server.last_request().unwrap();
+    assert_eq!(request.path, ARCHIVE_PATH);
+    assert_eq!(request.param("start_date"), Some("2023-12-30"));
+    assert_eq!(request.param("end_date"), Some("2024-01-02"));
+}
+
+#[tokio::test]
+async fn test_geocoding_echoes_request() {
+    let server = MockServer::start().await.unwrap();
+    let client = server.client();
+
+    let response = client
+        .geocoding()
+        .search(GeocodingRequest::new("Berlin").count(3).language("de"))
+        .await
+        .unwrap();
+
+    assert_eq!(response.results.len(), 3);
+    assert!(response.results.iter().all(|l| l.name == "Berlin"));
+
+    let request = server.last_request().unwrap();
+    assert_eq!(request.path, GEOCODING_PATH);
+    assert_eq!(request.param("language"), Some("de"));
+
+    let response = client
+        .geocoding()
+        .search(GeocodingRequest::new("10115").country_code("DE"))
+        .await
+        .unwrap();
+    assert_eq!(response.results[0].country_code.as_deref(), Some("DE"));
+    assert_eq!(
+        server.last_request().unwrap().param("countryCode"),
+        Some("DE")
+    );
+}
+
+#[tokio::test]
+async fn test_location_query_candidates() {
+    let server = MockServer::start().await.unwrap();
+    let client = server.client();
+
+    let query: LocationQuery = "Paris".parse().unwrap();
+    let candidates = query.candidates(client.geocoding(), None).await.unwrap();
+    assert_eq!(candidates.len(), usize::from(MAX_CANDIDATES));
+    assert!(
+        candidates
+            .windows(2)
+            .all(|pair| pair[0].population >= pair[1].population)
+    );
+    assert_eq!(
+        query.resolve(client.geocoding(), None).await.unwrap().id,
+        candidates[0].id
+    );
+
+    // Coordinates resolve without a request
+    let requests = server.requests().len();
+    let query: LocationQuery = "48.85,2.35".parse().unwrap();
+    let candidates = query.candidates(client.geocoding(), None).await.unwrap();
+    assert_eq!(candidates.len(), 1);
+    assert_eq!(server.requests().len(), requests);
+}
+
+#[tokio::test]
+async fn test_geocoding_short_query_has_no_results() {
+    let server = MockServer::start().await.unwrap();
+
+    let result = server
+        .client()
+        .geocoding()
+        .search(GeocodingRequest::new("B"))
+        .await;
+
+    assert!(matches!(result, Err(OpenMeteoError::NoResults)));
+}
+
+#[tokio::test]
+async fn test_forecast_error_reason() {
+    let server = MockServer::start().await.unwrap();
+
+    let result = server
+        .client()
+        .forecast()
+        .get(ForecastRequest::new(91.0, 13.41))
+        .await;
+
+    match result {
+        Err(OpenMeteoError::ApiError { status, reason, .. }) => {
+            assert_eq!(status, 400);
+            assert_eq!(reason, "Latitude must be in range of -90 to 90°.")
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_geocoding_error_reason() {
+    let server = MockServer::start().await.unwrap();
+    server.enqueue(MockResponse::error(
+        400,
+        "Parameter count must be between 1 and 100.",
+    ));
+
+    let result = server
+        .client()
+        .geocoding()
+        .search(GeocodingRequest::new("Berlin").count(0))
+        .await;
+
+    match result {
+        Err(e @ OpenMeteoError::ApiError { .. }) => {
+            assert!(e.is_client_error());
+            assert!(
+                e.to_string()
+                    .contains("Parameter count must be between 1 and 100.")
+            );
+            assert!(e.url().unwrap().contains("/v1/search"));
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_queued_responses() {
+    let server = MockServer::start().await.unwrap();
+    let client = server.client();
+    server.enqueue(MockResponse::rate_limited(Some(30)));
+    server.enqueue(MockResponse::error(503, "Service temporarily unavailable"));
+
+    let first = client
+        .forecast()
+        .get(ForecastRequest::new(52.52, 13.41))
+        .await;
+    let second = client
+        .forecast()
+        .get(ForecastRequest::new(52.52, 13.41))
+        .await;
+    let third = client
+        .forecast()
+        .get(ForecastRequest::new(52.52, 13.41))
+        .await;
+
+    let first = first.unwrap_err();
+    assert!(matches!(first, OpenMeteoError::RateLimited { .. }));
+    assert_eq!(first.retry_after(), Some(Duration::from_secs(30)));
+    assert!(first.is_retryable());
+
+    let second = second.unwrap_err();
+    assert!(matches!(second, OpenMeteoError::ServerError { .. }));
+    assert_eq!(second.status().unwrap(), 503);
+    assert!(second.is_retryable());
+
+    assert!(third.is_ok());
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn test_slow_response() {
+    let server = MockServer::start().await.unwrap();
+    server.enqueue(MockResponse::echo().with_delay(Duration::from_secs(5)));
+    let client = server.client();
+
+    let result = tokio::time::timeout(
+        Duration::from_millis(200),
+        client.forecast().get(ForecastRequest::new(52.52, 13.41)),
+    )
+    .await;
+
+    assert!(result.is_err(), "slow response should not complete");
+}
+
+#[tokio::test]
+async fn test_api_key_is_sent_and_redacted() {
+    let server = MockServer::start().await.unwrap();
+    let client = server.client().api_key("top-secret");
+    server.enqueue(MockResponse::error(400, "Invalid API key"));
+
+    let error = client
+        .forecast()
+        .get(ForecastRequest::new(52.52, 13.41))
+        .await
+        .unwrap_err();
+    client
+        .geocoding()
+        .search(GeocodingRequest::new("Berlin"))
+        .await
+        .unwrap();
+
+    assert!(!error.url().unwrap().contains("top-secret"));
+    assert!(!error.to_string().contains("top-secret"));
+    for request in server.requests() {
+        assert_eq!(request.param("apikey"), Some("top-secret"));
+    }
+}
+
+#[tokio::test]
+async fn test_client_config_read_timeout() {
+    let server = MockServer::start().await.unwrap();
+    server.enqueue(MockResponse::echo().with_delay(Duration::from_secs(5)));
+    let client = OpenMeteoClient::with_config(
+        ClientConfig::new()
+            .base_host(server.url())
+            .read_timeout(Duration::from_millis(100)),
+    )
+    .unwrap();
+
+    let error = client
+        .forecast()
+        .get(ForecastRequest::new(52.52, 13.41))
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, OpenMeteoError::Timeout { .. }));
+    assert!(error.is_retryable());
+}
+
+#[tokio::test]
+async fn test_client_config_body_read_timeout() {
+    let server = MockServer::start().await.unwrap();
+    server.enqueue(MockResponse::echo().with_body_delay(Duration::from_secs(5)));
+    let client = OpenMeteoClient::with_config(
+        ClientConfig::new()
+            .base_host(server.url())
+            .read_timeout(Duration::from_millis(100)),
+    )
+    .unwrap();
+
+    // The status line arrives in time; reading the body times out
+    let error = client
+        .forecast()
+        .get(ForecastRequest::new(52.52, 13.41))
+        .await
+        .unwrap_err();
+
+    assert!(
+        matches!(error, OpenMeteoError::Timeout { .. }),
+        "{:?}",
+        error
+    );
+}
+
+#[tokio::test]
+async fn test_client_config_user_agent() {
+    let server = MockServer::start().await.unwrap();
+    let client = OpenMeteoClient::with_config(
+        ClientConfig::new()
+            .base_host(server.url())
+            .user_agent("tiny-weather-tests/1.0"),
+    )
+    .unwrap();
+
+    client
+        .geocoding()
+        .search(GeocodingRequest::new("Berlin"))
+        .await
+        .unwrap();
+
+    let request = server.last_request().unwrap();
+    assert_eq!(request.headers["user-agent"], "tiny-weather-tests/1.0");
+}
+
+/// Log lines written by a test subscriber
+#[derive(Clone, Default)]
+struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl CapturedLogs {
+    fn take(&self) -> String {
+        String::from_utf8(std::mem::take(&mut *self.0.lock().unwrap())).unwrap()
+    }
+}
+
+impl Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_forecast_warns_about_distant_grid_point() {
+    let server = MockServer::start().await.unwrap();
+    let logs = CapturedLogs::default();
+    let writer = logs.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::WARN)
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+    // The test runtime is single-threaded, so the client logs to this subscriber
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    // A grid point about 53 km north of the requested place
+    let distant = r#"{"latitude":53.0,"longitude":13.41,"elevation":40.0,"generationtime_ms":0.1,
+        "utc_offset_seconds":0,"timezone":"GMT","timezone_abbreviation":"GMT"}"#;
+    server.enqueue(MockResponse::json(distant));
+    let forecast = server.client().forecast().clone();
+    forecast
+        .get(ForecastRequest::new(52.52, 13.41))
+        .await
+        .unwrap();
+    let warning = logs.take();
+    assert!(
+        warning.contains("WARN") && warning.contains("grid point (53, 13.41), 53.4 km away"),
+        "{}",
+        warning
+    );
+
+    // Nearby grid points, and distant ones under a raised threshold, are quiet
+    forecast
+        .get(ForecastRequest::new(52.52, 13.41))
+        .await
+        .unwrap();
+    server.enqueue(MockResponse::json(distant));
+    forecast
+        .snap_warning(100.0)
+        .get(ForecastRequest::new(52.52, 13.41))
+        .await
+        .unwrap();
+    assert_eq!(logs.take(), "");
+}